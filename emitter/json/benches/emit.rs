@@ -0,0 +1,83 @@
+//! Measures emitting into a `String` and streaming into a writer.
+//!
+//! Run with `cargo bench -p lazyjson-emitter-json --bench emit`. Both should
+//! scale linearly with the size of the tree, and streaming into a sink should
+//! not be slower than building the `String`.
+
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use lazyjson_core::treebuilder::{
+    node::{ArrayNode, ObjectNode},
+    Node,
+};
+use lazyjson_emitter_json::{EmitJson, EmitOptions};
+
+const RUNS: u32 = 5;
+
+fn main() {
+    println!(
+        "{:>8} {:>10} {:>12} {:>12} {:>12}",
+        "entries", "bytes", "string", "writer", "sink"
+    );
+
+    for entry_cnt in [10_000, 20_000, 40_000, 80_000] {
+        let node = create_node(entry_cnt);
+        let options = &EmitOptions::DEFAULT;
+
+        let len = node.emit_json_with(0, options).len();
+
+        let string = measure(|| {
+            node.emit_json_with(0, options);
+        });
+        let writer = measure(|| {
+            let mut out = Vec::with_capacity(len);
+            node.emit_json_to(&mut out, 0, options).unwrap();
+        });
+        let sink = measure(|| node.emit_json_to(io::sink(), 0, options).unwrap());
+
+        println!(
+            "{:>8} {:>10} {:>12?} {:>12?} {:>12?}",
+            entry_cnt, len, string, writer, sink
+        );
+    }
+}
+
+/// Creates an array of `entry_cnt` objects, that each contain a few strings
+/// (some of them need escaping), numbers and a nested array.
+fn create_node(entry_cnt: usize) -> Node {
+    let entries = (0..entry_cnt)
+        .map(|i| {
+            let entry = [
+                ("id", i.into()),
+                ("name", format!("entry \"{}\"\n", i).into()),
+                ("ratio", (i as f64 / 7.0).into()),
+                (
+                    "tags",
+                    ArrayNode::synthetic(vec!["a".into(), "b".into(), true.into()]).into(),
+                ),
+            ]
+            .into_iter()
+            .map(|(key, val)| (key.to_owned(), val))
+            .collect();
+
+            ObjectNode::synthetic(entry).into()
+        })
+        .collect();
+
+    ArrayNode::synthetic(entries).into()
+}
+
+fn measure(mut f: impl FnMut()) -> Duration {
+    let mut total = Duration::ZERO;
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        f();
+        total += start.elapsed();
+    }
+
+    total / RUNS
+}
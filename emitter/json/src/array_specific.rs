@@ -1,45 +1,119 @@
-use lazyjson_core::treebuilder::node::ArrayNode;
+use std::io::{self, Write};
 
-use crate::EmitJson;
+use lazyjson_core::treebuilder::{node::ArrayNode, Node};
+
+use crate::{EmitJson, EmitOptions};
 
 impl EmitJson for ArrayNode {
-    fn emit_json(&self, indent_level: usize) -> String {
-        if self.entries.len() == 0 {
-            return String::from("[]");
+    fn write_json_value<W: Write>(
+        &self,
+        w: &mut W,
+        indent_level: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()> {
+        let column = options.get_indentation_width(indent_level);
+
+        write_array(self, w, indent_level, column, options)
+    }
+}
+
+/// Writes the array, like [`EmitJson::write_json_value`]. `column` is the
+/// width of what is already written on the current line, for example the
+/// indentation and the key of an entry.
+pub(crate) fn write_array<W: Write>(
+    arr: &ArrayNode,
+    w: &mut W,
+    indent_level: usize,
+    column: usize,
+    options: &EmitOptions,
+) -> io::Result<()> {
+    if arr.entries.is_empty() {
+        return w.write_all(b"[]");
+    }
+
+    if let Some(packed) = get_packed(&arr.entries, indent_level, column, options)? {
+        return w.write_all(&packed);
+    }
+
+    let entry_indent_level = indent_level + 1;
+
+    w.write_all(b"[")?;
+
+    for (i, node) in arr.entries.iter().enumerate() {
+        if i > 0 {
+            w.write_all(b",")?;
         }
 
-        let entry_indent_level = indent_level + 1;
+        if !options.compact {
+            w.write_all(b"\n")?;
+            options.write_indentation(w, entry_indent_level)?;
+        }
 
-        let mut entries_str = String::new();
-        for i in 0..self.entries.len() {
-            let node = &self.entries[i];
+        node.write_json_value(w, entry_indent_level, options)?;
+    }
 
-            entries_str += &format!(
-                "\n{}",
-                lazyjson_core::emit::get_indentation(entry_indent_level)
-            );
-            entries_str += &node.emit_json(indent_level + 1);
+    if !options.compact {
+        w.write_all(b"\n")?;
+        options.write_indentation(w, indent_level)?;
+    }
 
-            if i < self.entries.len() - 1 {
-                entries_str += ",";
-            }
+    w.write_all(b"]")
+}
+
+/// Returns the array on one line, if it only contains scalars and fits into
+/// [`EmitOptions::line_width`] after `column`. Fails like
+/// [`EmitJson::write_json_value`] for the entries.
+fn get_packed(
+    nodes: &[Node],
+    indent_level: usize,
+    column: usize,
+    options: &EmitOptions,
+) -> io::Result<Option<Vec<u8>>> {
+    let line_width = match options.line_width.filter(|_| !options.compact) {
+        Some(line_width) => line_width,
+        None => return Ok(None),
+    };
+
+    let is_flat = nodes.iter().all(|node| match node {
+        Node::Array(a) => a.entries.is_empty(),
+        Node::Object(o) => o.entries.is_empty(),
+        _ => true,
+    });
+
+    if !is_flat {
+        return Ok(None);
+    }
+
+    let mut packed = vec![b'['];
+
+    for (i, node) in nodes.iter().enumerate() {
+        if i > 0 {
+            packed.extend_from_slice(b", ");
         }
 
-        format!(
-            "[{}\n{}]",
-            entries_str,
-            lazyjson_core::emit::get_indentation(indent_level)
-        )
+        node.write_json_value(&mut packed, indent_level + 1, options)?;
+    }
+
+    packed.push(b']');
+
+    // Only valid UTF-8 is written.
+    let width = std::str::from_utf8(&packed).unwrap().chars().count();
+
+    match width <= line_width.saturating_sub(column) {
+        true => Ok(Some(packed)),
+        false => Ok(None),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io;
+
     use lazyjson_core::treebuilder::Node;
 
     use crate::{
         testing::{create_arr, create_bool, create_null, create_num, create_str},
-        EmitJson,
+        EmitJson, EmitOptions, Indent,
     };
 
     #[test]
@@ -82,4 +156,72 @@ mod tests {
 ]"
         );
     }
+
+    #[test]
+    fn array_specific_compact() {
+        let arr: Node = create_arr(vec![
+            create_num("1").into(),
+            create_arr(vec![create_null().into()]).into(),
+        ])
+        .into();
+
+        assert_eq!(arr.emit_json_with(0, &EmitOptions::COMPACT), "[1,[null]]");
+    }
+
+    #[test]
+    fn array_specific_indent() {
+        let arr = create_arr(vec![create_arr(vec![create_bool(true).into()]).into()]);
+
+        let tabs = EmitOptions {
+            indent: Indent::Tabs,
+            ..EmitOptions::DEFAULT
+        };
+        let custom = EmitOptions {
+            indent: Indent::Custom(String::from(" ")),
+            ..EmitOptions::DEFAULT
+        };
+
+        assert_eq!(arr.emit_json_with(0, &tabs), "[\n\t[\n\t\ttrue\n\t]\n]");
+        assert_eq!(arr.emit_json_with(0, &custom), "[\n [\n  true\n ]\n]");
+    }
+
+    #[test]
+    fn array_specific_packed() {
+        let options = EmitOptions {
+            line_width: Some(16),
+            ..EmitOptions::DEFAULT
+        };
+
+        let arr = create_arr(vec![
+            create_arr(vec![create_num("1").into(), create_arr(Vec::new()).into()]).into(),
+            create_arr(vec![create_str("foo").into(), create_str("bar").into()]).into(),
+        ]);
+
+        // The second array is 18 chars wide with its indentation.
+        assert_eq!(
+            arr.emit_json_with(0, &options),
+            "[
+    [1, []],
+    [
+        \"foo\",
+        \"bar\"
+    ]
+]"
+        );
+    }
+
+    #[test]
+    fn array_specific_packed_canonical_out_of_range() {
+        let arr: Node = create_arr(vec![create_num("1e400").into()]).into();
+
+        let options = EmitOptions {
+            canonical: true,
+            line_width: Some(80),
+            ..EmitOptions::DEFAULT
+        };
+
+        let err = arr.emit_json_to(Vec::new(), 0, &options).unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
 }
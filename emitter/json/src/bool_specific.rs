@@ -1,14 +1,16 @@
+use std::io::{self, Write};
+
 use lazyjson_core::treebuilder::node::BoolNode;
 
-use crate::EmitJson;
+use crate::{EmitJson, EmitOptions};
 
 impl EmitJson for BoolNode {
-    fn emit_json(&self, _: usize) -> String {
+    fn write_json_value<W: Write>(&self, w: &mut W, _: usize, _: &EmitOptions) -> io::Result<()> {
         if self.val {
-            return String::from("true");
+            return w.write_all(b"true");
         }
 
-        String::from("false")
+        w.write_all(b"false")
     }
 }
 
@@ -0,0 +1,139 @@
+use lazyjson_core::treebuilder::AccessErr;
+
+use crate::{EmitJson, EmitOptions};
+
+/// Emits canonical JSON as specified by
+/// [RFC 8785 (JCS)](https://www.rfc-editor.org/rfc/rfc8785), with the
+/// [`EmitOptions::CANONICAL`]. Use [`EmitJson::emit_json_to`] with them to
+/// write it to a stream instead.
+///
+/// Numbers that can not be represented by an `f64` (`1e400`) are rejected with
+/// an error of the typ
+/// [`AccessErrTyp::NumberOutOfRange`](lazyjson_core::treebuilder::access_error::AccessErrTyp::NumberOutOfRange).
+pub trait EmitCanonicalJson {
+    fn emit_canonical_json(&self) -> Result<String, AccessErr>;
+}
+
+impl<T: EmitJson> EmitCanonicalJson for T {
+    fn emit_canonical_json(&self) -> Result<String, AccessErr> {
+        let mut json = Vec::new();
+
+        match self.write_json_value(&mut json, 0, &EmitOptions::CANONICAL) {
+            // Only valid UTF-8 is written.
+            Ok(()) => Ok(String::from_utf8(json).unwrap()),
+            // Writing to a `Vec` does not fail, so the error is the one of a
+            // number that is out of range.
+            Err(e) => Err(*e.into_inner().unwrap().downcast().unwrap()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io};
+
+    use lazyjson_core::treebuilder::{access_error::AccessErrTyp, Node};
+
+    use crate::{
+        testing::{create_arr, create_bool, create_null, create_num, create_obj, create_str},
+        EmitCanonicalJson, EmitJson, EmitOptions,
+    };
+
+    /// The example of section 3.2.2 of the RFC.
+    #[test]
+    fn rfc_example() {
+        let numbers = [
+            "333333333.33333329",
+            "1E30",
+            "4.50",
+            "2e-3",
+            "0.000000000000000000000000001",
+        ]
+        .iter()
+        .map(|n| create_num(n).into())
+        .collect();
+
+        let entries = HashMap::from([
+            ("numbers".to_owned(), create_arr(numbers).into()),
+            (
+                "string".to_owned(),
+                create_str("\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/").into(),
+            ),
+            (
+                "literals".to_owned(),
+                create_arr(vec![
+                    create_null().into(),
+                    create_bool(true).into(),
+                    create_bool(false).into(),
+                ])
+                .into(),
+            ),
+        ]);
+
+        let node: Node = create_obj(entries).into();
+
+        assert_eq!(
+            node.emit_canonical_json().unwrap(),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    /// The example of section 3.2.3 of the RFC.
+    #[test]
+    fn rfc_sorting() {
+        let keys = [
+            "\u{20ac}",
+            "\r",
+            "\u{fb33}",
+            "1",
+            "\u{1f600}",
+            "\u{0080}",
+            "\u{00f6}",
+        ];
+        let entries = keys
+            .iter()
+            .map(|key| (key.to_string(), create_null().into()))
+            .collect();
+
+        let node: Node = create_obj(entries).into();
+
+        assert_eq!(
+            node.emit_canonical_json().unwrap(),
+            "{\"\\r\":null,\"1\":null,\"\u{0080}\":null,\"\u{00f6}\":null,\"\u{20ac}\":null,\"\u{1f600}\":null,\"\u{fb33}\":null}"
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let node: Node = create_arr(vec![create_num("1e400").into()]).into();
+
+        assert_eq!(
+            node.emit_canonical_json().unwrap_err().typ,
+            AccessErrTyp::NumberOutOfRange { expected: "f64" }
+        );
+
+        let err = node
+            .emit_json_to(Vec::new(), 0, &EmitOptions::CANONICAL)
+            .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn streamed() {
+        let node = lazyjson_core::json!({"b": [1.50, "\u{1f600}"], "a": {"\u{fb33}": 1e2, "\u{1f600}": null}});
+
+        let mut out = Vec::new();
+        node.emit_json_to(&mut out, 0, &EmitOptions::CANONICAL)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"a\":{\"\u{1f600}\":null,\"\u{fb33}\":100},\"b\":[1.5,\"\u{1f600}\"]}"
+        );
+        assert_eq!(
+            node.emit_json_with(0, &EmitOptions::CANONICAL),
+            node.emit_canonical_json().unwrap()
+        );
+    }
+}
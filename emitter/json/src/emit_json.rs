@@ -1,3 +1,123 @@
+use std::io::{self, BufWriter, Write};
+
+use crate::EmitOptions;
+
 pub trait EmitJson {
-    fn emit_json(&self, indent_level: usize) -> String;
+    /// Emits the node with the [`EmitOptions::DEFAULT`].
+    fn emit_json(&self, indent_level: usize) -> String {
+        self.emit_json_with(indent_level, &EmitOptions::DEFAULT)
+    }
+    /// Emits the node, followed by a newline if
+    /// [`EmitOptions::trailing_newline`] is set.
+    ///
+    /// # Panics
+    ///
+    /// With [`EmitOptions::canonical`], if a number is out of the range of an
+    /// `f64`. Use [`EmitJson::emit_json_to`] or
+    /// [`EmitCanonicalJson`](crate::EmitCanonicalJson) to handle that.
+    fn emit_json_with(&self, indent_level: usize, options: &EmitOptions) -> String {
+        let mut json = Vec::new();
+
+        // Writing to a `Vec` does not fail, only canonical numbers can.
+        self.write_json_value(&mut json, indent_level, options)
+            .unwrap();
+
+        if options.trailing_newline {
+            json.push(b'\n');
+        }
+
+        // Only valid UTF-8 is written.
+        String::from_utf8(json).unwrap()
+    }
+    /// Writes the node to `w` while it is emitted, instead of building the
+    /// whole output in memory first. The writes are buffered, so `w` does not
+    /// need to be. The first error of `w` is returned, and the output is
+    /// incomplete then.
+    fn emit_json_to<W: Write>(
+        &self,
+        w: W,
+        indent_level: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+
+        self.write_json_value(&mut w, indent_level, options)?;
+
+        if options.trailing_newline {
+            w.write_all(b"\n")?;
+        }
+
+        w.flush()
+    }
+    /// Writes the node without the trailing newline, as the entry of an array
+    /// or object. The writes are not buffered.
+    fn write_json_value<W: Write>(
+        &self,
+        w: &mut W,
+        indent_level: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use lazyjson_core::treebuilder::{node::ArrayNode, Node};
+
+    use super::*;
+
+    /// Accepts `cap` bytes, and fails after that.
+    struct LimitedWriter {
+        written: Vec<u8>,
+        cap: usize,
+    }
+
+    impl Write for LimitedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.cap - self.written.len());
+
+            if len == 0 && !buf.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+            }
+
+            self.written.extend_from_slice(&buf[..len]);
+
+            Ok(len)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn emit_json_to_matches_string() {
+        let node = lazyjson_core::json!({"a": [1, "ü\n", null], "b": {"c": true}});
+        let options = EmitOptions {
+            trailing_newline: true,
+            ..EmitOptions::DEFAULT
+        };
+
+        let mut out = Vec::new();
+        node.emit_json_to(&mut out, 0, &options).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            node.emit_json_with(0, &options)
+        );
+    }
+
+    #[test]
+    fn emit_json_to_propagates_errors() {
+        let node: Node = ArrayNode::synthetic((0..10_000).map(|i| i.into()).collect()).into();
+
+        let mut w = LimitedWriter {
+            written: Vec::new(),
+            cap: 100,
+        };
+        let err = node
+            .emit_json_to(&mut w, 0, &EmitOptions::DEFAULT)
+            .unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+        assert_eq!(w.written.len(), 100);
+    }
 }
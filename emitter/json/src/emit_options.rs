@@ -0,0 +1,105 @@
+use std::io::{self, Write};
+
+use lazyjson_core::treebuilder::KeyOrder;
+
+/// How nodes are emitted by [`EmitJson::emit_json_with`](crate::EmitJson::emit_json_with).
+#[derive(Clone)]
+pub struct EmitOptions {
+    /// Escapes all non-ASCII characters as `\uXXXX` (characters outside of the
+    /// Basic Multilingual Plane as a surrogate pair), so the output is plain
+    /// ASCII.
+    pub ensure_ascii: bool,
+    /// What one level of indentation is made of.
+    pub indent: Indent,
+    /// Emits everything on one line, without indentation.
+    pub compact: bool,
+    /// Separates keys and values by `": "` instead of `":"`.
+    pub space_after_colon: bool,
+    pub key_order: KeyOrder,
+    /// Ends the output with a newline. Only added after the node that is
+    /// emitted, not after its entries.
+    pub trailing_newline: bool,
+    /// Arrays that only contain numbers, strings, booleans, nulls and empty
+    /// arrays or objects are emitted on one line if it is at most this many
+    /// chars long, including the indentation. `None` never packs arrays.
+    pub line_width: Option<usize>,
+    /// Formats numbers like ECMAScript does and sorts keys by their UTF-16
+    /// code units, as [RFC 8785 (JCS)](https://www.rfc-editor.org/rfc/rfc8785)
+    /// requires. Overrides [`EmitOptions::key_order`]. The output is only
+    /// canonical together with the other options of
+    /// [`EmitOptions::CANONICAL`].
+    ///
+    /// Numbers that can not be represented by an `f64` (`1e400`) fail the
+    /// write with an [`io::ErrorKind::InvalidData`] error, which wraps an
+    /// [`AccessErr`](lazyjson_core::treebuilder::AccessErr) of the typ
+    /// [`AccessErrTyp::NumberOutOfRange`](lazyjson_core::treebuilder::access_error::AccessErrTyp::NumberOutOfRange).
+    pub canonical: bool,
+}
+
+impl EmitOptions {
+    pub const DEFAULT: EmitOptions = EmitOptions {
+        ensure_ascii: false,
+        indent: Indent::Spaces(4),
+        compact: false,
+        space_after_colon: true,
+        key_order: KeyOrder::Sorted,
+        trailing_newline: false,
+        line_width: None,
+        canonical: false,
+    };
+    /// Emits the smallest output, without any whitespace.
+    pub const COMPACT: EmitOptions = EmitOptions {
+        compact: true,
+        space_after_colon: false,
+        ..EmitOptions::DEFAULT
+    };
+    /// Emits canonical JSON: compact, with only the characters escaped that
+    /// have to be, and with [`EmitOptions::canonical`]. The output of equal
+    /// documents is byte for byte the same, so it can be hashed or signed.
+    pub const CANONICAL: EmitOptions = EmitOptions {
+        canonical: true,
+        ..EmitOptions::COMPACT
+    };
+
+    pub(crate) fn write_indentation<W: Write>(
+        &self,
+        w: &mut W,
+        indent_level: usize,
+    ) -> io::Result<()> {
+        let indent = match &self.indent {
+            Indent::Spaces(width) => return write!(w, "{:1$}", "", width * indent_level),
+            Indent::Tabs => "\t",
+            Indent::Custom(indent) => indent,
+        };
+
+        for _ in 0..indent_level {
+            w.write_all(indent.as_bytes())?;
+        }
+
+        Ok(())
+    }
+    /// Returns the width of the indentation in chars.
+    pub(crate) fn get_indentation_width(&self, indent_level: usize) -> usize {
+        match &self.indent {
+            Indent::Spaces(width) => width * indent_level,
+            Indent::Tabs => indent_level,
+            Indent::Custom(indent) => indent.chars().count() * indent_level,
+        }
+    }
+    pub(crate) fn get_colon(&self) -> &'static str {
+        match self.space_after_colon {
+            true => ": ",
+            false => ":",
+        }
+    }
+}
+
+/// One level of indentation. Ignored by [`EmitOptions::compact`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs,
+    /// Any string, for example `"  "` or `"\t\t"`. It should only be made of
+    /// whitespace, otherwise the output is not valid JSON.
+    Custom(String),
+}
@@ -0,0 +1,74 @@
+use std::io::{self, Write};
+
+/// Escapes the string for a JSON string literal. `"`, `\` and control
+/// characters are escaped, using the short forms (`\n`) where they exist and
+/// lowercase `\u00xx` otherwise (as required by RFC 8785). With
+/// `ensure_ascii`, non-ASCII characters are escaped as well, as UTF-16 code
+/// units (`\ud83d\ude00`).
+pub fn escape(val: &str, ensure_ascii: bool) -> String {
+    let mut escaped = Vec::with_capacity(val.len());
+
+    // Writing to a `Vec` does not fail, and only valid UTF-8 is written.
+    write_escaped(&mut escaped, val, ensure_ascii).unwrap();
+
+    String::from_utf8(escaped).unwrap()
+}
+
+/// Writes the string like [`escape`] returns it. Runs of chars that are not
+/// escaped are written at once.
+pub fn write_escaped<W: Write>(w: &mut W, val: &str, ensure_ascii: bool) -> io::Result<()> {
+    // The start of the chars that were not written yet.
+    let mut start = 0;
+
+    for (i, c) in val.char_indices() {
+        let is_escaped = matches!(c, '"' | '\\') || c < ' ' || (ensure_ascii && !c.is_ascii());
+
+        if !is_escaped {
+            continue;
+        }
+
+        w.write_all(&val.as_bytes()[start..i])?;
+        start = i + c.len_utf8();
+
+        match c {
+            '"' => w.write_all(b"\\\"")?,
+            '\\' => w.write_all(b"\\\\")?,
+            '\u{8}' => w.write_all(b"\\b")?,
+            '\u{c}' => w.write_all(b"\\f")?,
+            '\n' => w.write_all(b"\\n")?,
+            '\r' => w.write_all(b"\\r")?,
+            '\t' => w.write_all(b"\\t")?,
+            c if c < ' ' => write!(w, "\\u{:04x}", c as u32)?,
+            c => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(w, "\\u{:04x}", unit)?;
+                }
+            }
+        }
+    }
+
+    w.write_all(&val.as_bytes()[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_control_chars() {
+        assert_eq!(
+            escape("a\"b\\c\n\u{1}\u{1f}", false),
+            "a\\\"b\\\\c\\n\\u0001\\u001f"
+        );
+    }
+
+    #[test]
+    fn escape_keeps_non_ascii() {
+        assert_eq!(escape("€/\u{7f}😀", false), "€/\u{7f}😀");
+    }
+
+    #[test]
+    fn escape_ensure_ascii() {
+        assert_eq!(escape("é€😀\n", true), "\\u00e9\\u20ac\\ud83d\\ude00\\n");
+    }
+}
@@ -1,7 +1,15 @@
+use std::io::{self, Write};
+
 use lazyjson_core::treebuilder::Node;
 
+mod emit_canonical_json;
 mod emit_json;
+mod emit_options;
+mod escape;
+pub use emit_canonical_json::EmitCanonicalJson;
 pub use emit_json::EmitJson;
+pub use emit_options::{EmitOptions, Indent};
+pub use lazyjson_core::treebuilder::KeyOrder;
 
 mod array_specific;
 mod bool_specific;
@@ -14,14 +22,19 @@ mod string_specific;
 mod testing;
 
 impl EmitJson for Node {
-    fn emit_json(&self, indent_level: usize) -> String {
+    fn write_json_value<W: Write>(
+        &self,
+        w: &mut W,
+        indent_level: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()> {
         match &self {
-            Node::Array(a) => a.emit_json(indent_level),
-            Node::Bool(b) => b.emit_json(indent_level),
-            Node::Null(n) => n.emit_json(indent_level),
-            Node::Number(n) => n.emit_json(indent_level),
-            Node::Object(o) => o.emit_json(indent_level),
-            Node::String(s) => s.emit_json(indent_level),
+            Node::Array(a) => a.write_json_value(w, indent_level, options),
+            Node::Bool(b) => b.write_json_value(w, indent_level, options),
+            Node::Null(n) => n.write_json_value(w, indent_level, options),
+            Node::Number(n) => n.write_json_value(w, indent_level, options),
+            Node::Object(o) => o.write_json_value(w, indent_level, options),
+            Node::String(s) => s.write_json_value(w, indent_level, options),
         }
     }
 }
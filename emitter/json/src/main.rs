@@ -1,7 +1,7 @@
 use std::{env, fs};
 
 use lazyjson_core::treebuilder::Config;
-use lazyjson_emitter_json::EmitJson;
+use lazyjson_emitter_json::{EmitJson, EmitOptions};
 
 fn main() -> Result<(), u8> {
     let args: Vec<String> = env::args().collect();
@@ -19,7 +19,11 @@ fn main() -> Result<(), u8> {
             return Err(1);
         }
         Ok(n) => {
-            fs::write(out_file_path, n.unwrap().emit_json(0)).unwrap();
+            let out_file = fs::File::create(out_file_path).expect("failed to create file");
+
+            n.unwrap()
+                .emit_json_to(out_file, 0, &EmitOptions::DEFAULT)
+                .expect("failed to write file");
         }
     }
 
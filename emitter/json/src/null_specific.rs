@@ -1,10 +1,12 @@
+use std::io::{self, Write};
+
 use lazyjson_core::treebuilder::node::NullNode;
 
-use crate::EmitJson;
+use crate::{EmitJson, EmitOptions};
 
 impl EmitJson for NullNode {
-    fn emit_json(&self, _: usize) -> String {
-        String::from("null")
+    fn write_json_value<W: Write>(&self, w: &mut W, _: usize, _: &EmitOptions) -> io::Result<()> {
+        w.write_all(b"null")
     }
 }
 
@@ -1,16 +1,125 @@
-use lazyjson_core::treebuilder::node::NumberNode;
+use std::io::{self, Write};
 
-use crate::EmitJson;
+use lazyjson_core::treebuilder::{
+    access_error::{AccessErr, AccessErrTyp},
+    node::NumberNode,
+};
+
+use crate::{EmitJson, EmitOptions};
 
 impl EmitJson for NumberNode {
-    fn emit_json(&self, _: usize) -> String {
-        self.val.to_string()
+    fn write_json_value<W: Write>(
+        &self,
+        w: &mut W,
+        _: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()> {
+        if !options.canonical {
+            return w.write_all(self.val.as_bytes());
+        }
+
+        match self.val.parse::<f64>() {
+            Ok(n) if n.is_finite() => w.write_all(format_es_number(n).as_bytes()),
+            _ => {
+                let err = AccessErr {
+                    typ: AccessErrTyp::NumberOutOfRange { expected: "f64" },
+                    from: self.from,
+                    to: self.to,
+                    provenance: self.provenance.as_deref().cloned(),
+                };
+
+                Err(io::Error::new(io::ErrorKind::InvalidData, err))
+            }
+        }
+    }
+}
+
+/// Formats the number like ECMAScript's `Number.prototype.toString` (as
+/// required by RFC 8785): the shortest digits that round trip, in plain
+/// notation for exponents from -6 to 20, and in exponential notation with an
+/// explicit sign otherwise (`1e+21`).
+fn format_es_number(n: f64) -> String {
+    if n == 0.0 {
+        return String::from("0");
+    }
+
+    // `{:e}` produces the shortest digits that round trip (`-1.5e-7`).
+    let formatted = format!("{:e}", n.abs());
+    let (mantissa, exp) = formatted.split_once('e').unwrap();
+    let exp = exp.parse::<i32>().unwrap();
+    let digits = get_even_on_tie(&mantissa.replace('.', ""), exp, n.abs());
+
+    let k = digits.len() as i32;
+    // The position of the decimal point, relative to the start of the digits.
+    let point = exp + 1;
+
+    let formatted = if k <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        format!(
+            "{}.{}",
+            &digits[..point as usize],
+            &digits[point as usize..]
+        )
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let sign = if exp < 0 { '-' } else { '+' };
+
+        match digits.split_at(1) {
+            (first, "") => format!("{}e{}{}", first, sign, exp.abs()),
+            (first, rest) => format!("{}.{}e{}{}", first, rest, sign, exp.abs()),
+        }
+    };
+
+    match n < 0.0 {
+        true => format!("-{}", formatted),
+        false => formatted,
+    }
+}
+
+/// If the value lies exactly halfway between the shortest digits and a
+/// neighbour with the same amount of digits, ECMAScript picks the even one,
+/// Rust not necessarily.
+fn get_even_on_tie(digits: &str, exp: i32, n: f64) -> String {
+    let (init, last) = digits.split_at(digits.len() - 1);
+    let last = last.parse::<u8>().unwrap();
+
+    if last % 2 == 0 {
+        return digits.to_owned();
+    }
+
+    // An `f64` has at most 767 significant decimal digits, so this is exact.
+    let exact = format!("{:.800e}", n);
+    let (exact_mantissa, exact_exp) = exact.split_once('e').unwrap();
+    let exact_digits = exact_mantissa.replace('.', "");
+    let exact_digits = exact_digits.trim_end_matches('0');
+
+    if exact_exp.parse::<i32>().unwrap() != exp {
+        return digits.to_owned();
+    }
+
+    let lower = format!("{}{}", init, last - 1);
+    let upper = format!("{}{}", init, last + 1);
+
+    let round_trips = |candidate: &str| {
+        let (first, rest) = candidate.split_at(1);
+
+        format!("{}.{}e{}", first, rest, exp).parse::<f64>() == Ok(n)
+    };
+
+    if exact_digits == format!("{}5", lower) && round_trips(&lower) {
+        lower
+    } else if last < 9 && exact_digits == format!("{}5", digits) && round_trips(&upper) {
+        upper
+    } else {
+        digits.to_owned()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::testing::create_num;
+    use crate::{testing::create_num, EmitCanonicalJson};
 
     use super::*;
 
@@ -22,4 +131,59 @@ mod tests {
             assert_eq!(num.emit_json(0), i.to_string());
         }
     }
+
+    /// The samples of appendix B of RFC 8785.
+    #[test]
+    fn format_es_number_rfc_samples() {
+        let samples: [(u64, &str); 25] = [
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x8000000000000001, "-5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0xffefffffffffffff, "-1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+            (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x41b3de4355555553, "333333333.3333332"),
+            (0x41b3de4355555554, "333333333.33333325"),
+            (0x41b3de4355555555, "333333333.3333333"),
+            (0x41b3de4355555556, "333333333.3333334"),
+            (0x41b3de4355555557, "333333333.33333343"),
+            (0xbecbf647612f3696, "-0.0000033333333333333333"),
+            (0x43143ff3c1cb0959, "1424953923781206.2"),
+            (0x3ff0000000000000, "1"),
+        ];
+
+        for (bits, expected) in samples {
+            assert_eq!(
+                format_es_number(f64::from_bits(bits)),
+                expected,
+                "{:x}",
+                bits
+            );
+        }
+    }
+
+    #[test]
+    fn number_specific_canonical() {
+        for (lexeme, expected) in [
+            ("4.50", "4.5"),
+            ("2e-3", "0.002"),
+            ("-0", "0"),
+            ("1E30", "1e+30"),
+        ] {
+            assert_eq!(create_num(lexeme).emit_canonical_json().unwrap(), expected);
+        }
+
+        assert!(create_num("1e400").emit_canonical_json().is_err());
+    }
 }
@@ -1,39 +1,76 @@
-use lazyjson_core::treebuilder::{node::ObjectNode, Node};
+use std::io::{self, Write};
 
-use crate::EmitJson;
+use lazyjson_core::treebuilder::{node::ObjectNode, KeyOrder, Node};
+
+use crate::{
+    array_specific::write_array,
+    escape::{escape, write_escaped},
+    EmitJson, EmitOptions,
+};
 
 impl EmitJson for ObjectNode {
-    fn emit_json(&self, indent_level: usize) -> String {
-        if self.entries.len() == 0 {
-            return String::from("{}");
+    fn write_json_value<W: Write>(
+        &self,
+        w: &mut W,
+        indent_level: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()> {
+        if self.entries.is_empty() {
+            return w.write_all(b"{}");
         }
 
         let entry_indent_level = indent_level + 1;
 
-        let mut entries = self.entries.iter().collect::<Vec<(&String, &Node)>>();
-        entries.sort_by_key(|&(key, _)| key);
+        w.write_all(b"{")?;
 
-        let mut entries_str = String::new();
+        let entries = match options.canonical {
+            true => {
+                let mut entries = self.entries_ordered(KeyOrder::None);
+                // RFC 8785 sorts by UTF-16 code units, which differs from the
+                // order of `str` for characters outside of the Basic
+                // Multilingual Plane.
+                entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
 
-        for i in 0..entries.len() {
-            let (key, node) = entries[i];
+                entries
+            }
+            false => self.entries_ordered(options.key_order),
+        };
 
-            entries_str += &format!(
-                "\n{}",
-                lazyjson_core::emit::get_indentation(entry_indent_level)
-            );
-            entries_str += &format!("\"{}\": {}", key, node.emit_json(entry_indent_level));
+        for (i, (key, node)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                w.write_all(b",")?;
+            }
 
-            if i < self.entries.len() - 1 {
-                entries_str += ",";
+            if !options.compact {
+                w.write_all(b"\n")?;
+                options.write_indentation(w, entry_indent_level)?;
             }
+
+            w.write_all(b"\"")?;
+            write_escaped(w, key, options.ensure_ascii)?;
+            w.write_all(b"\"")?;
+            w.write_all(options.get_colon().as_bytes())?;
+
+            match node {
+                // Packed arrays continue the line of the key.
+                Node::Array(a) if options.line_width.is_some() => {
+                    let column = options.get_indentation_width(entry_indent_level)
+                        + escape(key, options.ensure_ascii).chars().count()
+                        + 2
+                        + options.get_colon().len();
+
+                    write_array(a, w, entry_indent_level, column, options)?;
+                }
+                _ => node.write_json_value(w, entry_indent_level, options)?,
+            }
+        }
+
+        if !options.compact {
+            w.write_all(b"\n")?;
+            options.write_indentation(w, indent_level)?;
         }
 
-        format!(
-            "{{{}\n{}}}",
-            entries_str,
-            lazyjson_core::emit::get_indentation(indent_level)
-        )
+        w.write_all(b"}")
     }
 }
 
@@ -41,7 +78,12 @@ impl EmitJson for ObjectNode {
 mod tests {
     use std::collections::HashMap;
 
-    use crate::testing::{create_bool, create_null, create_obj};
+    use lazyjson_core::treebuilder::Config;
+
+    use crate::{
+        testing::{create_bool, create_null, create_obj},
+        Indent, KeyOrder,
+    };
 
     use super::*;
 
@@ -93,4 +135,114 @@ mod tests {
 }"
         );
     }
+
+    #[test]
+    fn object_specific_escaped_keys() {
+        let mut entries = HashMap::new();
+        entries.insert(String::from("a\"b\n"), create_null().into());
+
+        assert_eq!(
+            create_obj(entries).emit_json(0),
+            "{
+    \"a\\\"b\\n\": null
+}"
+        );
+    }
+
+    #[test]
+    fn object_specific_generated() {
+        let node = lazyjson_core::json!({"ports": [80, 443], "tls": null});
+
+        assert_eq!(
+            node.emit_json(0),
+            "{
+    \"ports\": [
+        80,
+        443
+    ],
+    \"tls\": null
+}"
+        );
+    }
+
+    #[test]
+    fn object_specific_key_order() {
+        let node = lazyjson_core::parse(r#"{"b": 1, "c": 2, "a": 3}"#, &Config::DEFAULT)
+            .unwrap()
+            .unwrap();
+
+        let source = EmitOptions {
+            key_order: KeyOrder::Source,
+            ..EmitOptions::COMPACT
+        };
+
+        assert_eq!(node.emit_json_with(0, &source), r#"{"b":1,"c":2,"a":3}"#);
+        assert_eq!(
+            node.emit_json_with(0, &EmitOptions::COMPACT),
+            r#"{"a":3,"b":1,"c":2}"#
+        );
+
+        // The value of `a` has the span of the variable definition.
+        let mut node = lazyjson_core::parse(r#"{let x = 5, "b": 1, "a": x}"#, &Config::DEFAULT)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(node.emit_json_with(0, &source), r#"{"b":1,"a":5}"#);
+
+        node.insert("0", lazyjson_core::json!(null)).unwrap();
+        node.insert("b", 2).unwrap();
+
+        assert_eq!(node.emit_json_with(0, &source), r#"{"b":2,"a":5,"0":null}"#);
+    }
+
+    #[test]
+    fn object_specific_options() {
+        let node = lazyjson_core::json!({"a": [1], "b": {}});
+
+        let options = EmitOptions {
+            compact: true,
+            trailing_newline: true,
+            ..EmitOptions::DEFAULT
+        };
+
+        assert_eq!(node.emit_json_with(0, &options), "{\"a\": [1],\"b\": {}}\n");
+
+        let options = EmitOptions {
+            space_after_colon: false,
+            trailing_newline: true,
+            indent: Indent::Spaces(2),
+            ..EmitOptions::DEFAULT
+        };
+
+        assert_eq!(
+            node.emit_json_with(0, &options),
+            "{
+  \"a\":[
+    1
+  ],
+  \"b\":{}
+}
+"
+        );
+    }
+
+    #[test]
+    fn object_specific_packed_after_key() {
+        let node = lazyjson_core::json!({"abcdefgh": [1, 2, 3]});
+
+        // `    "abcdefgh": [1, 2, 3]` is 25 chars wide.
+        let options = |line_width| EmitOptions {
+            line_width: Some(line_width),
+            ..EmitOptions::DEFAULT
+        };
+
+        assert_eq!(
+            node.emit_json_with(0, &options(25)),
+            "{\n    \"abcdefgh\": [1, 2, 3]\n}"
+        );
+        assert_eq!(
+            node.emit_json_with(0, &options(24)),
+            "{\n    \"abcdefgh\": [\n        1,\n        2,\n        3\n    ]\n}"
+        );
+    }
 }
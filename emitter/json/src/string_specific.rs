@@ -1,10 +1,19 @@
+use std::io::{self, Write};
+
 use lazyjson_core::treebuilder::node::StringNode;
 
-use crate::EmitJson;
+use crate::{escape::write_escaped, EmitJson, EmitOptions};
 
 impl EmitJson for StringNode {
-    fn emit_json(&self, _: usize) -> String {
-        format!("\"{}\"", self.val)
+    fn write_json_value<W: Write>(
+        &self,
+        w: &mut W,
+        _: usize,
+        options: &EmitOptions,
+    ) -> io::Result<()> {
+        w.write_all(b"\"")?;
+        write_escaped(w, &self.val, options.ensure_ascii)?;
+        w.write_all(b"\"")
     }
 }
 
@@ -22,4 +31,24 @@ mod tests {
             assert_eq!(str.emit_json(0), format!("\"str: {}\"", i));
         }
     }
+
+    #[test]
+    fn string_specific_escaped() {
+        let str = create_str("say \"hi\"\n\\ é");
+
+        assert_eq!(str.emit_json(0), "\"say \\\"hi\\\"\\n\\\\ é\"");
+    }
+
+    #[test]
+    fn string_specific_ensure_ascii() {
+        let options = EmitOptions {
+            ensure_ascii: true,
+            ..EmitOptions::DEFAULT
+        };
+
+        assert_eq!(
+            create_str("é😀").emit_json_with(0, &options),
+            "\"\\u00e9\\ud83d\\ude00\""
+        );
+    }
 }
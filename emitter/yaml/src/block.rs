@@ -0,0 +1,134 @@
+use std::io::{self, Write};
+
+use lazyjson_core::treebuilder::Node;
+
+use crate::{scalar, EmitOptions};
+
+/// The longest key, in chars, that YAML allows as an implicit key (`key:`).
+/// Longer keys are written as explicit keys (`? key`).
+const MAX_IMPLICIT_KEY_LEN: usize = 1024;
+
+/// Where a value is written, which decides what precedes it on its first
+/// line.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// The root of a document, nothing precedes it.
+    Document,
+    /// After the `key:` of a mapping that is indented by `indent`.
+    MapValue,
+    /// After the `-` of a sequence that is indented by `indent`.
+    SeqItem,
+}
+
+/// Writes the node including the newline after it. The key or dash it
+/// belongs to is already written, without the space after it.
+pub fn write_value<W: Write>(
+    w: &mut W,
+    node: &Node,
+    indent: usize,
+    ctx: Context,
+    options: &EmitOptions,
+) -> io::Result<()> {
+    let step = options.indent.max(1);
+
+    let is_block = match node {
+        Node::Array(a) => !a.entries.is_empty(),
+        Node::Object(o) => !o.entries.is_empty(),
+        Node::String(s) => scalar::is_literal(&s.val),
+        _ => false,
+    };
+
+    if !is_block {
+        if ctx != Context::Document {
+            w.write_all(b" ")?;
+        }
+
+        write_scalar(w, node)?;
+
+        return w.write_all(b"\n");
+    }
+
+    match (node, ctx) {
+        (Node::String(s), Context::Document) => scalar::write_literal(w, &s.val, step),
+        (Node::String(s), _) => {
+            w.write_all(b" ")?;
+            scalar::write_literal(w, &s.val, indent + step)
+        }
+        (_, Context::Document) => write_collection(w, node, 0, false, options),
+        (_, Context::MapValue) => {
+            w.write_all(b"\n")?;
+            write_collection(w, node, indent + step, false, options)
+        }
+        // The entries are aligned with the first one, which is written on the
+        // line of the dash.
+        (_, Context::SeqItem) => {
+            w.write_all(b" ")?;
+            write_collection(w, node, indent + 2, true, options)
+        }
+    }
+}
+
+/// Writes the entries of a non-empty array or object, each line indented by
+/// `indent`, except the first one with `is_first_inline`.
+fn write_collection<W: Write>(
+    w: &mut W,
+    node: &Node,
+    indent: usize,
+    is_first_inline: bool,
+    options: &EmitOptions,
+) -> io::Result<()> {
+    let write_indentation = |w: &mut W, i: usize| match i == 0 && is_first_inline {
+        true => Ok(()),
+        false => write!(w, "{:1$}", "", indent),
+    };
+
+    match node {
+        Node::Array(a) => {
+            for (i, entry) in a.entries.iter().enumerate() {
+                write_indentation(w, i)?;
+                w.write_all(b"-")?;
+                write_value(w, entry, indent, Context::SeqItem, options)?;
+            }
+        }
+        Node::Object(o) => {
+            for (i, (key, val)) in o.entries_ordered(options.key_order).into_iter().enumerate() {
+                let mut written_key = Vec::new();
+                scalar::write_str(&mut written_key, key)?;
+
+                write_indentation(w, i)?;
+
+                // Only valid UTF-8 is written.
+                if std::str::from_utf8(&written_key).unwrap().chars().count() > MAX_IMPLICIT_KEY_LEN
+                {
+                    w.write_all(b"? ")?;
+                    w.write_all(&written_key)?;
+                    write!(w, "\n{:1$}", "", indent)?;
+                } else {
+                    w.write_all(&written_key)?;
+                }
+
+                w.write_all(b":")?;
+                write_value(w, val, indent, Context::MapValue, options)?;
+            }
+        }
+        _ => unreachable!("only arrays and objects are collections"),
+    }
+
+    Ok(())
+}
+
+/// Writes a node that fits on one line, in flow style for empty arrays and
+/// objects.
+fn write_scalar<W: Write>(w: &mut W, node: &Node) -> io::Result<()> {
+    match node {
+        Node::Array(_) => w.write_all(b"[]"),
+        Node::Bool(b) => match b.val {
+            true => w.write_all(b"true"),
+            false => w.write_all(b"false"),
+        },
+        Node::Null(_) => w.write_all(b"null"),
+        Node::Number(n) => w.write_all(n.val.as_bytes()),
+        Node::Object(_) => w.write_all(b"{}"),
+        Node::String(s) => scalar::write_str(w, &s.val),
+    }
+}
@@ -0,0 +1,23 @@
+use lazyjson_core::treebuilder::KeyOrder;
+
+/// How nodes are emitted by [`EmitYaml::emit_yaml_with`](crate::EmitYaml::emit_yaml_with).
+#[derive(Clone)]
+pub struct EmitOptions {
+    /// The amount of spaces one level of indentation is made of. Entries of
+    /// sequences are always indented by two spaces relative to their `- `.
+    /// Values below `1` are treated as `1`.
+    pub indent: usize,
+    pub key_order: KeyOrder,
+    /// Emits each entry of a root array as its own document, separated by
+    /// `---`, like a stream of Kubernetes manifests. An empty root array
+    /// results in no documents. Other roots are emitted as a single document.
+    pub multi_document: bool,
+}
+
+impl EmitOptions {
+    pub const DEFAULT: EmitOptions = EmitOptions {
+        indent: 2,
+        key_order: KeyOrder::Sorted,
+        multi_document: false,
+    };
+}
@@ -0,0 +1,157 @@
+use std::io::{self, BufWriter, Write};
+
+use lazyjson_core::treebuilder::Node;
+
+use crate::{
+    block::{self, Context},
+    EmitOptions,
+};
+
+pub trait EmitYaml {
+    /// Emits the node with the [`EmitOptions::DEFAULT`].
+    fn emit_yaml(&self) -> String {
+        self.emit_yaml_with(&EmitOptions::DEFAULT)
+    }
+    fn emit_yaml_with(&self, options: &EmitOptions) -> String {
+        let mut yaml = Vec::new();
+
+        // Writing to a `Vec` does not fail.
+        self.emit_yaml_to(&mut yaml, options).unwrap();
+
+        // Only valid UTF-8 is written.
+        String::from_utf8(yaml).unwrap()
+    }
+    /// Writes the node to `w` as block-style YAML, ending with a newline. The
+    /// writes are buffered, so `w` does not need to be.
+    fn emit_yaml_to<W: Write>(&self, w: W, options: &EmitOptions) -> io::Result<()>;
+}
+
+impl EmitYaml for Node {
+    fn emit_yaml_to<W: Write>(&self, w: W, options: &EmitOptions) -> io::Result<()> {
+        let mut w = BufWriter::new(w);
+
+        match self {
+            Node::Array(a) if options.multi_document => {
+                for entry in a.entries.iter() {
+                    w.write_all(b"---\n")?;
+                    block::write_value(&mut w, entry, 0, Context::Document, options)?;
+                }
+            }
+            _ => block::write_value(&mut w, self, 0, Context::Document, options)?,
+        }
+
+        w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lazyjson_core::{json, treebuilder::Config};
+
+    use crate::KeyOrder;
+
+    use super::*;
+
+    #[test]
+    fn scalars() {
+        assert_eq!(json!(null).emit_yaml(), "null\n");
+        assert_eq!(json!(true).emit_yaml(), "true\n");
+        assert_eq!(json!(1.5).emit_yaml(), "1.5\n");
+        assert_eq!(json!("yes").emit_yaml(), "\"yes\"\n");
+        assert_eq!(json!([]).emit_yaml(), "[]\n");
+        assert_eq!(json!({}).emit_yaml(), "{}\n");
+    }
+
+    #[test]
+    fn nested() {
+        let node = json!({
+            "metadata": {"name": "web", "labels": {"app": "web"}},
+            "ports": [80, "443"],
+            "items": [{"a": 1, "b": [true, {}]}, [1, [2, 3]], []],
+            "on": null
+        });
+
+        assert_eq!(
+            node.emit_yaml(),
+            r#"items:
+  - a: 1
+    b:
+      - true
+      - {}
+  - - 1
+    - - 2
+      - 3
+  - []
+metadata:
+  labels:
+    app: web
+  name: web
+"on": null
+ports:
+  - 80
+  - "443"
+"#
+        );
+    }
+
+    #[test]
+    fn literal() {
+        let node = json!({"script": "echo a\necho b\n", "list": ["x\ny", "z"]});
+
+        assert_eq!(
+            node.emit_yaml(),
+            "list:\n  - |-\n    x\n    y\n  - z\nscript: |\n  echo a\n  echo b\n"
+        );
+        assert_eq!(json!("a\nb").emit_yaml(), "|-\n  a\n  b\n");
+    }
+
+    #[test]
+    fn long_keys() {
+        let key = "k".repeat(1100);
+        let node = json!([{"a": 1, (key.as_str()): {"b": [2]}}]);
+
+        assert_eq!(
+            node.emit_yaml(),
+            format!("- a: 1\n  ? {}\n  :\n    b:\n      - 2\n", key)
+        );
+
+        let key = "k".repeat(1024);
+
+        assert_eq!(
+            json!({(key.as_str()): 1}).emit_yaml(),
+            format!("{}: 1\n", key)
+        );
+    }
+
+    #[test]
+    fn options() {
+        let node = lazyjson_core::parse(
+            r#"[{"kind": "Service", "apiVersion": "v1"}, {"kind": "Pod", "spec": {"a": 1}}]"#,
+            &Config::DEFAULT,
+        )
+        .unwrap()
+        .unwrap();
+
+        let options = EmitOptions {
+            indent: 4,
+            key_order: KeyOrder::Source,
+            multi_document: true,
+        };
+
+        assert_eq!(
+            node.emit_yaml_with(&options),
+            "---\nkind: Service\napiVersion: v1\n---\nkind: Pod\nspec:\n    a: 1\n"
+        );
+        assert_eq!(json!([]).emit_yaml_with(&options), "");
+
+        // The value of `name` has the span of the variable definition.
+        let node = lazyjson_core::parse(
+            r#"{let app = "web", "kind": "Pod", "name": app}"#,
+            &Config::DEFAULT,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(node.emit_yaml_with(&options), "kind: Pod\nname: web\n");
+    }
+}
@@ -0,0 +1,7 @@
+mod block;
+mod emit_options;
+mod emit_yaml;
+mod scalar;
+pub use emit_options::EmitOptions;
+pub use emit_yaml::EmitYaml;
+pub use lazyjson_core::treebuilder::KeyOrder;
@@ -0,0 +1,256 @@
+use std::io::{self, Write};
+
+/// Strings that YAML 1.1 or 1.2 resolves to null, a bool, or a merge or
+/// value key, compared case-insensitively.
+const RESERVED: [&str; 12] = [
+    "~", "null", "true", "false", "yes", "no", "y", "n", "on", "off", "<<", "=",
+];
+
+/// Chars that have a meaning at the start of a plain scalar.
+const INDICATORS: &str = "-?:,[]{}#&*!|>'\"%@`";
+
+/// Writes the string as a plain scalar if it is read back as the same string,
+/// and as a double-quoted scalar otherwise.
+pub fn write_str<W: Write>(w: &mut W, val: &str) -> io::Result<()> {
+    match is_plain_safe(val) {
+        true => w.write_all(val.as_bytes()),
+        false => write_double_quoted(w, val),
+    }
+}
+
+/// Returns whether the string can be written as a plain scalar. Strings are
+/// quoted generously, for example everything that starts like a number, so
+/// `"1.2.3"` is quoted even though it would be read back as a string.
+pub fn is_plain_safe(val: &str) -> bool {
+    let first = match val.chars().next() {
+        Some(first) => first,
+        None => return false,
+    };
+
+    !(first.is_whitespace()
+        || val.ends_with(char::is_whitespace)
+        || INDICATORS.contains(first)
+        || val.starts_with("...")
+        || val.contains(": ")
+        || val.contains(" #")
+        || val.ends_with(':')
+        || val.chars().any(needs_escape)
+        || RESERVED.contains(&val.to_lowercase().as_str())
+        || is_number_like(val)
+        || is_timestamp_like(val))
+}
+
+/// Returns whether the string looks like a number in any of the notations of
+/// YAML 1.1 or 1.2, like `1_000`, `0x1f`, `1:30` or `.inf`.
+fn is_number_like(val: &str) -> bool {
+    let unsigned = val.strip_prefix(['+', '-']).unwrap_or(val);
+
+    if [".inf", ".nan"].contains(&unsigned.to_lowercase().as_str()) {
+        return true;
+    }
+
+    let mut chars = unsigned.chars();
+
+    let is_numeric_start = match (chars.next(), chars.next()) {
+        (Some(c), _) if c.is_ascii_digit() => true,
+        (Some('.'), Some(c)) => c.is_ascii_digit(),
+        _ => false,
+    };
+
+    is_numeric_start
+        && unsigned
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_.:+-".contains(c))
+}
+
+/// Returns whether the string starts like a YAML 1.1 timestamp, a date
+/// (`2001-12-14`) that is followed by nothing, or by a time after a `T` or
+/// whitespace (`2001-12-14 21:59:43.10 -5`).
+fn is_timestamp_like(val: &str) -> bool {
+    let is_digits =
+        |s: &str, lens: &[usize]| lens.contains(&s.len()) && s.chars().all(|c| c.is_ascii_digit());
+
+    let mut parts = val.splitn(3, '-');
+
+    let (year, month, rest) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(year), Some(month), Some(rest)) => (year, month, rest),
+        _ => return false,
+    };
+
+    let day_len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (day, time) = rest.split_at(day_len);
+
+    is_digits(year, &[4])
+        && is_digits(month, &[1, 2])
+        && is_digits(day, &[1, 2])
+        && (time.is_empty() || time.starts_with(['T', 't', ' ', '\t']))
+}
+
+/// Returns whether the char can not appear in a plain or literal scalar. That
+/// are all chars outside of YAML's printable set (`c-printable`), control
+/// chars, and the line breaks and byte order mark of YAML 1.1.
+fn needs_escape(c: char) -> bool {
+    let is_printable = matches!(
+        c,
+        ' '..='\u{7e}' | '\u{a0}'..='\u{d7ff}' | '\u{e000}'..='\u{fffd}' | '\u{10000}'..
+    );
+
+    !is_printable || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}')
+}
+
+pub fn write_double_quoted<W: Write>(w: &mut W, val: &str) -> io::Result<()> {
+    w.write_all(b"\"")?;
+
+    for c in val.chars() {
+        match c {
+            '"' => w.write_all(b"\\\"")?,
+            '\\' => w.write_all(b"\\\\")?,
+            '\n' => w.write_all(b"\\n")?,
+            '\r' => w.write_all(b"\\r")?,
+            '\t' => w.write_all(b"\\t")?,
+            c if needs_escape(c) => write!(w, "\\u{:04x}", c as u32)?,
+            c => write!(w, "{}", c)?,
+        }
+    }
+
+    w.write_all(b"\"")
+}
+
+/// Returns whether the string is written as a literal block scalar (`|`). It
+/// has to span multiple lines, and is quoted instead if it contains other
+/// control chars than tabs, or its first line that is not empty starts with a
+/// space (it would need an indentation indicator).
+pub fn is_literal(val: &str) -> bool {
+    let body = val.trim_end_matches('\n');
+    let is_multiline = body.contains('\n') || (!body.is_empty() && body.len() < val.len());
+
+    is_multiline
+        && !val
+            .chars()
+            .any(|c| c != '\n' && c != '\t' && needs_escape(c))
+        && !body
+            .split('\n')
+            .find(|line| !line.is_empty())
+            .is_some_and(|line| line.starts_with(' '))
+}
+
+/// Writes the header of the literal block scalar and its lines, each
+/// indented by `indent` spaces. Trailing newlines are kept by the chomping
+/// indicator.
+pub fn write_literal<W: Write>(w: &mut W, val: &str, indent: usize) -> io::Result<()> {
+    let body = val.trim_end_matches('\n');
+
+    let header: &[u8] = match val.len() - body.len() {
+        0 => b"|-\n",
+        1 => b"|\n",
+        _ => b"|+\n",
+    };
+
+    w.write_all(header)?;
+
+    for line in body.split('\n') {
+        if !line.is_empty() {
+            write!(w, "{:1$}{2}", "", indent, line)?;
+        }
+
+        w.write_all(b"\n")?;
+    }
+
+    for _ in 1..val.len() - body.len() {
+        w.write_all(b"\n")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn str(val: &str) -> String {
+        let mut out = Vec::new();
+        write_str(&mut out, val).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn plain() {
+        for val in ["foo", "nginx:1.25", "a b", "é", "a-", "x-1", "inf"] {
+            assert_eq!(str(val), val);
+        }
+    }
+
+    #[test]
+    fn ambiguous() {
+        for val in [
+            "", "yes", "No", "ON", "null", "~", "true", "<<", "1", "-1.5", "0x1f", "0o17", "1_000",
+            "1e3", "1:30", ".5", ".inf", "-.Inf", ".NaN", "1.2.3",
+        ] {
+            assert_eq!(str(val), format!("\"{}\"", val));
+        }
+    }
+
+    #[test]
+    fn indicators() {
+        for val in [
+            "*ref", "&anchor", "!tag", "- item", "?", ":", "#comment", "{a}", "[a]", "|", ">",
+            "'a'", "%TAG", "@a", "`a`", "...", "a: b", "a #b", "a:", " a", "a ",
+        ] {
+            assert_eq!(str(val), format!("\"{}\"", val));
+        }
+    }
+
+    #[test]
+    fn escaped() {
+        assert_eq!(str("a\"b\\c\u{1}\t\u{2028}"), r#""a\"b\\c\u0001\t\u2028""#);
+        assert_eq!(
+            str("\u{80}\u{85}\u{9f}\u{a0}\u{fffe}\u{ffff}\u{1f600}"),
+            "\"\\u0080\\u0085\\u009f\u{a0}\\ufffe\\uffff\u{1f600}\""
+        );
+    }
+
+    #[test]
+    fn timestamps() {
+        for val in [
+            "2001-12-14",
+            "2001-1-4",
+            "2001-12-14t21:59:43.10-05:00",
+            "2001-12-14 21:59:43.10 -5",
+            "2001-12-14\t21:59:43.10",
+        ] {
+            assert_eq!(str(val), format!("\"{}\"", val.replace('\t', "\\t")));
+        }
+
+        for val in ["v2001-12-14 21:59", "2001-12-145 a", "01-12-14 21:59"] {
+            assert_eq!(str(val), val);
+        }
+    }
+
+    #[test]
+    fn literal() {
+        let literal = |val: &str| {
+            let mut out = Vec::new();
+            write_literal(&mut out, val, 2).unwrap();
+
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(literal("a\n\n b"), "|-\n  a\n\n   b\n");
+        assert_eq!(literal("a\nb\n"), "|\n  a\n  b\n");
+        assert_eq!(literal("a\n\n\n"), "|+\n  a\n\n\n");
+    }
+
+    #[test]
+    fn not_literal() {
+        for val in ["a", "\n\n", " a\nb", "\n a\nb", "a\r\nb"] {
+            assert!(!is_literal(val), "{:?}", val);
+        }
+
+        for val in ["a\nb", "\na\n b", "a\tb\n"] {
+            assert!(is_literal(val), "{:?}", val);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Measures how parsing scales with the amount of variables and usages.
+//!
+//! Run with `cargo bench -p lazyjson --bench variables`. The time per variable
+//! should stay (roughly) constant when the amount of variables grows.
+
+use std::time::{Duration, Instant};
+
+use lazyjson::treebuilder::Config;
+
+const RUNS: u32 = 5;
+
+fn main() {
+    println!(
+        "{:>8} {:>8} {:>12} {:>14}",
+        "vars", "usages", "total", "per variable"
+    );
+
+    for var_cnt in [1_000, 2_000, 4_000, 8_000] {
+        let inp = create_inp(var_cnt, 4);
+        let elapsed = measure(&inp);
+
+        println!(
+            "{:>8} {:>8} {:>12?} {:>14?}",
+            var_cnt,
+            var_cnt * 4,
+            elapsed,
+            elapsed / var_cnt as u32,
+        );
+    }
+}
+
+/// Creates an object declaring `var_cnt` variables, that are each used
+/// `usages_per_var` times. Every variable is an object that contains an array,
+/// so deep-cloning the values on use would show.
+fn create_inp(var_cnt: usize, usages_per_var: usize) -> String {
+    let mut inp = String::from("{\n");
+
+    for i in 0..var_cnt {
+        inp += &format!(
+            "    let var_{} = {{ \"id\": {}, \"tags\": [\"a\", \"b\", \"c\", \"d\"] }},\n",
+            i, i
+        );
+    }
+
+    inp += "    \"usages\": [\n";
+
+    for _ in 0..usages_per_var {
+        for i in 0..var_cnt {
+            inp += &format!("        {{ \"value\": var_{} }},\n", i);
+        }
+    }
+
+    inp += "        null\n    ]\n}\n";
+
+    inp
+}
+
+fn measure(inp: &str) -> Duration {
+    let mut total = Duration::ZERO;
+
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let node = lazyjson::parse(inp, &Config::DEFAULT).expect("failed to parse");
+        total += start.elapsed();
+
+        assert!(node.is_some());
+    }
+
+    total / RUNS
+}
@@ -1,3 +1,124 @@
+use crate::treebuilder::{KeyOrder, Node};
+
 pub fn get_indentation(indentation_level: usize) -> String {
     "    ".repeat(indentation_level)
 }
+
+/// Writes the node as JSON, indented by two spaces and with sorted keys.
+/// Written by hand, so it does not depend on the optional features.
+pub fn to_json(node: &Node) -> String {
+    let mut json = String::new();
+    write_json(&mut json, node, 0);
+
+    json
+}
+
+fn write_json(out: &mut String, node: &Node, indent: usize) {
+    let write_indentation = |out: &mut String, indent: usize| {
+        out.push('\n');
+        out.push_str(&"  ".repeat(indent));
+    };
+
+    match node {
+        Node::Array(a) if !a.entries.is_empty() => {
+            out.push('[');
+
+            for (i, entry) in a.entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+
+                write_indentation(out, indent + 1);
+                write_json(out, entry, indent + 1);
+            }
+
+            write_indentation(out, indent);
+            out.push(']');
+        }
+        Node::Object(o) if !o.entries.is_empty() => {
+            out.push('{');
+
+            for (i, (key, val)) in o.entries_ordered(KeyOrder::Sorted).into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+
+                write_indentation(out, indent + 1);
+                write_str(out, key);
+                out.push_str(": ");
+                write_json(out, val, indent + 1);
+            }
+
+            write_indentation(out, indent);
+            out.push('}');
+        }
+        Node::Array(_) => out.push_str("[]"),
+        Node::Object(_) => out.push_str("{}"),
+        Node::Bool(b) => out.push_str(&b.val.to_string()),
+        Node::Null(_) => out.push_str("null"),
+        Node::Number(n) => out.push_str(&n.val),
+        Node::String(s) => write_str(out, &s.val),
+    }
+}
+
+fn write_str(out: &mut String, val: &str) {
+    out.push('"');
+
+    for c in val.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse, treebuilder::Config};
+
+    use super::*;
+
+    fn to_json_of(inp: &str) -> String {
+        to_json(&parse(inp, &Config::DEFAULT).unwrap().unwrap())
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(to_json_of("true"), "true");
+        assert_eq!(to_json_of("null"), "null");
+        assert_eq!(to_json_of("1.50"), "1.50");
+        assert_eq!(to_json_of(r#""a""#), r#""a""#);
+    }
+
+    #[test]
+    fn escaping() {
+        let mut out = String::new();
+        write_str(&mut out, "\"\\\n\r\t\u{1}\u{1f}ä ");
+
+        assert_eq!(out, r#""\"\\\n\r\t\u0001\u001fä ""#);
+    }
+
+    #[test]
+    fn nesting() {
+        assert_eq!(
+            to_json_of(r#"{"b": [1, {"d": [], "c": {}}], "a": "x"}"#),
+            r#"{
+  "a": "x",
+  "b": [
+    1,
+    {
+      "c": {},
+      "d": []
+    }
+  ]
+}"#
+        );
+    }
+}
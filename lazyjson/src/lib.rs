@@ -1,10 +1,12 @@
 use std::rc::Rc;
 
 use queue::Queue;
+use source_map::SourceMap;
 use treebuilder::{config::Config, node::Node, VarDict};
 
 pub mod emit;
 pub mod peak_while;
+pub mod source_map;
 pub mod tokenizer;
 pub mod treebuilder;
 
@@ -29,3 +31,70 @@ pub fn parse(inp: &str, config: &Config) -> Result<Option<Node>, String> {
 
     Ok(node)
 }
+
+/// A parsed input, which keeps what is needed to locate its nodes.
+pub struct Document<'a> {
+    pub root: Option<Node>,
+    pub source_map: SourceMap<'a>,
+}
+
+/// Like [`parse`], but keeps the tokens, so the nodes can be mapped back to the
+/// input.
+///
+/// ```
+/// use lazyjson::treebuilder::Config;
+///
+/// let doc = lazyjson::parse_document("{\"a\": [1, 2]}", &Config::DEFAULT).unwrap();
+/// let root = doc.root.as_ref().unwrap();
+///
+/// assert_eq!(doc.source_map.text(&root["a"]), Some("[1, 2]"));
+/// ```
+pub fn parse_document<'a>(inp: &'a str, config: &Config) -> Result<Document<'a>, String> {
+    let toks = match tokenizer::tokenize(inp, config) {
+        Err(e) => return Err(e.msg(inp)),
+        Ok(toks) => toks,
+    };
+
+    let root = match treebuilder::build(&toks, config) {
+        Err(e) => return Err(e.msg(&toks, inp)),
+        Ok(root) => root,
+    };
+
+    Ok(Document {
+        root,
+        source_map: SourceMap::new(inp, toks),
+    })
+}
+
+/// Parses the input and deserializes a `T` from it. The message of a
+/// deserialization error contains the path and the position of the offending
+/// node.
+///
+/// ```
+/// use lazyjson::treebuilder::Config;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// let server: Server = lazyjson::from_str(r#"{"port": 8080}"#, &Config::DEFAULT).unwrap();
+///
+/// assert_eq!(server.port, 8080);
+/// ```
+#[cfg(feature = "serde")]
+pub fn from_str<T: serde::de::DeserializeOwned>(inp: &str, config: &Config) -> Result<T, String> {
+    let toks = match tokenizer::tokenize(inp, config) {
+        Err(e) => return Err(e.msg(inp)),
+        Ok(toks) => toks,
+    };
+
+    let node = match treebuilder::build(&toks, config) {
+        Err(e) => return Err(e.msg(&toks, inp)),
+        Ok(None) => return Err("expected a value but the input is empty\n".to_owned()),
+        Ok(Some(node)) => node,
+    };
+
+    treebuilder::from_node(&node).map_err(|e| e.msg(&toks, inp))
+}
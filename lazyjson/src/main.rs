@@ -2,11 +2,21 @@ use std::env;
 use std::fs;
 
 use lazyjson;
-use lazyjson::treebuilder::config::Config;
+use lazyjson::emit;
+use lazyjson::tokenizer::{self, Token};
+use lazyjson::treebuilder::{self, config::Config, DiffConfig, InferConfig, Node};
 
 fn main() -> Result<(), u8> {
     let args: Vec<String> = env::args().collect();
 
+    if args.get(1).map(String::as_str) == Some("diff") {
+        return diff(&args[2..]);
+    }
+
+    if args.get(1).map(String::as_str) == Some("infer") {
+        return infer(&args[2..]);
+    }
+
     let file_path = args.get(1).expect("no input file provided");
     let file = fs::read_to_string(file_path).expect("failed to read file");
 
@@ -27,3 +37,122 @@ fn main() -> Result<(), u8> {
 
     Ok(())
 }
+
+/// `diff <old file> <new file> [--ordered-objects] [--match-by <key>] [flags]`
+fn diff(args: &[String]) -> Result<(), u8> {
+    let old_path = args.first().expect("no old input file provided");
+    let new_path = args.get(1).expect("no new input file provided");
+    let old_file = fs::read_to_string(old_path).expect("failed to read old file");
+    let new_file = fs::read_to_string(new_path).expect("failed to read new file");
+
+    let mut diff_config = DiffConfig::DEFAULT.clone();
+    let mut config_args = vec![];
+    let mut flags = args[2..].iter();
+
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--ordered-objects" => diff_config.ordered_objects = true,
+            "--match-by" => {
+                let key = flags.next().expect("no key provided for --match-by");
+                diff_config.array_key = Some(key.clone());
+            }
+            _ => config_args.push(flag.clone()),
+        }
+    }
+
+    let config = match Config::from_iter(&mut config_args.iter()) {
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(1);
+        }
+        Ok(c) => c,
+    };
+
+    let (old_toks, old) = parse_with_toks(&old_file, &config)?;
+    let (new_toks, new) = parse_with_toks(&new_file, &config)?;
+
+    let changes = old.diff(&new, &diff_config);
+
+    for change in &changes {
+        println!("{}", change.msg(&old_toks, &old_file, &new_toks, &new_file));
+    }
+
+    if changes.is_empty() {
+        println!("no changes");
+    }
+
+    Ok(())
+}
+
+/// `infer <file>... [--max-enum-values <n>] [flags]`
+fn infer(args: &[String]) -> Result<(), u8> {
+    let mut infer_config = InferConfig::DEFAULT.clone();
+    let mut paths = vec![];
+    let mut config_args = vec![];
+    let mut flags = args.iter();
+
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--max-enum-values" => {
+                let n = flags
+                    .next()
+                    .expect("no amount provided for --max-enum-values");
+                infer_config.max_enum_values = n.parse().expect("invalid amount");
+            }
+            _ if flag.starts_with("--") => config_args.push(flag.clone()),
+            _ => paths.push(flag),
+        }
+    }
+
+    if paths.is_empty() {
+        panic!("no input file provided");
+    }
+
+    let config = match Config::from_iter(&mut config_args.iter()) {
+        Err(e) => {
+            eprintln!("{}", e);
+            return Err(1);
+        }
+        Ok(c) => c,
+    };
+
+    let mut samples = vec![];
+
+    for path in paths {
+        let file = fs::read_to_string(path).expect("failed to read file");
+        samples.push(parse_with_toks(&file, &config)?.1);
+    }
+
+    print_json(&treebuilder::infer_schema(&samples, &infer_config))
+}
+
+/// Prints the node as JSON (see [`emit::to_json`]).
+fn print_json(node: &Node) -> Result<(), u8> {
+    println!("{}", emit::to_json(node));
+
+    Ok(())
+}
+
+/// Like [`lazyjson::parse`], but also returns the tokens, which are needed to
+/// show where nodes are located.
+fn parse_with_toks(inp: &str, config: &Config) -> Result<(Vec<Token>, Node), u8> {
+    let toks = match tokenizer::tokenize(inp, config) {
+        Err(e) => {
+            eprintln!("{}", e.msg(inp));
+            return Err(1);
+        }
+        Ok(toks) => toks,
+    };
+
+    match treebuilder::build(&toks, config) {
+        Err(e) => {
+            eprintln!("{}", e.msg(&toks, inp));
+            Err(1)
+        }
+        Ok(None) => {
+            eprintln!("the input contains no value");
+            Err(1)
+        }
+        Ok(Some(node)) => Ok((toks, node)),
+    }
+}
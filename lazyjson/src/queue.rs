@@ -37,6 +37,12 @@ impl<T> Queue<T> {
     pub fn idx(&self) -> usize {
         self.idx
     }
+
+    /// Move the queue to an arbitrary position. This allows going back to
+    /// already consumed items (for example to consume them a second time).
+    pub fn seek(&mut self, idx: usize) {
+        self.idx = idx;
+    }
 }
 
 #[cfg(test)]
@@ -73,6 +79,18 @@ mod test {
         assert_eq!(queue.idx(), 0);
     }
 
+    #[test]
+    fn seek_moves_the_queue_to_the_given_position() {
+        let mut queue = Queue::new("abc".chars().collect());
+
+        queue.next();
+        queue.next();
+        queue.seek(0);
+
+        assert_eq!(queue.idx(), 0);
+        assert_eq!(queue.next(), Some(&'a'));
+    }
+
     #[test]
     fn get_returns_the_requested_slice_index() {
         let queue = Queue::new("abc".chars().collect());
@@ -0,0 +1,254 @@
+use std::ops::Range;
+
+use crate::{
+    tokenizer::Token,
+    treebuilder::{Node, PathSegment},
+};
+
+/// A position in the input. Both are counted from 1, and the column counts
+/// chars, like the positions in error messages.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Where a node is located in the input.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct SourceSpan {
+    pub bytes: Range<usize>,
+    pub start: Position,
+    /// The position right after the last char of the node.
+    pub end: Position,
+}
+
+/// Maps the spans of nodes, which are indices of tokens, back to the input.
+/// Generated nodes (see [`SYNTHETIC`](crate::treebuilder::node::SYNTHETIC))
+/// have no location.
+#[derive(Debug, Clone)]
+pub struct SourceMap<'a> {
+    inp: &'a str,
+    toks: Vec<Token>,
+    /// The byte offset of each char, followed by the length of the input. The
+    /// spans of tokens are char indices.
+    char_offsets: Vec<usize>,
+    /// The byte offset of the first char of each line.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    /// Takes the tokens the nodes were built from.
+    pub fn new(inp: &'a str, toks: Vec<Token>) -> SourceMap<'a> {
+        let mut char_offsets = inp.char_indices().map(|(i, _)| i).collect::<Vec<usize>>();
+        char_offsets.push(inp.len());
+
+        let line_starts = std::iter::once(0)
+            .chain(inp.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        SourceMap {
+            inp,
+            toks,
+            char_offsets,
+            line_starts,
+        }
+    }
+
+    pub fn inp(&self) -> &'a str {
+        self.inp
+    }
+    pub fn toks(&self) -> &[Token] {
+        &self.toks
+    }
+
+    /// Returns the byte range of the node, or `None` if it is generated. The
+    /// value of a variable is located where the variable is used, not where
+    /// it is defined.
+    pub fn bytes(&self, node: &Node) -> Option<Range<usize>> {
+        let (from, to) = match node.provenance() {
+            Some(p) => (p.usage_from, p.usage_to),
+            None => (node.from(), node.to()),
+        };
+
+        if node.is_synthetic() || from >= to {
+            return None;
+        }
+
+        let first = self.toks.get(from)?;
+        let last = self.toks.get(to - 1)?;
+
+        Some(self.char_offsets[first.from]..self.char_offsets[last.to])
+    }
+    pub fn span(&self, node: &Node) -> Option<SourceSpan> {
+        let bytes = self.bytes(node)?;
+
+        Some(SourceSpan {
+            start: self.position(bytes.start),
+            end: self.position(bytes.end),
+            bytes,
+        })
+    }
+    /// Returns the text of the node, exactly as it was written (the name of the
+    /// variable for values of variables).
+    pub fn text(&self, node: &Node) -> Option<&'a str> {
+        self.bytes(node).map(|bytes| &self.inp[bytes])
+    }
+    /// Returns the position of the byte offset. Offsets inside of a char, or
+    /// after the end of the input, are clamped.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.inp.len());
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+
+        Position {
+            line,
+            column: self.char_offsets.partition_point(|i| *i < offset)
+                - self.char_offsets.partition_point(|i| *i < line_start)
+                + 1,
+        }
+    }
+    /// Returns the innermost node that contains the byte offset, and its path
+    /// from the root. Keys of objects are part of the object, not of the
+    /// entry. The value of a variable contains its usage, but none of its
+    /// entries do.
+    pub fn node_at<'n>(
+        &self,
+        root: &'n Node,
+        offset: usize,
+    ) -> Option<(Vec<PathSegment>, &'n Node)> {
+        if !self.contains(root, offset) {
+            return None;
+        }
+
+        let mut path = vec![];
+        let mut node = root;
+
+        loop {
+            let child = match node {
+                Node::Array(a) => a
+                    .entries
+                    .iter()
+                    .enumerate()
+                    .find(|(_, entry)| self.contains(entry, offset))
+                    .map(|(i, entry)| (PathSegment::Index(i), entry)),
+                Node::Object(o) => o
+                    .entries
+                    .iter()
+                    .find(|(_, val)| self.contains(val, offset))
+                    .map(|(key, val)| (PathSegment::Key(key.clone()), val)),
+                _ => None,
+            };
+
+            match child {
+                Some((segment, child)) => {
+                    path.push(segment);
+                    node = child;
+                }
+                None => return Some((path, node)),
+            }
+        }
+    }
+
+    fn contains(&self, node: &Node, offset: usize) -> bool {
+        self.bytes(node)
+            .is_some_and(|bytes| bytes.contains(&offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_document, treebuilder::Config};
+
+    use super::*;
+
+    const INP: &str = "{\n  \"näme\": \"x\",\n  \"list\": [1, {\"b\": true}]\n}";
+
+    #[test]
+    fn span() {
+        let doc = parse_document(INP, &Config::DEFAULT).unwrap();
+        let root = doc.root.as_ref().unwrap();
+        let map = &doc.source_map;
+
+        assert_eq!(map.text(&root["näme"]), Some("\"x\""));
+        assert_eq!(
+            map.span(&root["list"]),
+            Some(SourceSpan {
+                bytes: 28..44,
+                start: Position {
+                    line: 3,
+                    column: 11
+                },
+                end: Position {
+                    line: 3,
+                    column: 27
+                },
+            })
+        );
+        assert_eq!(map.text(root), Some(INP));
+        assert_eq!(map.span(root).unwrap().end, Position { line: 4, column: 2 });
+    }
+
+    #[test]
+    fn synthetic_nodes_have_no_span() {
+        let doc = parse_document(INP, &Config::DEFAULT).unwrap();
+
+        assert_eq!(doc.source_map.span(&1.into()), None);
+    }
+
+    #[test]
+    fn node_at() {
+        let doc = parse_document(INP, &Config::DEFAULT).unwrap();
+        let root = doc.root.as_ref().unwrap();
+        let map = &doc.source_map;
+
+        let offset = INP.find("true").unwrap() + 2;
+        let (path, node) = map.node_at(root, offset).unwrap();
+
+        assert_eq!(
+            path,
+            [
+                PathSegment::Key("list".to_owned()),
+                PathSegment::Index(1),
+                PathSegment::Key("b".to_owned())
+            ]
+        );
+        assert_eq!(node, &root["list"][1]["b"]);
+
+        let (path, node) = map.node_at(root, INP.find("\"list\"").unwrap()).unwrap();
+
+        assert!(path.is_empty());
+        assert_eq!(node, root);
+        assert!(map.node_at(root, INP.len()).is_none());
+    }
+
+    #[test]
+    fn variables() {
+        let inp = "{let x = [1, 2], \"k\": x, \"l\": [x]}";
+        let doc = parse_document(inp, &Config::DEFAULT).unwrap();
+        let root = doc.root.as_ref().unwrap();
+        let map = &doc.source_map;
+
+        let usage = inp.find("[x]").unwrap() + 1;
+
+        assert_eq!(map.text(&root["k"]), Some("x"));
+        assert_eq!(map.bytes(&root["l"][0]), Some(usage..usage + 1));
+
+        let (path, node) = map.node_at(root, inp.find(": x").unwrap() + 2).unwrap();
+
+        assert_eq!(path, [PathSegment::Key("k".to_owned())]);
+        assert_eq!(node, &root["k"]);
+
+        let (path, _) = map.node_at(root, usage).unwrap();
+
+        assert_eq!(
+            path,
+            [PathSegment::Key("l".to_owned()), PathSegment::Index(0)]
+        );
+
+        // The definition is not the location of any node.
+        let (path, node) = map.node_at(root, inp.find('2').unwrap()).unwrap();
+
+        assert!(path.is_empty());
+        assert_eq!(node, root);
+    }
+}
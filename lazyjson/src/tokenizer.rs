@@ -1,6 +1,7 @@
 pub mod error;
 
 mod delimiter_consumer;
+mod identifier_consumer;
 mod keyword_literal_consumer;
 mod line_comment_consumer;
 mod number_literal_consumer;
@@ -15,6 +16,7 @@ pub use error::TokenizationErr;
 pub use token::{Token, TokenType};
 
 use delimiter_consumer::delimiter_consumer;
+use identifier_consumer::identifier_consumer;
 use keyword_literal_consumer::keyword_literal_consumer;
 use line_comment_consumer::line_comment_consumer;
 use number_literal_consumer::number_literal_consumer;
@@ -42,6 +44,7 @@ pub fn tokenize(inp: &str, config: &Config) -> Result<Vec<Token>, TokenizationEr
         &whitespace_consumer,
         &delimiter_consumer,
         &keyword_literal_consumer,
+        &identifier_consumer,
         &number_literal_consumer,
         &operator_consumer,
         &separator_consumer,
@@ -186,7 +189,7 @@ mod tests {
             [
                 Token::new_delimiter("{", 0, 1),
                 Token::new_kwd("let", 1, 4),
-                Token::new_kwd("test", 5, 9),
+                Token::new_ident("test", 5, 9),
                 Token::new_equal_assignment_op(10),
                 Token::new_num("10", 12, 14),
                 Token::new_delimiter("}", 14, 15),
@@ -194,6 +197,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn identifiers() {
+        assert_eq!(
+            tokenize("db_host port2 letter", &Config::DEFAULT).unwrap(),
+            [
+                Token::new_ident("db_host", 0, 7),
+                Token::new_ident("port2", 8, 13),
+                Token::new_ident("letter", 14, 20),
+            ],
+        );
+    }
+
     #[test]
     fn comments_allowed() {
         let mut config = Config::DEFAULT;
@@ -2,7 +2,7 @@ use crate::char_queue::CharQueue;
 
 use super::{error::TokenizationErr, Token};
 
-const DELIMITERS: [char; 4] = ['[', ']', '{', '}'];
+const DELIMITERS: [char; 6] = ['(', ')', '[', ']', '{', '}'];
 
 pub fn delimiter_consumer(inp: &mut CharQueue) -> Result<Option<Token>, TokenizationErr> {
     let c = inp.peek().ok_or(TokenizationErr::new_out_of_bounds())?;
@@ -0,0 +1,100 @@
+use super::{error::TokenizationErr, keyword_literal_consumer::KEYWORDS, token::*};
+
+use crate::char_queue::CharQueue;
+
+/// Consumes user supplied names (`[A-Za-z_$][A-Za-z0-9_$]*`), for example the
+/// name of a variable. Reserved [`KEYWORDS`] are left for the
+/// `keyword_literal_consumer`.
+pub fn identifier_consumer(inp: &mut CharQueue) -> Result<Option<Token>, TokenizationErr> {
+    let ident = match read_identifier(inp) {
+        Some(ident) => ident,
+        None => return Ok(None),
+    };
+
+    if KEYWORDS.contains(&ident.as_str()) {
+        return Ok(None);
+    }
+
+    let from = inp.idx();
+    let to = inp.idx() + ident.len();
+
+    inp.advance_by(ident.len());
+
+    Ok(Some(Token::new_ident(&ident, from, to)))
+}
+
+/// Reads (but does **NOT** consume) the identifier starting at the current
+/// queue position.
+pub fn read_identifier(inp: &CharQueue) -> Option<String> {
+    if !is_identifier_start(inp.peek()?) {
+        return None;
+    }
+
+    let to = inp
+        .find_next(|c| !is_identifier_char(c))
+        .unwrap_or(inp.len());
+
+    Some(inp.get(inp.idx()..to)?.iter().collect())
+}
+
+fn is_identifier_start(c: &char) -> bool {
+    c.is_ascii_alphabetic() || c == &'_' || c == &'$'
+}
+
+fn is_identifier_char(c: &char) -> bool {
+    is_identifier_start(c) || c.is_ascii_digit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_identifier() {
+        let inp = &mut CharQueue::new("1abc");
+
+        assert_eq!(identifier_consumer(inp), Ok(None));
+        assert_eq!(inp.next(), Some(&'1'));
+    }
+
+    #[test]
+    fn keyword_is_not_consumed() {
+        let inp = &mut CharQueue::new("let");
+
+        assert_eq!(identifier_consumer(inp), Ok(None));
+        assert_eq!(inp.next(), Some(&'l'));
+    }
+
+    #[test]
+    fn valid_at_start() {
+        for ident in [
+            "foo", "db_host", "port2", "_private", "$ref", "letter", "nullable",
+        ] {
+            let inp = &mut CharQueue::new(ident);
+
+            assert_eq!(
+                identifier_consumer(inp),
+                Ok(Some(Token::new_ident(ident, 0, ident.len())))
+            );
+        }
+    }
+
+    #[test]
+    fn valid_at_offset() {
+        let inp = &mut CharQueue::new("   foo");
+        inp.advance_by(3);
+
+        assert_eq!(
+            identifier_consumer(inp),
+            Ok(Some(Token::new_ident("foo", 3, 6)))
+        );
+    }
+
+    #[test]
+    fn is_consumed() {
+        let inp = &mut CharQueue::new("foo_1 ");
+        identifier_consumer(inp).unwrap();
+
+        assert_eq!(inp.next(), Some(&' '));
+    }
+}
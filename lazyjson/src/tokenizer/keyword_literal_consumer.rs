@@ -1,13 +1,15 @@
-use super::{error::TokenizationErr, token::*};
+use super::{error::TokenizationErr, identifier_consumer::read_identifier, token::*};
 
 use crate::char_queue::CharQueue;
 
-// TODO: split up into "keyword literals" (hardcoded and predefined), and name literals
-// (dynamic and user supplied).
+/// Words that have a predefined meaning, and can therefore not be used as
+/// identifiers (for example as variable names).
+pub const KEYWORDS: [&str; 7] = ["false", "for", "if", "in", "let", "null", "true"];
+
 pub fn keyword_literal_consumer(inp: &mut CharQueue) -> Result<Option<Token>, TokenizationErr> {
-    let kwd = match read_until_non_alphabetical(inp) {
-        Some(kwd) => kwd,
-        None => return Ok(None),
+    let kwd = match read_identifier(inp) {
+        Some(kwd) if KEYWORDS.contains(&kwd.as_str()) => kwd,
+        _ => return Ok(None),
     };
 
     let from = inp.idx();
@@ -18,16 +20,6 @@ pub fn keyword_literal_consumer(inp: &mut CharQueue) -> Result<Option<Token>, To
     Ok(Some(Token::new_kwd(&kwd, from, to)))
 }
 
-fn read_until_non_alphabetical(inp: &mut CharQueue) -> Option<String> {
-    let to = inp.find_next(|c| !c.is_alphabetic()).unwrap_or(inp.len());
-    let val = inp.get(inp.idx()..to)?.iter().collect::<String>();
-
-    if val.is_empty() {
-        return None;
-    }
-    return Some(val);
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,6 +32,16 @@ mod tests {
         assert_eq!(t, None);
     }
 
+    #[test]
+    fn identifier_is_not_a_keyword() {
+        for inp in ["foo", "letter", "true_value"] {
+            let inp = &mut CharQueue::new(inp);
+
+            assert_eq!(keyword_literal_consumer(inp), Ok(None));
+            assert_eq!(inp.idx(), 0);
+        }
+    }
+
     #[test]
     fn checking_does_not_consume() {
         let inp = &mut CharQueue::new("1");
@@ -51,8 +53,9 @@ mod tests {
 
     #[test]
     fn valid_at_start() {
-        consume_valid_at_start("false");
-        consume_valid_at_start("true");
+        for kwd in KEYWORDS {
+            consume_valid_at_start(kwd);
+        }
     }
 
     #[test]
@@ -8,6 +8,9 @@ pub enum TokenType {
     /// Refers to the "normal assignment operator" ('='). In opposition to the
     /// [`TokenType::JsonAssignmentOperator`]
     EqualAssignmentOperator,
+    /// A user supplied name (for example of a variable). In opposition to the
+    /// reserved [`TokenType::KeywordLiteral`].
+    Identifier,
     KeywordLiteral,
     LineComment,
     NumberLiteral,
@@ -46,6 +49,15 @@ impl Token {
             val: "".into(),
         }
     }
+    /// Create a new token of the type [`TokenType::Identifier`].
+    pub fn new_ident(val: &str, from: usize, to: usize) -> Token {
+        Token {
+            from,
+            to,
+            typ: TokenType::Identifier,
+            val: val.into(),
+        }
+    }
     /// Create a new token of the type [`TokenType::KeywordLiteral`].
     pub fn new_kwd(val: &str, from: usize, to: usize) -> Token {
         Token {
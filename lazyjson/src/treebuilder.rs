@@ -2,31 +2,69 @@ use std::rc::Rc;
 
 use crate::{queue::Queue, tokenizer::Token};
 
+pub mod access_error;
+pub mod arena;
 pub mod array_consumer;
+pub mod builtins;
 pub mod config;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod diff;
+pub mod edit;
 pub mod error;
+pub mod infer;
+#[cfg(feature = "serde_json")]
+pub mod json_value;
 pub mod keyword_consumer;
 pub mod node;
+pub mod number;
 pub mod number_consumer;
 pub mod object_consumer;
+pub mod patch;
+pub mod pointer;
+pub mod query;
+#[cfg(feature = "schema")]
+pub mod schema;
+pub mod semantic;
+#[cfg(feature = "serde")]
+pub mod ser;
 pub mod string_consumer;
 pub mod value_consumer;
 pub mod var_dict;
+pub mod visit;
 
+pub use access_error::AccessErr;
+pub use arena::{Arena, NodeId, NodeRef};
 pub use array_consumer::array_consumer;
 pub use config::Config;
+#[cfg(feature = "serde")]
+pub use de::{from_node, DeErr};
+pub use diff::{Change, DiffConfig};
 pub use error::TreebuilderErr;
+pub use infer::{infer_schema, InferConfig};
+#[cfg(feature = "serde_json")]
+pub use json_value::ValueConfig;
 pub use keyword_consumer::keyword_consumer;
-pub use node::Node;
+pub use node::{KeyOrder, Node, PathSegment};
+pub use number::Number;
 pub use number_consumer::number_consumer;
 pub use object_consumer::object_consumer;
+pub use patch::PatchErr;
+pub use pointer::PointerErr;
+pub use query::{QueryErr, QueryMatch};
+#[cfg(feature = "schema")]
+pub use schema::{Schema, SchemaErr, Violation};
+pub use semantic::{EqConfig, Semantic};
 pub use string_consumer::string_consumer;
 pub use value_consumer::value_consumer;
 pub use var_dict::VarDict;
+pub use visit::{Fold, Visit, VisitMut};
 
 #[cfg(test)]
 mod testing;
 
+mod comprehension_consumer;
+mod function_call_consumer;
 mod variable_definition_consumer;
 mod variable_usage_consumer;
 
@@ -0,0 +1,161 @@
+use crate::tokenizer::Token;
+
+use super::{
+    error::get_msg,
+    node::{Node, NodeTyp, Provenance},
+};
+use std::{
+    error::Error,
+    fmt::{self, Debug},
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum AccessErrTyp {
+    /// The node has a different type than the requested one.
+    InvalidTyp {
+        expected: NodeTyp,
+        received: NodeTyp,
+    },
+    /// The array has no entry at the index.
+    MissingIndex { idx: usize, len: usize },
+    /// The object has no entry with the key.
+    MissingKey { key: String },
+    /// The number can not be represented by the requested Rust type, for
+    /// example `1.5` as an `i64`.
+    NumberOutOfRange { expected: &'static str },
+}
+
+/// Returned by the accessors of [`Node`] (like [`Node::as_str`]). The span
+/// refers to the node that was accessed, so validation errors of an application
+/// can point into the source.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AccessErr {
+    pub typ: AccessErrTyp,
+    pub from: usize,
+    pub to: usize,
+    /// Set if the accessed node was produced by using a variable.
+    pub provenance: Option<Provenance>,
+}
+
+impl fmt::Display for AccessErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for AccessErr {}
+
+impl AccessErr {
+    /// Creates a new error of the typ [`AccessErrTyp::InvalidTyp`].
+    pub fn new_invalid_typ(node: &Node, expected: NodeTyp) -> AccessErr {
+        AccessErr::new(
+            node,
+            AccessErrTyp::InvalidTyp {
+                expected,
+                received: node.typ(),
+            },
+        )
+    }
+    /// Creates a new error of the typ [`AccessErrTyp::MissingIndex`].
+    pub fn new_missing_index(node: &Node, idx: usize, len: usize) -> AccessErr {
+        AccessErr::new(node, AccessErrTyp::MissingIndex { idx, len })
+    }
+    /// Creates a new error of the typ [`AccessErrTyp::MissingKey`].
+    pub fn new_missing_key(node: &Node, key: &str) -> AccessErr {
+        AccessErr::new(
+            node,
+            AccessErrTyp::MissingKey {
+                key: key.to_owned(),
+            },
+        )
+    }
+    /// Creates a new error of the typ [`AccessErrTyp::NumberOutOfRange`].
+    pub fn new_number_out_of_range(node: &Node, expected: &'static str) -> AccessErr {
+        AccessErr::new(node, AccessErrTyp::NumberOutOfRange { expected })
+    }
+
+    fn new(node: &Node, typ: AccessErrTyp) -> AccessErr {
+        AccessErr {
+            typ,
+            from: node.from(),
+            to: node.to(),
+            provenance: node.provenance().cloned(),
+        }
+    }
+
+    pub fn msg(&self, toks: &[Token], inp: &str) -> String {
+        get_msg(
+            &get_verbal_hint(&self.typ),
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
+        )
+    }
+}
+
+pub(super) fn get_verbal_hint(typ: &AccessErrTyp) -> String {
+    match typ {
+        AccessErrTyp::InvalidTyp { expected, received } => {
+            format!("expected a `{}` but received a `{}`", expected, received)
+        }
+        AccessErrTyp::MissingIndex { idx, len } => format!(
+            "expected an entry at index {} but the array has {} entries",
+            idx, len
+        ),
+        AccessErrTyp::MissingKey { key } => format!("expected an entry with the key `{}`", key),
+        AccessErrTyp::NumberOutOfRange { expected } => {
+            format!("expected a number that fits into the type `{}`", expected)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::treebuilder::node::{NumberNode, StringNode};
+
+    use super::*;
+
+    #[test]
+    fn invalid_typ_msg() {
+        let inp = r#"{"port": "80"}"#;
+        let toks = [
+            Token::new_delimiter("{", 0, 1),
+            Token::new_str("port", 1, 7),
+            Token::new_json_assignment_op(7),
+            Token::new_str("80", 9, 13),
+            Token::new_delimiter("}", 13, 14),
+        ];
+        let node: Node = StringNode::new(3, "80".to_owned()).into();
+
+        assert_eq!(
+            AccessErr::new_invalid_typ(&node, NodeTyp::Number).msg(&toks, inp),
+            "expected a `number` but received a `string`, line: 1, char: 10\n\n{\"port\": \"80\"}\n         ^^^^\n"
+        );
+    }
+
+    #[test]
+    fn msg_with_provenance() {
+        let inp = "{let p = 1.5, \"port\": p}";
+        let toks = [
+            Token::new_delimiter("{", 0, 1),
+            Token::new_kwd("let", 1, 4),
+            Token::new_ident("p", 5, 6),
+            Token::new_equal_assignment_op(7),
+            Token::new_num("1.5", 9, 12),
+            Token::new_sep(",", 12, 13),
+            Token::new_str("port", 14, 20),
+            Token::new_json_assignment_op(20),
+            Token::new_ident("p", 22, 23),
+            Token::new_delimiter("}", 23, 24),
+        ];
+        let node: Node = Node::Number(NumberNode::new(4, "1.5".to_owned()))
+            .with_provenance(Provenance::new("p", 8, 1, 5));
+
+        assert_eq!(
+            AccessErr::new_number_out_of_range(&node, "u64").msg(&toks, inp),
+            "expected a number that fits into the type `u64`, line: 1, char: 10\n\n{let p = 1.5, \"port\": p}\n         ^^^\n\nin value of variable `p` defined at line: 1, char: 2\n\n{let p = 1.5, \"port\": p}\n ^^^^^^^^^^^\n"
+        );
+    }
+}
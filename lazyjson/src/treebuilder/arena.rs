@@ -0,0 +1,735 @@
+use std::{collections::HashMap, convert::TryFrom, ops::Range, rc::Rc};
+
+use crate::{queue::Queue, tokenizer::Token};
+
+use super::{
+    access_error::{AccessErr, AccessErrTyp},
+    array_consumer::{consume_arr, consume_arr_opn},
+    config::Config,
+    error::TreebuilderErr,
+    node::{
+        ArrayNode, BoolNode, KeyOrder, Node, NodeTyp, NullNode, NumberNode, ObjectNode, Provenance,
+        StringNode, SYNTHETIC,
+    },
+    number::Number,
+    object_consumer::{consume_obj, consume_obj_opn},
+    value_consumer::{value_consumer, ValBuilder},
+    var_dict::VarDict,
+};
+
+/// Refers to a node stored in an [`Arena`].
+#[derive(Eq, PartialEq, Hash, Debug, Clone, Copy)]
+pub struct NodeId(u32);
+
+/// What is stored for each node. Ranges refer to the shared buffers of the
+/// arena, so nodes do not own any allocations.
+#[derive(Debug, Clone)]
+enum Slot {
+    /// A range of [`Arena::children`].
+    Array {
+        first: u32,
+        len: u32,
+    },
+    Bool(bool),
+    Null,
+    /// A range of [`Arena::text`].
+    Number {
+        start: u32,
+        len: u32,
+    },
+    /// A range of [`Arena::entries`] in source order, and the same range of
+    /// [`Arena::index`].
+    Object {
+        first: u32,
+        len: u32,
+    },
+    /// A range of [`Arena::text`].
+    String {
+        start: u32,
+        len: u32,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Data {
+    slot: Slot,
+    from: usize,
+    to: usize,
+}
+
+/// Stores nodes in flat buffers instead of a tree of allocations, which needs
+/// a lot less memory for large documents: children are referenced by index,
+/// keys of objects are interned, and all strings share a single buffer.
+///
+/// Nodes are built into the arena from tokens with [`Arena::build`], or copied
+/// into it from a tree with [`Arena::insert`], after which the tree can be
+/// dropped. They are read through a [`NodeRef`], which has the same
+/// accessors as [`Node`], and can be converted back with [`NodeRef::to_node`].
+/// The arena is append only.
+#[derive(Debug, Clone, Default)]
+pub struct Arena {
+    nodes: Vec<Data>,
+    children: Vec<NodeId>,
+    entries: Vec<(u32, NodeId)>,
+    /// For each object, the positions of its entries sorted by key, so that
+    /// entries can be looked up with a binary search.
+    index: Vec<u32>,
+    keys: Vec<Rc<str>>,
+    key_ids: HashMap<Rc<str>, u32>,
+    text: String,
+    /// Only few nodes are produced by using a variable, so their provenance is
+    /// stored separately.
+    provenances: HashMap<NodeId, Provenance>,
+}
+
+impl Arena {
+    pub fn new() -> Arena {
+        Arena::default()
+    }
+
+    /// Builds the nodes from the tokens directly into the arena, without a
+    /// tree of [`Node`]s in between. Takes the same input as
+    /// [`build`](super::build), and returns the same errors. Only values that
+    /// are computed (variables, function calls and comprehensions) are built
+    /// as [`Node`]s first.
+    ///
+    /// Returns an error of the typ [`TreebuilderErrTyp::TooLarge`] if the
+    /// arena would hold more than `u32::MAX` nodes, entries or bytes of text.
+    /// Nodes that were built before an error stay in the arena.
+    ///
+    /// [`TreebuilderErrTyp::TooLarge`]: super::error::TreebuilderErrTyp::TooLarge
+    pub fn build(
+        &mut self,
+        toks: &[Token],
+        config: &Config,
+    ) -> Result<Option<NodeId>, TreebuilderErr> {
+        self.val(
+            &mut Queue::new(Vec::from(toks)),
+            &Rc::new(VarDict::new()),
+            config,
+        )
+    }
+    /// Copies the node and all of its descendants into the arena.
+    ///
+    /// Panics if the arena would hold more than `u32::MAX` nodes, entries or
+    /// bytes of text.
+    pub fn insert(&mut self, node: &Node) -> NodeId {
+        self.try_insert(node)
+            .expect("an arena can not hold more than `u32::MAX` nodes, entries or bytes")
+    }
+    /// Panics if the id belongs to another arena.
+    pub fn get(&self, id: NodeId) -> NodeRef<'_> {
+        assert!((id.0 as usize) < self.nodes.len(), "unknown node id");
+
+        NodeRef { arena: self, id }
+    }
+    /// Returns the amount of nodes in the arena.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns `None` if the arena is full.
+    fn try_insert(&mut self, node: &Node) -> Option<NodeId> {
+        let id = match node {
+            Node::Array(a) => {
+                let ids = a
+                    .entries
+                    .iter()
+                    .map(|entry| self.try_insert(entry))
+                    .collect::<Option<Vec<NodeId>>>()?;
+
+                self.push_arr(ids, node.from(), node.to())?
+            }
+            Node::Object(o) => {
+                let ids = o
+                    .entries_ordered(KeyOrder::Source)
+                    .into_iter()
+                    .map(|(key, val)| Some((self.intern(key)?, self.try_insert(val)?)))
+                    .collect::<Option<Vec<(u32, NodeId)>>>()?;
+
+                self.push_obj(ids, node.from(), node.to())?
+            }
+            Node::Bool(b) => self.push(Slot::Bool(b.val), node.from(), node.to())?,
+            Node::Null(_) => self.push(Slot::Null, node.from(), node.to())?,
+            Node::Number(n) => {
+                let (start, len) = self.push_text(&n.val)?;
+
+                self.push(Slot::Number { start, len }, node.from(), node.to())?
+            }
+            Node::String(s) => {
+                let (start, len) = self.push_text(&s.val)?;
+
+                self.push(Slot::String { start, len }, node.from(), node.to())?
+            }
+        };
+
+        if let Some(provenance) = node.provenance() {
+            self.provenances.insert(id, provenance.clone());
+        }
+
+        Some(id)
+    }
+    /// The `push_*` functions return `None` if the arena is full.
+    fn push(&mut self, slot: Slot, from: usize, to: usize) -> Option<NodeId> {
+        let id = NodeId(to_u32(self.nodes.len())?);
+
+        self.nodes.push(Data { slot, from, to });
+
+        Some(id)
+    }
+    fn push_arr(&mut self, ids: Vec<NodeId>, from: usize, to: usize) -> Option<NodeId> {
+        let first = to_u32(self.children.len())?;
+        let len = to_u32(ids.len())?;
+
+        self.children.extend(ids);
+
+        self.push(Slot::Array { first, len }, from, to)
+    }
+    /// Of entries with the same key, the position of the first one and the
+    /// value of the last one is kept, like in [`ObjectNode::from_entries`].
+    fn push_obj(&mut self, entries: Vec<(u32, NodeId)>, from: usize, to: usize) -> Option<NodeId> {
+        let mut deduped: Vec<(u32, NodeId)> = Vec::with_capacity(entries.len());
+        let mut positions: HashMap<u32, usize> = HashMap::new();
+
+        for (key, id) in entries {
+            match positions.get(&key) {
+                Some(i) => deduped[*i].1 = id,
+                None => {
+                    positions.insert(key, deduped.len());
+                    deduped.push((key, id));
+                }
+            }
+        }
+
+        let keys = &self.keys;
+        let mut index = (0..to_u32(deduped.len())?).collect::<Vec<u32>>();
+        index.sort_by_key(|i| &keys[deduped[*i as usize].0 as usize]);
+
+        let first = to_u32(self.entries.len())?;
+        let len = to_u32(deduped.len())?;
+
+        self.entries.extend(deduped);
+        self.index.extend(index);
+
+        self.push(Slot::Object { first, len }, from, to)
+    }
+    fn intern(&mut self, key: &str) -> Option<u32> {
+        if let Some(id) = self.key_ids.get(key) {
+            return Some(*id);
+        }
+
+        let id = to_u32(self.keys.len())?;
+        let key: Rc<str> = Rc::from(key);
+
+        self.keys.push(key.clone());
+        self.key_ids.insert(key, id);
+
+        Some(id)
+    }
+    fn push_text(&mut self, val: &str) -> Option<(u32, u32)> {
+        let start = to_u32(self.text.len())?;
+        let len = to_u32(val.len())?;
+        self.text.push_str(val);
+
+        Some((start, len))
+    }
+    fn text(&self, start: u32, len: u32) -> &str {
+        &self.text[to_range(start, len)]
+    }
+}
+
+/// Builds the values of arrays and objects directly into the arena, with the
+/// consumers of arrays and objects.
+impl ValBuilder for Arena {
+    type Val = NodeId;
+
+    fn val(
+        &mut self,
+        inp: &mut Queue<Token>,
+        var_dict: &Rc<VarDict>,
+        config: &Config,
+    ) -> Result<Option<NodeId>, TreebuilderErr> {
+        let opn_i = inp.idx();
+
+        if inp.peek().is_some() {
+            if consume_arr_opn(inp) {
+                return consume_arr(self, inp, opn_i, var_dict, config).map(Some);
+            }
+
+            if consume_obj_opn(inp) {
+                return consume_obj(self, inp, opn_i, var_dict, config).map(Some);
+            }
+        }
+
+        match value_consumer(inp, var_dict, config)? {
+            Some(node) => self.node(node, opn_i, inp.idx()).map(Some),
+            None => Ok(None),
+        }
+    }
+    fn node(&mut self, node: Node, from: usize, to: usize) -> Result<NodeId, TreebuilderErr> {
+        self.try_insert(&node)
+            .ok_or(TreebuilderErr::new_too_large(from, to))
+    }
+    fn arr(
+        &mut self,
+        entries: Vec<NodeId>,
+        from: usize,
+        to: usize,
+    ) -> Result<NodeId, TreebuilderErr> {
+        self.push_arr(entries, from, to)
+            .ok_or(TreebuilderErr::new_too_large(from, to))
+    }
+    fn obj(
+        &mut self,
+        entries: Vec<(String, NodeId)>,
+        from: usize,
+        to: usize,
+    ) -> Result<NodeId, TreebuilderErr> {
+        let id = entries
+            .into_iter()
+            .map(|(key, id)| Some((self.intern(&key)?, id)))
+            .collect::<Option<Vec<(u32, NodeId)>>>()
+            .and_then(|entries| self.push_obj(entries, from, to));
+
+        id.ok_or(TreebuilderErr::new_too_large(from, to))
+    }
+}
+
+fn to_u32(n: usize) -> Option<u32> {
+    u32::try_from(n).ok()
+}
+
+/// Both fit into `u32`, but their sum does not have to.
+fn to_range(first: u32, len: u32) -> Range<usize> {
+    first as usize..first as usize + len as usize
+}
+
+/// A node stored in an [`Arena`]. Provides the same accessors as [`Node`].
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRef<'a> {
+    arena: &'a Arena,
+    id: NodeId,
+}
+
+impl<'a> NodeRef<'a> {
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+    pub fn typ(&self) -> NodeTyp {
+        match self.data().slot {
+            Slot::Array { .. } => NodeTyp::Array,
+            Slot::Bool(_) => NodeTyp::Bool,
+            Slot::Null => NodeTyp::Null,
+            Slot::Number { .. } => NodeTyp::Number,
+            Slot::Object { .. } => NodeTyp::Object,
+            Slot::String { .. } => NodeTyp::String,
+        }
+    }
+    /// See [`Node::from`].
+    pub fn from(&self) -> usize {
+        self.data().from
+    }
+    /// See [`Node::to`].
+    pub fn to(&self) -> usize {
+        self.data().to
+    }
+    pub fn is_synthetic(&self) -> bool {
+        self.data().from == SYNTHETIC
+    }
+    pub fn provenance(&self) -> Option<&'a Provenance> {
+        self.arena.provenances.get(&self.id)
+    }
+
+    pub fn as_str(&self) -> Result<&'a str, AccessErr> {
+        match self.data().slot {
+            Slot::String { start, len } => Ok(self.arena.text(start, len)),
+            _ => Err(self.new_invalid_typ(NodeTyp::String)),
+        }
+    }
+    pub fn as_bool(&self) -> Result<bool, AccessErr> {
+        match self.data().slot {
+            Slot::Bool(val) => Ok(val),
+            _ => Err(self.new_invalid_typ(NodeTyp::Bool)),
+        }
+    }
+    /// See [`Node::as_i64`].
+    pub fn as_i64(&self) -> Result<i64, AccessErr> {
+        self.as_number()?
+            .to_i64()
+            .map_err(|_| self.new_number_out_of_range("i64"))
+    }
+    /// See [`Node::as_u64`].
+    pub fn as_u64(&self) -> Result<u64, AccessErr> {
+        self.as_number()?
+            .to_u64()
+            .map_err(|_| self.new_number_out_of_range("u64"))
+    }
+    pub fn as_i128(&self) -> Result<i128, AccessErr> {
+        self.as_number()?
+            .to_i128()
+            .map_err(|_| self.new_number_out_of_range("i128"))
+    }
+    /// See [`Node::as_f64`].
+    pub fn as_f64(&self) -> Result<f64, AccessErr> {
+        self.as_number()?
+            .to_f64()
+            .map_err(|_| self.new_number_out_of_range("f64"))
+    }
+    pub fn as_number(&self) -> Result<Number, AccessErr> {
+        match self.data().slot {
+            Slot::Number { start, len } => {
+                Ok(Number::from_lexeme(self.arena.text(start, len).to_owned()))
+            }
+            _ => Err(self.new_invalid_typ(NodeTyp::Number)),
+        }
+    }
+    pub fn as_array(&self) -> Result<ArrayRef<'a>, AccessErr> {
+        match self.data().slot {
+            Slot::Array { first, len } => Ok(ArrayRef {
+                arena: self.arena,
+                ids: &self.arena.children[to_range(first, len)],
+            }),
+            _ => Err(self.new_invalid_typ(NodeTyp::Array)),
+        }
+    }
+    pub fn as_object(&self) -> Result<ObjectRef<'a>, AccessErr> {
+        match self.data().slot {
+            Slot::Object { first, len } => Ok(ObjectRef {
+                arena: self.arena,
+                entries: &self.arena.entries[to_range(first, len)],
+                index: &self.arena.index[to_range(first, len)],
+            }),
+            _ => Err(self.new_invalid_typ(NodeTyp::Object)),
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self.data().slot, Slot::Null)
+    }
+    /// Returns the entry of the object with the key.
+    pub fn get(&self, key: &str) -> Result<NodeRef<'a>, AccessErr> {
+        self.as_object()?.get(key).ok_or_else(|| {
+            self.new_err(AccessErrTyp::MissingKey {
+                key: key.to_owned(),
+            })
+        })
+    }
+    /// Returns the entry of the array at the index.
+    pub fn get_index(&self, i: usize) -> Result<NodeRef<'a>, AccessErr> {
+        let entries = self.as_array()?;
+
+        entries.get(i).ok_or_else(|| {
+            self.new_err(AccessErrTyp::MissingIndex {
+                idx: i,
+                len: entries.len(),
+            })
+        })
+    }
+
+    /// Copies the node and all of its descendants out of the arena.
+    pub fn to_node(&self) -> Node {
+        let Data { slot, from, to } = self.data().clone();
+
+        let node = match slot {
+            Slot::Array { .. } => Node::Array(ArrayNode::new(
+                from,
+                to,
+                self.as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|n| n.to_node())
+                    .collect(),
+            )),
+            Slot::Bool(val) => Node::Bool(BoolNode {
+                val,
+                from,
+                to,
+                provenance: None,
+            }),
+            Slot::Null => Node::Null(NullNode {
+                from,
+                to,
+                provenance: None,
+            }),
+            Slot::Number { start, len } => Node::Number(NumberNode {
+                val: self.arena.text(start, len).to_owned(),
+                from,
+                to,
+                provenance: None,
+            }),
+            Slot::Object { .. } => Node::Object(ObjectNode::from_entries(
+                from,
+                to,
+                self.as_object()
+                    .unwrap()
+                    .iter()
+                    .map(|(key, val)| (key.to_owned(), val.to_node())),
+            )),
+            Slot::String { start, len } => Node::String(StringNode {
+                val: self.arena.text(start, len).to_owned(),
+                from,
+                to,
+                provenance: None,
+            }),
+        };
+
+        match self.provenance() {
+            Some(provenance) => node.with_provenance(provenance.clone()),
+            None => node,
+        }
+    }
+
+    fn data(&self) -> &'a Data {
+        &self.arena.nodes[self.id.0 as usize]
+    }
+    fn new_invalid_typ(&self, expected: NodeTyp) -> AccessErr {
+        self.new_err(AccessErrTyp::InvalidTyp {
+            expected,
+            received: self.typ(),
+        })
+    }
+    fn new_number_out_of_range(&self, expected: &'static str) -> AccessErr {
+        self.new_err(AccessErrTyp::NumberOutOfRange { expected })
+    }
+    fn new_err(&self, typ: AccessErrTyp) -> AccessErr {
+        AccessErr {
+            typ,
+            from: self.from(),
+            to: self.to(),
+            provenance: self.provenance().cloned(),
+        }
+    }
+}
+
+/// The entries of an array in an [`Arena`].
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayRef<'a> {
+    arena: &'a Arena,
+    ids: &'a [NodeId],
+}
+
+impl<'a> ArrayRef<'a> {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+    pub fn get(&self, i: usize) -> Option<NodeRef<'a>> {
+        self.ids.get(i).map(|id| self.arena.get(*id))
+    }
+    pub fn iter(&self) -> impl Iterator<Item = NodeRef<'a>> + 'a {
+        let arena = self.arena;
+
+        self.ids.iter().map(move |id| arena.get(*id))
+    }
+}
+
+/// The entries of an object in an [`Arena`], in source order.
+#[derive(Debug, Clone, Copy)]
+pub struct ObjectRef<'a> {
+    arena: &'a Arena,
+    entries: &'a [(u32, NodeId)],
+    /// The positions of the entries, sorted by key.
+    index: &'a [u32],
+}
+
+impl<'a> ObjectRef<'a> {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    pub fn get(&self, key: &str) -> Option<NodeRef<'a>> {
+        let keys = &self.arena.keys;
+
+        self.index
+            .binary_search_by(|i| (*keys[self.entries[*i as usize].0 as usize]).cmp(key))
+            .ok()
+            .map(|i| self.arena.get(self.entries[self.index[i] as usize].1))
+    }
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, NodeRef<'a>)> + 'a {
+        let arena = self.arena;
+
+        self.entries
+            .iter()
+            .map(move |(k, id)| (&*arena.keys[*k as usize], arena.get(*id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tokenizer::tokenize,
+        treebuilder::{build, testing::parse, Config},
+    };
+
+    use super::*;
+
+    fn source_keys(node: &Node) -> Vec<&str> {
+        match node {
+            Node::Object(o) => o
+                .entries_ordered(KeyOrder::Source)
+                .into_iter()
+                .map(|(key, _)| key.as_str())
+                .collect(),
+            _ => panic!("not an object"),
+        }
+    }
+
+    const INP: &str = r#"{"users": [{"id": 1, "name": "a"}, {"id": 2, "name": "b", "admin": true}], "next": null, "total": 2.5}"#;
+
+    #[test]
+    fn round_trip() {
+        let node = parse(INP);
+        let mut arena = Arena::new();
+        let id = arena.insert(&node);
+
+        assert_eq!(arena.get(id).to_node(), node);
+    }
+
+    #[test]
+    fn accessors() {
+        let mut arena = Arena::new();
+        let id = arena.insert(&parse(INP));
+        let root = arena.get(id);
+        let users = root.get("users").unwrap().as_array().unwrap();
+
+        assert_eq!(users.len(), 2);
+        assert_eq!(users.get(1).unwrap().get("name").unwrap().as_str(), Ok("b"));
+        assert_eq!(users.get(0).unwrap().get("id").unwrap().as_u64(), Ok(1));
+        assert_eq!(root.get("total").unwrap().as_f64(), Ok(2.5));
+        assert!(root.get("next").unwrap().is_null());
+        assert_eq!(
+            root.as_object()
+                .unwrap()
+                .iter()
+                .map(|(key, _)| key)
+                .collect::<Vec<&str>>(),
+            ["users", "next", "total"]
+        );
+    }
+
+    #[test]
+    fn errs_have_spans() {
+        let node = parse(INP);
+        let mut arena = Arena::new();
+        let id = arena.insert(&node);
+        let root = arena.get(id);
+
+        assert_eq!(root.get("total").unwrap().as_i64(), node["total"].as_i64());
+        assert_eq!(root.get("next").unwrap().as_str(), node["next"].as_str());
+        assert_eq!(
+            root.get("missing").unwrap_err(),
+            node.get("missing").unwrap_err()
+        );
+        assert_eq!(
+            root.get("users").unwrap().get_index(5).unwrap_err(),
+            node["users"].get_index(5).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn build_from_toks() {
+        let inps = [
+            INP,
+            r#"{"a": 1, "b": [], "a": {"c": [true]}}"#,
+            r#"{let x = [1, {"y": 2}], "a": x, "b": [x, len(x)], "c": [for v in x: [v]]}"#,
+            "[let x = 1, x, [x, {}]]",
+            r#""a""#,
+        ];
+
+        for inp in inps {
+            let toks = tokenize(inp, &Config::DEFAULT).unwrap();
+            let mut arena = Arena::new();
+            let id = arena.build(&toks, &Config::DEFAULT).unwrap().unwrap();
+
+            assert_eq!(arena.get(id).to_node(), parse(inp), "{}", inp);
+            assert_eq!(
+                arena.get(id).to_node().provenance(),
+                parse(inp).provenance(),
+                "{}",
+                inp
+            );
+        }
+    }
+
+    #[test]
+    fn build_from_toks_errs() {
+        let trailing = Config {
+            allow_trailing_commas: true,
+            ..Config::DEFAULT
+        };
+
+        for inp in [
+            "[1, 2",
+            "[1 2]",
+            "[1,]",
+            r#"{"a": 1,}"#,
+            r#"{"a" 1}"#,
+            r#"{1: 1}"#,
+            r#"{"a": [1,],}"#,
+            "[for v in 1: v]",
+            "[x]",
+            "[",
+        ] {
+            for config in [&Config::DEFAULT, &trailing] {
+                let toks = tokenize(inp, config).unwrap();
+
+                assert_eq!(
+                    Arena::new().build(&toks, config).err(),
+                    build(&toks, config).err(),
+                    "{}",
+                    inp
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn keeps_source_order() {
+        let inp = r#"{"b": 1, "c": {"z": 1, "y": 2}, "a": 2, "b": 3}"#;
+        let toks = tokenize(inp, &Config::DEFAULT).unwrap();
+        let mut arena = Arena::new();
+        let built = arena.build(&toks, &Config::DEFAULT).unwrap().unwrap();
+        let inserted = arena.insert(&parse(inp));
+
+        for id in [built, inserted] {
+            let root = arena.get(id).as_object().unwrap();
+
+            assert_eq!(
+                root.iter().map(|(key, _)| key).collect::<Vec<&str>>(),
+                ["b", "c", "a"]
+            );
+            assert_eq!(root.get("b").unwrap().as_u64(), Ok(3));
+            assert_eq!(root.get("a").unwrap().as_u64(), Ok(2));
+            assert!(root.get("d").is_none());
+
+            let node = arena.get(id).to_node();
+
+            assert_eq!(source_keys(&node), ["b", "c", "a"]);
+            assert_eq!(source_keys(&node["c"]), ["z", "y"]);
+        }
+    }
+
+    #[test]
+    fn full_arena() {
+        assert_eq!(to_u32(u32::MAX as usize), Some(u32::MAX));
+        assert_eq!(to_u32(u32::MAX as usize + 1), None);
+    }
+
+    #[test]
+    fn keys_are_interned() {
+        let mut arena = Arena::new();
+        arena.insert(&parse(INP));
+
+        assert_eq!(arena.keys.len(), 6);
+        assert_eq!(arena.len(), 11);
+    }
+}
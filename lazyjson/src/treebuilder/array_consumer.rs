@@ -2,12 +2,13 @@ use std::rc::Rc;
 
 use crate::queue::Queue;
 use crate::tokenizer::{Token, TokenType};
+use crate::treebuilder::comprehension_consumer::comprehension_consumer;
 use crate::treebuilder::variable_definition_consumer::variable_definition_consumer;
 
 use super::config::Config;
-use super::node::ArrayNode;
+use super::value_consumer::{NodeBuilder, ValBuilder};
 use super::var_dict::VarDict;
-use super::{error::TreebuilderErr, node::Node, value_consumer::value_consumer};
+use super::{error::TreebuilderErr, node::Node};
 
 pub fn array_consumer(
     inp: &mut Queue<Token>,
@@ -20,35 +21,56 @@ pub fn array_consumer(
         return Ok(None);
     }
 
+    consume_arr(&mut NodeBuilder, inp, opn_i, parent_var_dict, config).map(Some)
+}
+
+/// Consumes the rest of an array after the opening bracket at `opn_i`, and
+/// builds it with the builder.
+pub(super) fn consume_arr<B: ValBuilder>(
+    builder: &mut B,
+    inp: &mut Queue<Token>,
+    opn_i: usize,
+    parent_var_dict: &Rc<VarDict>,
+    config: &Config,
+) -> Result<B::Val, TreebuilderErr> {
     if consume_arr_cls(inp, opn_i)? {
-        return Ok(Some(ArrayNode::new(opn_i, inp.idx(), Vec::new()).into()));
+        return builder.arr(Vec::new(), opn_i, inp.idx());
+    }
+
+    let comprehension_i = inp.idx();
+
+    if let Some(entries) = comprehension_consumer(inp, opn_i, parent_var_dict, config)? {
+        if !consume_arr_cls(inp, opn_i)? {
+            return Err(TreebuilderErr::new_not_arr_cls(inp.idx()));
+        }
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| builder.node(entry, comprehension_i, inp.idx()))
+            .collect::<Result<Vec<B::Val>, TreebuilderErr>>()?;
+
+        return builder.arr(entries, opn_i, inp.idx());
     }
 
     let mut entries = Vec::new();
-    let mut var_dict = VarDict::new_with_parent(parent_var_dict);
+    let mut var_dict = Rc::new(VarDict::new_with_parent(parent_var_dict));
 
     loop {
-        if let Some((key, val)) =
-            // TODO: figure out how to do this without cloning
-            variable_definition_consumer(inp, &Rc::new(var_dict.clone()), config)?
-        {
-            var_dict.insert(key, val);
+        if let Some((key, def)) = variable_definition_consumer(inp, &var_dict, config)? {
+            // The nested consumers are done with the scope, so it is no
+            // longer shared and can be extended without copying it.
+            Rc::make_mut(&mut var_dict).insert_def(key, def);
         } else {
-            let entry = value_consumer(
-                inp,
-                // TODO: figure out how to do this without cloning
-                &Rc::new(var_dict.clone()),
-                &Config::DEFAULT,
-            )?
-            .ok_or(TreebuilderErr::new_not_a_val(
-                get_last_tok_idx(&entries) + 1,
-            ))?;
+            let entry_i = inp.idx();
+            let entry = builder
+                .val(inp, &var_dict, &Config::DEFAULT)?
+                .ok_or(TreebuilderErr::new_not_a_val(entry_i))?;
 
             entries.push(entry);
         }
 
         if consume_arr_cls(inp, opn_i)? {
-            return Ok(Some(ArrayNode::new(opn_i, inp.idx(), entries).into()));
+            return builder.arr(entries, opn_i, inp.idx());
         }
 
         consume_val_sep(inp)?;
@@ -60,12 +82,12 @@ pub fn array_consumer(
                 return Err(TreebuilderErr::new_trailing_sep(inp.idx() - 2));
             }
 
-            return Ok(Some(ArrayNode::new(opn_i, inp.idx(), entries).into()));
+            return builder.arr(entries, opn_i, inp.idx());
         }
     }
 }
 
-fn consume_arr_opn<'a>(inp: &'a mut Queue<Token>) -> bool {
+pub(super) fn consume_arr_opn<'a>(inp: &'a mut Queue<Token>) -> bool {
     let t = inp.peek().unwrap();
 
     if t.typ == TokenType::Delimiter && t.val == "[" {
@@ -77,7 +99,10 @@ fn consume_arr_opn<'a>(inp: &'a mut Queue<Token>) -> bool {
     false
 }
 
-fn consume_arr_cls<'a>(inp: &'a mut Queue<Token>, opn_i: usize) -> Result<bool, TreebuilderErr> {
+pub(super) fn consume_arr_cls<'a>(
+    inp: &'a mut Queue<Token>,
+    opn_i: usize,
+) -> Result<bool, TreebuilderErr> {
     let t = inp
         .peek()
         .ok_or(TreebuilderErr::new_unterminated_arr(opn_i))?;
@@ -91,7 +116,7 @@ fn consume_arr_cls<'a>(inp: &'a mut Queue<Token>, opn_i: usize) -> Result<bool,
     Ok(false)
 }
 
-fn consume_val_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
+pub(super) fn consume_val_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
     let t = inp.peek().unwrap();
 
     if t.typ != TokenType::Separator || t.val != "," {
@@ -103,22 +128,15 @@ fn consume_val_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
     Ok(())
 }
 
-fn get_last_tok_idx(entries: &Vec<Node>) -> usize {
-    match entries.last() {
-        None => 0,
-        Some(e) => e.to(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
     use crate::treebuilder::{
-        node::{ArrayNode, BoolNode, NumberNode, ObjectNode, StringNode},
+        node::{ArrayNode, BoolNode, NumberNode, ObjectNode, Provenance, StringNode},
         testing::{
-            new_delimiter, new_equal_assignment_op, new_json_assignment_op, new_kwd, new_num,
-            new_sep, new_str,
+            new_delimiter, new_equal_assignment_op, new_ident, new_json_assignment_op, new_kwd,
+            new_num, new_sep, new_str,
         },
         Config,
     };
@@ -278,13 +296,22 @@ mod tests {
 
     #[test]
     fn use_variable_of_parent() {
-        let inp = &mut Queue::new(vec![new_delimiter("["), new_kwd("foo"), new_delimiter("]")]);
+        let inp = &mut Queue::new(vec![
+            new_delimiter("["),
+            new_ident("foo"),
+            new_delimiter("]"),
+        ]);
 
         let mut var_dict = VarDict::new();
         var_dict.insert("foo".into(), NumberNode::new(0, "10".to_owned()).into());
         let var_dict = &Rc::new(var_dict);
 
-        let exp_arr = ArrayNode::new(0, 3, vec![NumberNode::new(0, "10".to_owned()).into()]);
+        let exp_arr = ArrayNode::new(
+            0,
+            3,
+            vec![Node::Number(NumberNode::new(0, "10".to_owned()))
+                .with_provenance(Provenance::new("foo", 1, 0, 1))],
+        );
 
         assert_eq!(
             array_consumer(inp, var_dict, &Config::DEFAULT),
@@ -297,11 +324,11 @@ mod tests {
         let inp = &mut Queue::new(vec![
             new_delimiter("["),
             new_kwd("let"),
-            new_kwd("foo"),
+            new_ident("foo"),
             new_equal_assignment_op(),
             new_str("bar"),
             new_sep(","),
-            new_kwd("foo"),
+            new_ident("foo"),
             new_delimiter("]"),
         ]);
 
@@ -311,7 +338,13 @@ mod tests {
         assert_eq!(
             array_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
             Ok(Some(
-                ArrayNode::new(0, 8, vec![StringNode::new(4, "bar".to_owned()).into()]).into()
+                ArrayNode::new(
+                    0,
+                    8,
+                    vec![Node::String(StringNode::new(4, "bar".to_owned()))
+                        .with_provenance(Provenance::new("foo", 6, 1, 5))],
+                )
+                .into()
             ))
         )
     }
@@ -324,11 +357,11 @@ mod tests {
         let inp = &mut Queue::new(vec![
             new_delimiter("["),
             new_kwd("let"),
-            new_kwd("foo"),
+            new_ident("foo"),
             new_equal_assignment_op(),
             new_str("bar"),
             new_sep(","),
-            new_kwd("foo"),
+            new_ident("foo"),
             new_sep(","),
             new_delimiter("]"),
         ]);
@@ -339,8 +372,70 @@ mod tests {
         assert_eq!(
             array_consumer(inp, &Rc::new(VarDict::new()), &config),
             Ok(Some(
-                ArrayNode::new(0, 9, vec![StringNode::new(4, "bar".to_owned()).into()]).into()
+                ArrayNode::new(
+                    0,
+                    9,
+                    vec![Node::String(StringNode::new(4, "bar".to_owned()))
+                        .with_provenance(Provenance::new("foo", 6, 1, 5))],
+                )
+                .into()
             ))
         )
     }
+
+    #[test]
+    fn comprehension() {
+        let inp = &mut Queue::new(vec![
+            new_delimiter("["),
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_ident("foo"),
+            new_json_assignment_op(),
+            new_ident("v"),
+            new_delimiter("]"),
+        ]);
+
+        let mut var_dict = VarDict::new();
+        var_dict.insert(
+            "foo".into(),
+            ArrayNode::new(0, 0, vec![BoolNode::new(0, true).into()]).into(),
+        );
+
+        assert_eq!(
+            array_consumer(inp, &Rc::new(var_dict), &Config::DEFAULT),
+            Ok(Some(
+                ArrayNode::new(
+                    0,
+                    8,
+                    vec![Node::Bool(BoolNode::new(0, true))
+                        .with_provenance(Provenance::new("v", 6, 1, 5))],
+                )
+                .into()
+            ))
+        );
+        assert_eq!(inp.next(), None);
+    }
+
+    #[test]
+    fn comprehension_followed_by_entry() {
+        let inp = &mut Queue::new(vec![
+            new_delimiter("["),
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_delimiter("["),
+            new_delimiter("]"),
+            new_json_assignment_op(),
+            new_ident("v"),
+            new_sep(","),
+            new_num("1"),
+            new_delimiter("]"),
+        ]);
+
+        assert_eq!(
+            array_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_not_arr_cls(8))
+        );
+    }
 }
@@ -0,0 +1,732 @@
+use std::cmp::Ordering;
+
+use super::{
+    node::{ArrayNode, BoolNode, Node, NumberNode, StringNode},
+    semantic::EqConfig,
+};
+
+/// The reason a built-in function rejected its arguments.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum BuiltinErr {
+    /// The argument at the index has an unexpected type. `expected` describes
+    /// the accepted type(s), for example "a string or an array".
+    Typ { arg: usize, expected: &'static str },
+    /// The argument at the index has the correct type, but an invalid value.
+    Val { arg: usize, reason: &'static str },
+}
+
+type BuiltinFn = fn(&[Node], usize, usize) -> Result<Node, BuiltinErr>;
+
+/// A function that can be called from within a document (`len(list)`).
+pub struct Builtin {
+    pub name: &'static str,
+    pub min_args: usize,
+    pub max_args: usize,
+    func: BuiltinFn,
+}
+
+impl Builtin {
+    /// Calls the function. `from` and `to` are the token indices of the whole
+    /// call, and are used as the span of newly created nodes.
+    ///
+    /// The amount of arguments has to be checked beforehand.
+    pub fn call(&self, args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+        (self.func)(args, from, to)
+    }
+}
+
+/// All built-in functions. Their names can not be used as variable names.
+pub static BUILTINS: [Builtin; 18] = [
+    builtin("contains", 2, 2, contains),
+    builtin("join", 2, 2, join),
+    builtin("keys", 1, 1, keys),
+    builtin("len", 1, 1, len),
+    builtin("lower", 1, 1, lower),
+    builtin("max", 1, 1, max),
+    builtin("min", 1, 1, min),
+    builtin("range", 1, 3, range),
+    builtin("replace", 3, 3, replace),
+    builtin("sort", 1, 1, sort),
+    builtin("split", 2, 2, split),
+    builtin("sum", 1, 1, sum),
+    builtin("to_number", 1, 1, to_number),
+    builtin("to_string", 1, 1, to_string),
+    builtin("trim", 1, 1, trim),
+    builtin("unique", 1, 1, unique),
+    builtin("upper", 1, 1, upper),
+    builtin("values", 1, 1, values),
+];
+
+const fn builtin(name: &'static str, min_args: usize, max_args: usize, func: BuiltinFn) -> Builtin {
+    Builtin {
+        name,
+        min_args,
+        max_args,
+        func,
+    }
+}
+
+pub fn get_builtin(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|b| b.name == name)
+}
+
+fn contains(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let found = match (&args[0], &args[1]) {
+        (Node::String(s), Node::String(needle)) => s.val.contains(&needle.val),
+        (Node::String(_), _) => {
+            return Err(BuiltinErr::Typ {
+                arg: 1,
+                expected: "a string",
+            })
+        }
+        (Node::Array(a), needle) => a.entries.iter().any(|e| eq_val(e, needle)),
+        (Node::Object(o), Node::String(key)) => o.entries.contains_key(&key.val),
+        (Node::Object(_), _) => {
+            return Err(BuiltinErr::Typ {
+                arg: 1,
+                expected: "a string",
+            })
+        }
+        _ => {
+            return Err(BuiltinErr::Typ {
+                arg: 0,
+                expected: "a string, an array or an object",
+            })
+        }
+    };
+
+    Ok(new_bool(from, to, found))
+}
+
+fn join(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let strs = get_strs(&args[0], 0)?;
+    let sep = get_str(&args[1], 1)?;
+
+    Ok(new_str(from, to, strs.join(sep)))
+}
+
+fn keys(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let keys = get_sorted_entries(&args[0], 0)?
+        .into_iter()
+        .map(|(key, _)| new_str(from, to, key.clone()))
+        .collect();
+
+    Ok(new_arr(from, to, keys))
+}
+
+fn len(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let len = match &args[0] {
+        Node::Array(a) => a.entries.len(),
+        Node::Object(o) => o.entries.len(),
+        Node::String(s) => s.val.chars().count(),
+        _ => {
+            return Err(BuiltinErr::Typ {
+                arg: 0,
+                expected: "a string, an array or an object",
+            })
+        }
+    };
+
+    Ok(new_num(from, to, len.to_string()))
+}
+
+fn lower(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    Ok(new_str(from, to, get_str(&args[0], 0)?.to_lowercase()))
+}
+
+fn max(args: &[Node], _: usize, _: usize) -> Result<Node, BuiltinErr> {
+    get_extreme(&args[0], Ordering::Greater)
+}
+
+fn min(args: &[Node], _: usize, _: usize) -> Result<Node, BuiltinErr> {
+    get_extreme(&args[0], Ordering::Less)
+}
+
+fn range(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let (start, end) = match args.len() {
+        1 => (0, get_int(&args[0], 0)?),
+        _ => (get_int(&args[0], 0)?, get_int(&args[1], 1)?),
+    };
+    let step = match args.get(2) {
+        None => 1,
+        Some(step) => get_int(step, 2)?,
+    };
+
+    if step == 0 {
+        return Err(BuiltinErr::Val {
+            arg: 2,
+            reason: "the step can not be zero",
+        });
+    }
+
+    let (start, end, step) = (i128::from(start), i128::from(end), i128::from(step));
+    let len = match (end - start).signum() == step.signum() {
+        true => ((end - start).abs() + step.abs() - 1) / step.abs(),
+        false => 0,
+    };
+
+    // The error points at the end of the range.
+    if len > MAX_RANGE_LEN {
+        return Err(BuiltinErr::Val {
+            arg: args.len().min(2) - 1,
+            reason: "the range is too large",
+        });
+    }
+
+    let entries = (0..len)
+        .map(|k| new_num(from, to, (start + k * step).to_string()))
+        .collect();
+
+    Ok(new_arr(from, to, entries))
+}
+
+fn replace(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let s = get_str(&args[0], 0)?;
+    let pattern = get_str(&args[1], 1)?;
+    let replacement = get_str(&args[2], 2)?;
+
+    if pattern.is_empty() {
+        return Err(BuiltinErr::Val {
+            arg: 1,
+            reason: "the pattern can not be empty",
+        });
+    }
+
+    Ok(new_str(from, to, s.replace(pattern, replacement)))
+}
+
+fn sort(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let mut entries = get_entries(&args[0], 0)?.to_vec();
+
+    if entries.iter().all(|e| matches!(e, Node::String(_))) {
+        entries.sort_by(|a, b| match (a, b) {
+            (Node::String(a), Node::String(b)) => a.val.cmp(&b.val),
+            _ => Ordering::Equal,
+        });
+    } else {
+        let mut nums = entries
+            .into_iter()
+            .map(|e| Ok((get_num(&e).ok_or(MIXED_TYPES)?, e)))
+            .collect::<Result<Vec<(f64, Node)>, BuiltinErr>>()?;
+
+        nums.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        entries = nums.into_iter().map(|(_, e)| e).collect();
+    }
+
+    Ok(new_arr(from, to, entries))
+}
+
+fn split(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let s = get_str(&args[0], 0)?;
+    let sep = get_str(&args[1], 1)?;
+
+    if sep.is_empty() {
+        return Err(BuiltinErr::Val {
+            arg: 1,
+            reason: "the separator can not be empty",
+        });
+    }
+
+    let parts = s
+        .split(sep)
+        .map(|part| new_str(from, to, part.to_owned()))
+        .collect();
+
+    Ok(new_arr(from, to, parts))
+}
+
+fn sum(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let nums = get_entries(&args[0], 0)?
+        .iter()
+        .map(|e| match e {
+            Node::Number(n) => Ok(&n.val),
+            _ => Err(ONLY_NUMBERS),
+        })
+        .collect::<Result<Vec<&String>, BuiltinErr>>()?;
+
+    // Integers are summed up exactly, as long as they do not overflow.
+    let int_sum = nums
+        .iter()
+        .try_fold(0i64, |sum, n| sum.checked_add(n.parse().ok()?));
+
+    if let Some(int_sum) = int_sum {
+        return Ok(new_num(from, to, int_sum.to_string()));
+    }
+
+    let sum = nums
+        .iter()
+        .map(|n| n.parse::<f64>().map_err(|_| ONLY_NUMBERS))
+        .sum::<Result<f64, BuiltinErr>>()?;
+
+    if !sum.is_finite() {
+        return Err(BuiltinErr::Val {
+            arg: 0,
+            reason: "the sum is too large",
+        });
+    }
+
+    Ok(new_num(from, to, sum.to_string()))
+}
+
+fn to_number(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    match &args[0] {
+        Node::Number(n) => Ok(new_num(from, to, n.val.clone())),
+        Node::String(s) => {
+            let val = s.val.trim();
+            let is_num = is_json_number(val) && val.parse::<f64>().is_ok_and(|n| n.is_finite());
+
+            if !is_num {
+                return Err(BuiltinErr::Val {
+                    arg: 0,
+                    reason: "the string does not contain a number",
+                });
+            }
+
+            Ok(new_num(from, to, val.to_owned()))
+        }
+        _ => Err(BuiltinErr::Typ {
+            arg: 0,
+            expected: "a string or a number",
+        }),
+    }
+}
+
+fn to_string(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let val = match &args[0] {
+        Node::Bool(b) => b.val.to_string(),
+        Node::Null(_) => "null".to_owned(),
+        Node::Number(n) => n.val.clone(),
+        Node::String(s) => s.val.clone(),
+        _ => {
+            return Err(BuiltinErr::Typ {
+                arg: 0,
+                expected: "a bool, null, a number or a string",
+            })
+        }
+    };
+
+    Ok(new_str(from, to, val))
+}
+
+fn trim(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    Ok(new_str(from, to, get_str(&args[0], 0)?.trim().to_owned()))
+}
+
+fn unique(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let mut entries: Vec<Node> = Vec::new();
+
+    for e in get_entries(&args[0], 0)? {
+        if !entries.iter().any(|u| eq_val(u, e)) {
+            entries.push(e.clone());
+        }
+    }
+
+    Ok(new_arr(from, to, entries))
+}
+
+fn upper(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    Ok(new_str(from, to, get_str(&args[0], 0)?.to_uppercase()))
+}
+
+fn values(args: &[Node], from: usize, to: usize) -> Result<Node, BuiltinErr> {
+    let vals = get_sorted_entries(&args[0], 0)?
+        .into_iter()
+        .map(|(_, val)| val.clone())
+        .collect();
+
+    Ok(new_arr(from, to, vals))
+}
+
+/// The maximum number of entries `range` creates, so that a query can not
+/// exhaust the memory.
+const MAX_RANGE_LEN: i128 = 1_000_000;
+
+const MIXED_TYPES: BuiltinErr = BuiltinErr::Val {
+    arg: 0,
+    reason: "the array has to contain only strings or only numbers",
+};
+
+const ONLY_NUMBERS: BuiltinErr = BuiltinErr::Val {
+    arg: 0,
+    reason: "the array has to contain only numbers",
+};
+
+fn get_entries(n: &Node, arg: usize) -> Result<&[Node], BuiltinErr> {
+    match n {
+        Node::Array(a) => Ok(&a.entries),
+        _ => Err(BuiltinErr::Typ {
+            arg,
+            expected: "an array",
+        }),
+    }
+}
+
+/// Objects are unordered, so the entries are sorted by their keys to get a
+/// stable result.
+fn get_sorted_entries(n: &Node, arg: usize) -> Result<Vec<(&String, &Node)>, BuiltinErr> {
+    match n {
+        Node::Object(o) => {
+            let mut entries = o.entries.iter().collect::<Vec<(&String, &Node)>>();
+            entries.sort_by_key(|&(key, _)| key);
+
+            Ok(entries)
+        }
+        _ => Err(BuiltinErr::Typ {
+            arg,
+            expected: "an object",
+        }),
+    }
+}
+
+fn get_extreme(n: &Node, ord: Ordering) -> Result<Node, BuiltinErr> {
+    let mut extreme: Option<(f64, &Node)> = None;
+
+    for e in get_entries(n, 0)? {
+        let num = get_num(e).ok_or(ONLY_NUMBERS)?;
+
+        match extreme {
+            Some((ext, _)) if num.partial_cmp(&ext) != Some(ord) => {}
+            _ => extreme = Some((num, e)),
+        }
+    }
+
+    match extreme {
+        None => Err(BuiltinErr::Val {
+            arg: 0,
+            reason: "the array can not be empty",
+        }),
+        Some((_, e)) => Ok(e.clone()),
+    }
+}
+
+fn get_int(n: &Node, arg: usize) -> Result<i64, BuiltinErr> {
+    match n {
+        Node::Number(num) => num.val.parse().map_err(|_| BuiltinErr::Val {
+            arg,
+            reason: "the number has to be an integer",
+        }),
+        _ => Err(BuiltinErr::Typ {
+            arg,
+            expected: "a number",
+        }),
+    }
+}
+
+fn get_num(n: &Node) -> Option<f64> {
+    match n {
+        Node::Number(num) => num.val.parse().ok(),
+        _ => None,
+    }
+}
+
+fn get_str(n: &Node, arg: usize) -> Result<&str, BuiltinErr> {
+    match n {
+        Node::String(s) => Ok(&s.val),
+        _ => Err(BuiltinErr::Typ {
+            arg,
+            expected: "a string",
+        }),
+    }
+}
+
+fn get_strs(n: &Node, arg: usize) -> Result<Vec<&str>, BuiltinErr> {
+    get_entries(n, arg)?
+        .iter()
+        .map(|e| match e {
+            Node::String(s) => Ok(s.val.as_str()),
+            _ => Err(BuiltinErr::Val {
+                arg,
+                reason: "the array has to contain only strings",
+            }),
+        })
+        .collect()
+}
+
+/// Returns whether the string is a number in the JSON grammar, like `-1.5e3`
+/// (but not `+1`, `01`, `.5` or `1.`).
+fn is_json_number(val: &str) -> bool {
+    let mut rest = val.strip_prefix('-').unwrap_or(val);
+    let int = take_digits(&mut rest);
+
+    if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+        return false;
+    }
+
+    if let Some(frac) = rest.strip_prefix('.') {
+        rest = frac;
+
+        if take_digits(&mut rest).is_empty() {
+            return false;
+        }
+    }
+
+    if let Some(exp) = rest.strip_prefix(['e', 'E']) {
+        rest = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+
+        if take_digits(&mut rest).is_empty() {
+            return false;
+        }
+    }
+
+    rest.is_empty()
+}
+
+/// Splits off the leading ASCII digits of the string, and returns them.
+fn take_digits<'a>(s: &mut &'a str) -> &'a str {
+    let len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, rest) = s.split_at(len);
+    *s = rest;
+
+    digits
+}
+
+/// Compares the values of two nodes, ignoring their spans. Numbers are compared
+/// by their numeric value (`1.0` equals `1`).
+pub(super) fn eq_val(a: &Node, b: &Node) -> bool {
+    a.eq_semantic(b, &EqConfig::DEFAULT)
+}
+
+fn new_arr(from: usize, to: usize, entries: Vec<Node>) -> Node {
+    ArrayNode::new(from, to, entries).into()
+}
+
+fn new_bool(from: usize, to: usize, val: bool) -> Node {
+    let mut n = BoolNode::new(from, val);
+    n.to = to;
+    n.into()
+}
+
+fn new_num(from: usize, to: usize, val: String) -> Node {
+    let mut n = NumberNode::new(from, val);
+    n.to = to;
+    n.into()
+}
+
+fn new_str(from: usize, to: usize, val: String) -> Node {
+    let mut n = StringNode::new(from, val);
+    n.to = to;
+    n.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::treebuilder::node::{NullNode, ObjectNode};
+
+    use super::*;
+
+    fn call(name: &str, args: Vec<Node>) -> Result<Node, BuiltinErr> {
+        get_builtin(name).unwrap().call(&args, 0, 1)
+    }
+
+    fn num(val: &str) -> Node {
+        new_num(0, 1, val.to_owned())
+    }
+
+    fn str(val: &str) -> Node {
+        new_str(0, 1, val.to_owned())
+    }
+
+    fn arr(entries: Vec<Node>) -> Node {
+        new_arr(0, 1, entries)
+    }
+
+    #[test]
+    fn names_are_sorted_and_unique() {
+        let names = BUILTINS.iter().map(|b| b.name).collect::<Vec<&str>>();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn unknown_builtin() {
+        assert!(get_builtin("foo").is_none());
+    }
+
+    #[test]
+    fn len_counts_chars() {
+        assert_eq!(call("len", vec![str("äöü")]), Ok(num("3")));
+    }
+
+    #[test]
+    fn len_of_number() {
+        assert_eq!(
+            call("len", vec![num("1")]),
+            Err(BuiltinErr::Typ {
+                arg: 0,
+                expected: "a string, an array or an object",
+            })
+        );
+    }
+
+    #[test]
+    fn keys_and_values_are_sorted_by_key() {
+        let obj: Node = ObjectNode::new(
+            0,
+            1,
+            HashMap::from([("b".to_owned(), num("2")), ("a".to_owned(), num("1"))]),
+        )
+        .into();
+
+        assert_eq!(
+            call("keys", vec![obj.clone()]),
+            Ok(arr(vec![str("a"), str("b")]))
+        );
+        assert_eq!(call("values", vec![obj]), Ok(arr(vec![num("1"), num("2")])));
+    }
+
+    #[test]
+    fn range_with_negative_step() {
+        assert_eq!(
+            call("range", vec![num("3"), num("0"), num("-1")]),
+            Ok(arr(vec![num("3"), num("2"), num("1")]))
+        );
+    }
+
+    #[test]
+    fn range_at_the_end_of_i64() {
+        assert_eq!(
+            call(
+                "range",
+                vec![
+                    num("9223372036854775806"),
+                    num("9223372036854775807"),
+                    num("10")
+                ]
+            ),
+            Ok(arr(vec![num("9223372036854775806")]))
+        );
+        assert_eq!(
+            call(
+                "range",
+                vec![
+                    num("-9223372036854775807"),
+                    num("-9223372036854775808"),
+                    num("-1")
+                ]
+            ),
+            Ok(arr(vec![num("-9223372036854775807")]))
+        );
+    }
+
+    #[test]
+    fn range_too_large() {
+        assert_eq!(
+            call("range", vec![num("9223372036854775807")]),
+            Err(BuiltinErr::Val {
+                arg: 0,
+                reason: "the range is too large",
+            })
+        );
+        assert_eq!(
+            call("range", vec![num("0"), num("1000001")]),
+            Err(BuiltinErr::Val {
+                arg: 1,
+                reason: "the range is too large",
+            })
+        );
+        assert_eq!(
+            call("range", vec![num("0"), num("2000000"), num("2")])
+                .map(|n| n.as_array().map(<[Node]>::len)),
+            Ok(Ok(1_000_000))
+        );
+    }
+
+    #[test]
+    fn range_with_zero_step() {
+        assert_eq!(
+            call("range", vec![num("0"), num("3"), num("0")]),
+            Err(BuiltinErr::Val {
+                arg: 2,
+                reason: "the step can not be zero",
+            })
+        );
+    }
+
+    #[test]
+    fn sort_mixed_types() {
+        assert_eq!(
+            call("sort", vec![arr(vec![num("1"), str("a")])]),
+            Err(MIXED_TYPES)
+        );
+    }
+
+    #[test]
+    fn sort_strings() {
+        assert_eq!(
+            call("sort", vec![arr(vec![str("b"), str("a")])]),
+            Ok(arr(vec![str("a"), str("b")]))
+        );
+    }
+
+    #[test]
+    fn sum_of_non_numbers() {
+        assert_eq!(
+            call("sum", vec![arr(vec![num("1"), str("2")])]),
+            Err(ONLY_NUMBERS)
+        );
+    }
+
+    #[test]
+    fn sum_out_of_range() {
+        assert_eq!(
+            call("sum", vec![arr(vec![num("1e308"), num("1e308")])]),
+            Err(BuiltinErr::Val {
+                arg: 0,
+                reason: "the sum is too large",
+            })
+        );
+    }
+
+    #[test]
+    fn split_and_replace() {
+        assert_eq!(
+            call("split", vec![str("a.b"), str(".")]),
+            Ok(arr(vec![str("a"), str("b")]))
+        );
+        assert_eq!(
+            call("replace", vec![str("a.b"), str("."), str("-")]),
+            Ok(str("a-b"))
+        );
+    }
+
+    #[test]
+    fn to_string_of_null() {
+        assert_eq!(
+            call("to_string", vec![NullNode::new(0).into()]),
+            Ok(str("null"))
+        );
+    }
+
+    #[test]
+    fn to_number_rejects_special_values() {
+        for val in [
+            "inf", "NaN", "", "1.", "+1", ".5", "01", "-", "1e", "1e400", "0x1",
+        ] {
+            assert_eq!(
+                call("to_number", vec![str(val)]),
+                Err(BuiltinErr::Val {
+                    arg: 0,
+                    reason: "the string does not contain a number",
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn to_number_accepts_json_numbers() {
+        for val in ["0", "-0.5", "10", "1.5e3", "2E-2", " 7 "] {
+            assert_eq!(call("to_number", vec![str(val)]), Ok(num(val.trim())));
+        }
+    }
+
+    #[test]
+    fn min_of_empty_array() {
+        assert!(call("min", vec![arr(vec![])]).is_err());
+    }
+}
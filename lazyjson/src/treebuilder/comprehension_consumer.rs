@@ -0,0 +1,623 @@
+use std::rc::Rc;
+
+use crate::{
+    queue::Queue,
+    tokenizer::{Token, TokenType},
+};
+
+use super::{
+    builtins::get_builtin,
+    node::{Node, NumberNode, StringNode},
+    value_consumer,
+    var_dict::VarDef,
+    Config, TreebuilderErr, VarDict,
+};
+
+/// Consumes an array comprehension like `for r in regions: { "region": r }`
+/// (optionally filtered with `if <condition>` before the `:`), and returns the
+/// generated entries.
+///
+/// Arrays are iterated by value (`for v in arr`) or by index and value
+/// (`for i, v in arr`). Objects are iterated by key (`for k in obj`) or by key
+/// and value (`for k, v in obj`), in the order of their sorted keys. The loop
+/// variables are only visible to the condition and the body.
+///
+/// The closing bracket of the array is **not** consumed.
+pub fn comprehension_consumer(
+    inp: &mut Queue<Token>,
+    opn_i: usize,
+    var_dict: &Rc<VarDict>,
+    config: &Config,
+) -> Result<Option<Vec<Node>>, TreebuilderErr> {
+    let for_i = inp.idx();
+
+    if !consume_kwd(inp, "for") {
+        return Ok(None);
+    }
+
+    let loop_vars = consume_loop_vars(inp, opn_i)?;
+
+    consume_in_kwd(inp, opn_i)?;
+
+    let iterable_i = inp.idx();
+    let iterable = match inp.peek() {
+        None => return Err(TreebuilderErr::new_unterminated_arr(opn_i)),
+        Some(_) => value_consumer(inp, var_dict, config)?.unwrap(),
+    };
+    let items = get_items(&iterable, &loop_vars, iterable_i, inp.idx())?;
+    let head_to = inp.idx();
+
+    let cond_i = match consume_kwd(inp, "if") {
+        true => {
+            let cond_i = inp.idx();
+            skip_value(inp, opn_i)?;
+            Some(cond_i)
+        }
+        false => None,
+    };
+
+    consume_body_assignment(inp, opn_i)?;
+
+    let body_i = inp.idx();
+    skip_value(inp, opn_i)?;
+    let end_i = inp.idx();
+
+    let mut entries = Vec::new();
+
+    for item in items {
+        let mut item_var_dict = VarDict::new_with_parent(var_dict);
+
+        // The head of the comprehension (`for v in arr`) is considered to be
+        // the definition of the loop variables.
+        for ((name, _), val) in loop_vars.iter().zip(item) {
+            item_var_dict.insert_def(name.clone(), VarDef::new(for_i, head_to, val));
+        }
+
+        let item_var_dict = Rc::new(item_var_dict);
+
+        if let Some(cond_i) = cond_i {
+            inp.seek(cond_i);
+
+            if !consume_cond(inp, &item_var_dict, config)? {
+                continue;
+            }
+        }
+
+        inp.seek(body_i);
+        entries.push(value_consumer(inp, &item_var_dict, config)?.unwrap());
+    }
+
+    inp.seek(end_i);
+
+    Ok(Some(entries))
+}
+
+/// Returns `true` if the keyword was found (and consumed).
+fn consume_kwd(inp: &mut Queue<Token>, kwd: &str) -> bool {
+    match inp.peek() {
+        Some(t) if t.typ == TokenType::KeywordLiteral && t.val == kwd => {
+            inp.next();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// The ok path returns the names of the loop variables, together with the
+/// index of the token they were declared at.
+fn consume_loop_vars(
+    inp: &mut Queue<Token>,
+    opn_i: usize,
+) -> Result<Vec<(String, usize)>, TreebuilderErr> {
+    let mut vars = vec![consume_loop_var(inp, opn_i)?];
+
+    if let Some(t) = inp.peek() {
+        if t.typ == TokenType::Separator && t.val == "," {
+            inp.next();
+            vars.push(consume_loop_var(inp, opn_i)?);
+        }
+    }
+
+    Ok(vars)
+}
+
+fn consume_loop_var(
+    inp: &mut Queue<Token>,
+    opn_i: usize,
+) -> Result<(String, usize), TreebuilderErr> {
+    let i = inp.idx();
+    let t = inp
+        .peek()
+        .ok_or(TreebuilderErr::new_unterminated_arr(opn_i))?;
+
+    match t.typ {
+        TokenType::Identifier if get_builtin(&t.val).is_some() => {
+            Err(TreebuilderErr::new_shadowed_builtin(i))
+        }
+        TokenType::Identifier => Ok((inp.next().unwrap().val.clone(), i)),
+        TokenType::KeywordLiteral => Err(TreebuilderErr::new_reserved_var_name(i)),
+        _ => Err(TreebuilderErr::new_not_var_name(i)),
+    }
+}
+
+fn consume_in_kwd(inp: &mut Queue<Token>, opn_i: usize) -> Result<(), TreebuilderErr> {
+    if inp.peek().is_none() {
+        return Err(TreebuilderErr::new_unterminated_arr(opn_i));
+    }
+
+    if !consume_kwd(inp, "in") {
+        return Err(TreebuilderErr::new_not_in_kwd(inp.idx()));
+    }
+
+    Ok(())
+}
+
+/// Consumes the `:` separating the head of the comprehension from its body.
+fn consume_body_assignment(inp: &mut Queue<Token>, opn_i: usize) -> Result<(), TreebuilderErr> {
+    let i = inp.idx();
+    let t = inp
+        .next()
+        .ok_or(TreebuilderErr::new_unterminated_arr(opn_i))?;
+
+    if t.typ != TokenType::JsonAssignmentOperator {
+        return Err(TreebuilderErr::new_not_an_assignment(i));
+    }
+
+    Ok(())
+}
+
+/// Splits the iterable into the values that are assigned to the loop variables
+/// on each iteration.
+fn get_items(
+    iterable: &Node,
+    loop_vars: &[(String, usize)],
+    from: usize,
+    to: usize,
+) -> Result<Vec<Vec<Node>>, TreebuilderErr> {
+    let with_key = loop_vars.len() > 1;
+    let key_i = loop_vars[0].1;
+
+    match iterable {
+        Node::Array(a) => Ok(a
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| match with_key {
+                true => vec![
+                    NumberNode::new(key_i, idx.to_string()).into(),
+                    entry.clone(),
+                ],
+                false => vec![entry.clone()],
+            })
+            .collect()),
+        Node::Object(o) => {
+            let mut entries = o.entries.iter().collect::<Vec<(&String, &Node)>>();
+            entries.sort_by_key(|&(key, _)| key);
+
+            Ok(entries
+                .into_iter()
+                .map(|(key, val)| {
+                    let key = StringNode::new(key_i, key.clone()).into();
+
+                    match with_key {
+                        true => vec![key, val.clone()],
+                        false => vec![key],
+                    }
+                })
+                .collect())
+        }
+        _ => Err(TreebuilderErr::new_not_iterable(from, to).with_provenance(iterable.provenance())),
+    }
+}
+
+/// The ok path returns if the condition evaluated to `true`.
+fn consume_cond(
+    inp: &mut Queue<Token>,
+    var_dict: &Rc<VarDict>,
+    config: &Config,
+) -> Result<bool, TreebuilderErr> {
+    let from = inp.idx();
+
+    match value_consumer(inp, var_dict, config)?.unwrap() {
+        Node::Bool(b) => Ok(b.val),
+        n => Err(TreebuilderErr::new_not_a_bool(from, inp.idx()).with_provenance(n.provenance())),
+    }
+}
+
+/// Advances the queue past the next value, without actually building it. This
+/// is used to find the end of the condition and body, which are later consumed
+/// once per iteration (and possibly never).
+fn skip_value(inp: &mut Queue<Token>, opn_i: usize) -> Result<(), TreebuilderErr> {
+    let i = inp.idx();
+    let t = inp
+        .peek()
+        .ok_or(TreebuilderErr::new_unterminated_arr(opn_i))?;
+
+    let is_val_start = match t.typ {
+        TokenType::Delimiter => t.val == "[" || t.val == "{",
+        TokenType::Identifier
+        | TokenType::KeywordLiteral
+        | TokenType::NumberLiteral
+        | TokenType::StringLiteral => true,
+        _ => false,
+    };
+
+    if !is_val_start {
+        return Err(TreebuilderErr::new_not_a_val(i));
+    }
+
+    let mut depth = 0;
+
+    loop {
+        let t = inp
+            .next()
+            .ok_or(TreebuilderErr::new_unterminated_arr(opn_i))?;
+
+        if t.typ == TokenType::Delimiter {
+            match t.val.as_str() {
+                "(" | "[" | "{" => depth += 1,
+                _ => depth -= 1,
+            }
+        }
+
+        // A function name is followed by its argument group.
+        if depth == 0 && t.typ == TokenType::Identifier && is_call_opn(inp.peek()) {
+            continue;
+        }
+
+        if depth == 0 {
+            return Ok(());
+        }
+    }
+}
+
+fn is_call_opn(t: Option<&Token>) -> bool {
+    matches!(t, Some(t) if t.typ == TokenType::Delimiter && t.val == "(")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::treebuilder::{
+        node::{ArrayNode, BoolNode, NullNode, ObjectNode, Provenance},
+        testing::{
+            new_delimiter, new_ident, new_json_assignment_op, new_kwd, new_num, new_sep, new_str,
+        },
+    };
+
+    use super::*;
+
+    fn new_var_dict(vars: Vec<(&str, Node)>) -> Rc<VarDict> {
+        let mut var_dict = VarDict::new();
+
+        for (name, val) in vars {
+            var_dict.insert(name.to_owned(), val);
+        }
+
+        Rc::new(var_dict)
+    }
+
+    fn used<N: Into<Node>>(node: N, name: &str, usage_i: usize, head_to: usize) -> Node {
+        node.into()
+            .with_provenance(Provenance::new(name, usage_i, 0, head_to))
+    }
+
+    #[test]
+    fn non_comprehension() {
+        let inp = &mut Queue::new(vec![new_ident("foo")]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Ok(None)
+        );
+        assert_eq!(inp.next(), Some(&new_ident("foo")));
+    }
+
+    #[test]
+    fn iterate_array() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("r"),
+            new_kwd("in"),
+            new_ident("regions"),
+            new_json_assignment_op(),
+            new_delimiter("{"),
+            new_str("region"),
+            new_json_assignment_op(),
+            new_ident("r"),
+            new_delimiter("}"),
+            new_delimiter("]"),
+        ]);
+        let var_dict = new_var_dict(vec![(
+            "regions",
+            ArrayNode::new(
+                0,
+                0,
+                vec![
+                    StringNode::new(0, "eu".to_owned()).into(),
+                    StringNode::new(0, "us".to_owned()).into(),
+                ],
+            )
+            .into(),
+        )]);
+
+        let mut eu = HashMap::new();
+        eu.insert(
+            "region".to_owned(),
+            used(StringNode::new(0, "eu".to_owned()), "r", 8, 4),
+        );
+        let mut us = HashMap::new();
+        us.insert(
+            "region".to_owned(),
+            used(StringNode::new(0, "us".to_owned()), "r", 8, 4),
+        );
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &var_dict, &Config::DEFAULT),
+            Ok(Some(vec![
+                ObjectNode::new(5, 10, eu).into(),
+                ObjectNode::new(5, 10, us).into(),
+            ]))
+        );
+        // Only the closing bracket of the array should be left.
+        assert_eq!(inp.next(), Some(&new_delimiter("]")));
+    }
+
+    #[test]
+    fn iterate_array_with_index() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("i"),
+            new_sep(","),
+            new_ident("v"),
+            new_kwd("in"),
+            new_delimiter("["),
+            new_kwd("null"),
+            new_delimiter("]"),
+            new_json_assignment_op(),
+            new_delimiter("["),
+            new_ident("i"),
+            new_sep(","),
+            new_ident("v"),
+            new_delimiter("]"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Ok(Some(vec![ArrayNode::new(
+                9,
+                14,
+                vec![
+                    used(NumberNode::new(1, "0".to_owned()), "i", 10, 8),
+                    used(NullNode::new(6), "v", 12, 8),
+                ],
+            )
+            .into()]))
+        );
+    }
+
+    #[test]
+    fn iterate_object_entries() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("k"),
+            new_sep(","),
+            new_ident("v"),
+            new_kwd("in"),
+            new_ident("obj"),
+            new_json_assignment_op(),
+            new_delimiter("["),
+            new_ident("k"),
+            new_sep(","),
+            new_ident("v"),
+            new_delimiter("]"),
+        ]);
+
+        let mut entries = HashMap::new();
+        entries.insert("b".to_owned(), NullNode::new(0).into());
+        entries.insert("a".to_owned(), BoolNode::new(0, true).into());
+        let var_dict = new_var_dict(vec![("obj", ObjectNode::new(0, 0, entries).into())]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &var_dict, &Config::DEFAULT),
+            Ok(Some(vec![
+                ArrayNode::new(
+                    7,
+                    12,
+                    vec![
+                        used(StringNode::new(1, "a".to_owned()), "k", 8, 6),
+                        used(BoolNode::new(0, true), "v", 10, 6),
+                    ],
+                )
+                .into(),
+                ArrayNode::new(
+                    7,
+                    12,
+                    vec![
+                        used(StringNode::new(1, "b".to_owned()), "k", 8, 6),
+                        used(NullNode::new(0), "v", 10, 6),
+                    ],
+                )
+                .into(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn iterate_object_keys() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("k"),
+            new_kwd("in"),
+            new_ident("obj"),
+            new_json_assignment_op(),
+            new_ident("k"),
+        ]);
+
+        let mut entries = HashMap::new();
+        entries.insert("foo".to_owned(), NullNode::new(0).into());
+        let var_dict = new_var_dict(vec![("obj", ObjectNode::new(0, 0, entries).into())]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &var_dict, &Config::DEFAULT),
+            Ok(Some(vec![used(
+                StringNode::new(1, "foo".to_owned()),
+                "k",
+                5,
+                4
+            )]))
+        );
+    }
+
+    #[test]
+    fn filter() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_delimiter("["),
+            new_kwd("true"),
+            new_sep(","),
+            new_kwd("false"),
+            new_delimiter("]"),
+            new_kwd("if"),
+            new_ident("v"),
+            new_json_assignment_op(),
+            new_num("1"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Ok(Some(vec![NumberNode::new(11, "1".to_owned()).into()]))
+        );
+        assert_eq!(inp.next(), None);
+    }
+
+    #[test]
+    fn filter_not_a_bool() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_delimiter("["),
+            new_num("1"),
+            new_delimiter("]"),
+            new_kwd("if"),
+            new_ident("v"),
+            new_json_assignment_op(),
+            new_ident("v"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_not_a_bool(7, 8)
+                .with_provenance(Some(&Provenance::new("v", 7, 0, 6))))
+        );
+    }
+
+    #[test]
+    fn empty_iterable_skips_body() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_delimiter("["),
+            new_delimiter("]"),
+            new_json_assignment_op(),
+            new_delimiter("{"),
+            new_str("key"),
+            new_json_assignment_op(),
+            new_ident("v"),
+            new_delimiter("}"),
+            new_delimiter("]"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Ok(Some(Vec::new()))
+        );
+        assert_eq!(inp.next(), Some(&new_delimiter("]")));
+    }
+
+    #[test]
+    fn not_iterable() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_num("1"),
+            new_json_assignment_op(),
+            new_ident("v"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_not_iterable(3, 4))
+        );
+    }
+
+    #[test]
+    fn shadowed_builtin_loop_variable() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("keys"),
+            new_kwd("in"),
+            new_ident("regions"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_shadowed_builtin(1))
+        );
+    }
+
+    #[test]
+    fn reserved_loop_variable_name() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_kwd("in"),
+            new_kwd("in"),
+            new_ident("regions"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_reserved_var_name(1))
+        );
+    }
+
+    #[test]
+    fn missing_in_kwd() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("v"),
+            new_ident("regions"),
+            new_json_assignment_op(),
+            new_ident("v"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_not_in_kwd(2))
+        );
+    }
+
+    #[test]
+    fn missing_body() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("for"),
+            new_ident("v"),
+            new_kwd("in"),
+            new_delimiter("["),
+            new_delimiter("]"),
+            new_json_assignment_op(),
+            new_delimiter("]"),
+        ]);
+
+        assert_eq!(
+            comprehension_consumer(inp, 0, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_not_a_val(6))
+        );
+    }
+}
@@ -0,0 +1,706 @@
+// The errors carry their provenance unboxed, like the other errors of the crate.
+#![allow(clippy::result_large_err)]
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Debug, Display},
+    iter::Enumerate,
+    slice::Iter,
+    vec::IntoIter,
+};
+
+use serde::de::{
+    self, value::BorrowedStrDeserializer, Deserialize, DeserializeSeed, Deserializer, EnumAccess,
+    MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+
+use crate::tokenizer::Token;
+
+use super::{
+    access_error::{self, AccessErr, AccessErrTyp},
+    error::get_msg,
+    node::{Node, NodeTyp, PathSegment, Provenance, SYNTHETIC},
+    pointer::to_pointer,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum DeErrTyp {
+    /// The node does not fit the Rust type, for example a `string` for an
+    /// `u16` field.
+    Access(AccessErrTyp),
+    /// Raised by the [`Deserialize`] implementation of the Rust type, for
+    /// example for a missing field or an unknown enum variant.
+    Custom(String),
+}
+
+/// Returned by [`from_node`] and the [`Deserializer`] implementation of
+/// [`Node`].
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DeErr {
+    pub typ: DeErrTyp,
+    /// The path from the deserialized node to the offending node.
+    pub path: Vec<PathSegment>,
+    /// Index of the first token of the offending node. **Inclusive.**
+    pub from: usize,
+    /// Index of the last token of the offending node. **NON inclusive.**
+    pub to: usize,
+    /// Set if the offending node was produced by using a variable.
+    pub provenance: Option<Provenance>,
+    /// Errors raised by a [`Deserialize`] implementation have no span, until
+    /// they are returned by the deserializer of the node they were raised for.
+    is_located: bool,
+}
+
+impl fmt::Display for DeErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for DeErr {}
+
+impl de::Error for DeErr {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeErr::new_custom(msg.to_string())
+    }
+}
+
+impl From<AccessErr> for DeErr {
+    fn from(err: AccessErr) -> Self {
+        DeErr::new_access(err)
+    }
+}
+
+impl DeErr {
+    /// Creates a new error of the typ [`DeErrTyp::Access`].
+    pub fn new_access(err: AccessErr) -> DeErr {
+        DeErr {
+            typ: DeErrTyp::Access(err.typ),
+            path: vec![],
+            from: err.from,
+            to: err.to,
+            provenance: err.provenance,
+            is_located: true,
+        }
+    }
+    /// Creates a new error of the typ [`DeErrTyp::Custom`]. The span is set
+    /// once the error is returned by the deserializer of a node.
+    pub fn new_custom(msg: String) -> DeErr {
+        DeErr {
+            typ: DeErrTyp::Custom(msg),
+            path: vec![],
+            from: SYNTHETIC,
+            to: SYNTHETIC,
+            provenance: None,
+            is_located: false,
+        }
+    }
+
+    fn locate(mut self, node: &Node) -> DeErr {
+        if !self.is_located {
+            self.from = node.from();
+            self.to = node.to();
+            self.provenance = node.provenance().cloned();
+            self.is_located = true;
+        }
+
+        self
+    }
+
+    fn prepend(mut self, segment: PathSegment) -> DeErr {
+        self.path.insert(0, segment);
+        self
+    }
+
+    pub fn msg(&self, toks: &[Token], inp: &str) -> String {
+        let verbal_hint = match &self.typ {
+            DeErrTyp::Access(typ) => access_error::get_verbal_hint(typ),
+            DeErrTyp::Custom(msg) => msg.clone(),
+        };
+
+        let verbal_hint = match self.path.is_empty() {
+            true => verbal_hint,
+            false => format!("{} at `{}`", verbal_hint, to_pointer(&self.path)),
+        };
+
+        get_msg(
+            &verbal_hint,
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
+        )
+    }
+}
+
+/// Deserializes a `T` from the node. Strings are borrowed from the node, so `T`
+/// can contain `&str` fields.
+pub fn from_node<'de, T: Deserialize<'de>>(node: &'de Node) -> Result<T, DeErr> {
+    T::deserialize(node)
+}
+
+/// Numbers are converted exactly (see [`Node::as_i64`]), so `1.5` is rejected
+/// for an integer field, and `2.0` is accepted. Objects are visited in sorted
+/// key order. Enum variants are either a string (`"Tcp"`), or an object with a
+/// single entry (`{"Tls": {"cert": "a.pem"}}`).
+impl<'de> Deserializer<'de> for &'de Node {
+    type Error = DeErr;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        match self {
+            Node::Array(a) => visit_array(&a.entries, visitor),
+            Node::Bool(b) => visitor.visit_bool(b.val),
+            Node::Null(_) => visitor.visit_unit(),
+            Node::Number(n) => match n.number().is_integer() {
+                true => visit_integer(self, visitor),
+                false => visitor.visit_f64(self.as_f64()?),
+            },
+            Node::Object(o) => visitor.visit_map(MapDeserializer::new(&o.entries)),
+            Node::String(s) => visitor.visit_borrowed_str(&s.val),
+        }
+        .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_bool(self.as_bool()?)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_i64(visitor)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_i64(self.as_i64()?)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_i128(self.as_i128()?)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_u64(visitor)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_u64(self.as_u64()?)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_i128(visitor)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_f64(self.as_f64()?)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_borrowed_str(self.as_str()?)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        match self {
+            Node::Null(_) => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+        .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        match self {
+            Node::Null(_) => visitor.visit_unit().map_err(|e: DeErr| e.locate(self)),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Null).into()),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_newtype_struct(self)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visit_array(self.as_array()?, visitor).map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor
+            .visit_map(MapDeserializer::new(self.as_object()?))
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        let variant = match self {
+            Node::String(s) => EnumDeserializer {
+                variant: &s.val,
+                val: None,
+            },
+            Node::Object(o) if o.entries.len() == 1 => {
+                let (variant, val) = o.entries.iter().next().unwrap();
+
+                EnumDeserializer {
+                    variant,
+                    val: Some(val),
+                }
+            }
+            Node::Object(o) => {
+                return Err(de::Error::invalid_length(
+                    o.entries.len(),
+                    &"an object with a single entry",
+                ))
+                .map_err(|e: DeErr| e.locate(self))
+            }
+            _ => return Err(AccessErr::new_invalid_typ(self, NodeTyp::String).into()),
+        };
+
+        visitor
+            .visit_enum(variant)
+            .map_err(|e: DeErr| e.locate(self))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeErr> {
+        visitor.visit_unit()
+    }
+}
+
+/// Visits integers as an `i64` if possible, and as an `u64` or an `i128`
+/// otherwise.
+fn visit_integer<'de, V: Visitor<'de>>(node: &'de Node, visitor: V) -> Result<V::Value, DeErr> {
+    if let Ok(n) = node.as_i64() {
+        return visitor.visit_i64(n);
+    }
+
+    if let Ok(n) = node.as_u64() {
+        return visitor.visit_u64(n);
+    }
+
+    visitor.visit_i128(node.as_i128()?)
+}
+
+/// Fails if the visitor did not consume all entries, for example if an array
+/// with three entries is deserialized into a tuple with two.
+fn visit_array<'de, V: Visitor<'de>>(entries: &'de [Node], visitor: V) -> Result<V::Value, DeErr> {
+    let mut seq = SeqDeserializer {
+        entries: entries.iter().enumerate(),
+    };
+    let val = visitor.visit_seq(&mut seq)?;
+    let remaining = seq.entries.len();
+
+    match remaining {
+        0 => Ok(val),
+        _ => Err(de::Error::invalid_length(
+            entries.len(),
+            &format!("an array with {} entries", entries.len() - remaining).as_str(),
+        )),
+    }
+}
+
+struct SeqDeserializer<'de> {
+    entries: Enumerate<Iter<'de, Node>>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = DeErr;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeErr> {
+        match self.entries.next() {
+            None => Ok(None),
+            Some((i, entry)) => seed
+                .deserialize(entry)
+                .map(Some)
+                .map_err(|e| e.prepend(PathSegment::Index(i))),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct MapDeserializer<'de> {
+    entries: IntoIter<(&'de String, &'de Node)>,
+    /// The entry whose key was returned last.
+    entry: Option<(&'de String, &'de Node)>,
+}
+
+impl<'de> MapDeserializer<'de> {
+    fn new(entries: &'de HashMap<String, Node>) -> MapDeserializer<'de> {
+        let mut entries = entries.iter().collect::<Vec<(&String, &Node)>>();
+        entries.sort_by_key(|(key, _)| *key);
+
+        MapDeserializer {
+            entries: entries.into_iter(),
+            entry: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = DeErr;
+
+    /// Errors of the key (like an unknown field) are reported at the value, as
+    /// keys have no node of their own.
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeErr> {
+        self.entry = self.entries.next();
+
+        match self.entry {
+            None => Ok(None),
+            Some((key, val)) => seed
+                .deserialize(BorrowedStrDeserializer::<DeErr>::new(key))
+                .map(Some)
+                .map_err(|e| e.locate(val).prepend(PathSegment::Key(key.clone()))),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeErr> {
+        let (key, val) = self
+            .entry
+            .take()
+            .expect("`next_value_seed` was called before `next_key_seed`");
+
+        seed.deserialize(val)
+            .map_err(|e| e.prepend(PathSegment::Key(key.clone())))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    /// `None` for unit variants written as a string.
+    val: Option<&'de Node>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeErr;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), DeErr> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::<DeErr>::new(self.variant))?;
+
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeErr;
+
+    fn unit_variant(self) -> Result<(), DeErr> {
+        match self.val {
+            None => Ok(()),
+            Some(val) => self.in_variant(<()>::deserialize(val)),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeErr> {
+        match self.val {
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"a newtype variant",
+            )),
+            Some(val) => self.in_variant(seed.deserialize(val)),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, DeErr> {
+        match self.val {
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"a tuple variant",
+            )),
+            Some(val) => self.in_variant(val.deserialize_tuple(len, visitor)),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeErr> {
+        match self.val {
+            None => Err(de::Error::invalid_type(
+                Unexpected::UnitVariant,
+                &"a struct variant",
+            )),
+            Some(val) => self.in_variant(val.deserialize_struct("", fields, visitor)),
+        }
+    }
+}
+
+impl EnumDeserializer<'_> {
+    fn in_variant<T>(&self, res: Result<T, DeErr>) -> Result<T, DeErr> {
+        res.map_err(|e| e.prepend(PathSegment::Key(self.variant.to_owned())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+
+    use crate::{
+        tokenizer::tokenize,
+        treebuilder::{build, testing::parse, Config},
+    };
+
+    use super::*;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Server<'a> {
+        name: &'a str,
+        port: u16,
+        tls: Option<Tls>,
+        #[serde(default)]
+        tags: Vec<String>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Tls {
+        Disabled,
+        Enabled { cert: String },
+    }
+
+    #[test]
+    fn structs() {
+        let node = parse(
+            r#"{
+                "name": "api",
+                "port": 8080,
+                "tls": {"Enabled": {"cert": "api.pem"}},
+                "unknown": [1, 2]
+            }"#,
+        );
+
+        assert_eq!(
+            from_node::<Server>(&node),
+            Ok(Server {
+                name: "api",
+                port: 8080,
+                tls: Some(Tls::Enabled {
+                    cert: "api.pem".to_owned()
+                }),
+                tags: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn unit_variants_and_null() {
+        let node = parse(
+            r#"[{"name": "a", "port": 1, "tls": "Disabled"}, {"name": "b", "port": 2, "tls": null}]"#,
+        );
+        let servers = from_node::<Vec<Server>>(&node).unwrap();
+
+        assert_eq!(servers[0].tls, Some(Tls::Disabled));
+        assert_eq!(servers[1].tls, None);
+    }
+
+    #[test]
+    fn any() {
+        #[derive(Deserialize, PartialEq, Debug)]
+        #[serde(untagged)]
+        enum Val {
+            Int(i64),
+            Float(f64),
+            Str(String),
+            Map(HashMap<String, Val>),
+            Seq(Vec<Val>),
+        }
+
+        assert_eq!(
+            from_node::<Val>(&parse(r#"{"a": [1, 2.5, "x"]}"#)),
+            Ok(Val::Map(HashMap::from([(
+                "a".to_owned(),
+                Val::Seq(vec![Val::Int(1), Val::Float(2.5), Val::Str("x".to_owned())])
+            )])))
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        assert_eq!(from_node::<(u8, bool)>(&parse("[1, true]")), Ok((1, true)));
+        assert_eq!(
+            from_node::<(u8, bool)>(&parse("[1, true, null]"))
+                .unwrap_err()
+                .typ,
+            DeErrTyp::Custom("invalid length 3, expected an array with 2 entries".to_owned())
+        );
+    }
+
+    #[test]
+    fn large_integers() {
+        assert_eq!(
+            from_node::<u128>(&parse("123456789012345678901234567890")),
+            Ok(123456789012345678901234567890)
+        );
+    }
+
+    #[test]
+    fn path_and_span_of_invalid_typ() {
+        let node = parse(r#"{"servers": [{"name": "a", "port": "80"}]}"#);
+        let err = from_node::<HashMap<String, Vec<Server>>>(&node).unwrap_err();
+
+        assert_eq!(
+            err.typ,
+            DeErrTyp::Access(AccessErrTyp::InvalidTyp {
+                expected: NodeTyp::Number,
+                received: NodeTyp::String,
+            })
+        );
+        assert_eq!(
+            err.path,
+            vec![
+                PathSegment::Key("servers".to_owned()),
+                PathSegment::Index(0),
+                PathSegment::Key("port".to_owned()),
+            ]
+        );
+        assert_eq!((err.from, err.to), (11, 12));
+    }
+
+    #[test]
+    fn fractions_are_not_truncated() {
+        let err = from_node::<HashMap<String, u16>>(&parse(r#"{"port": 80.5}"#)).unwrap_err();
+
+        assert_eq!(
+            err.typ,
+            DeErrTyp::Access(AccessErrTyp::NumberOutOfRange { expected: "u64" })
+        );
+        assert_eq!((err.from, err.to), (3, 4));
+    }
+
+    #[test]
+    fn custom_err_is_located_at_its_node() {
+        let node = parse(r#"{"servers": [{"name": "a"}]}"#);
+        let err = from_node::<HashMap<String, Vec<Server>>>(&node).unwrap_err();
+
+        assert_eq!(err.typ, DeErrTyp::Custom("missing field `port`".to_owned()));
+        assert_eq!(
+            err.path,
+            vec![
+                PathSegment::Key("servers".to_owned()),
+                PathSegment::Index(0)
+            ]
+        );
+        assert_eq!((err.from, err.to), (4, 9));
+    }
+
+    #[test]
+    fn msg() {
+        let inp = r#"{"port": 70000}"#;
+        let toks = tokenize(inp, &Config::DEFAULT).unwrap();
+        let node = build(&toks, &Config::DEFAULT).unwrap().unwrap();
+
+        assert_eq!(
+            from_node::<HashMap<String, u16>>(&node)
+                .unwrap_err()
+                .msg(&toks, inp),
+            "invalid value: integer `70000`, expected u16 at `/port`, line: 1, char: 10\n\n{\"port\": 70000}\n         ^^^^^\n"
+        );
+    }
+}
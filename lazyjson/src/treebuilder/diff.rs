@@ -0,0 +1,450 @@
+use std::{collections::HashMap, fmt};
+
+use crate::tokenizer::Token;
+
+use super::{
+    builtins::eq_val,
+    error::get_msg,
+    node::{KeyOrder, Node, ObjectNode, PathSegment},
+    pointer::to_pointer,
+};
+
+/// How two documents are compared by [`Node::diff`].
+#[derive(Clone)]
+pub struct DiffConfig {
+    /// Also reports objects whose common keys are in a different order (see
+    /// [`KeyOrder::Source`]). By default, objects are compared like maps.
+    pub ordered_objects: bool,
+    /// Matches the entries of arrays by the value of this key, instead of by
+    /// their index, if all entries of both arrays are objects with the key.
+    /// Entries that were only moved are not reported.
+    pub array_key: Option<String>,
+}
+
+impl DiffConfig {
+    pub const DEFAULT: DiffConfig = DiffConfig {
+        ordered_objects: false,
+        array_key: None,
+    };
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum ChangeTyp {
+    Added,
+    Removed,
+    /// The value, or the type, of a node changed.
+    Changed,
+    /// The entries of an object are in a different order. Only reported with
+    /// [`DiffConfig::ordered_objects`].
+    Reordered,
+}
+
+impl fmt::Display for ChangeTyp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let typ = match self {
+            ChangeTyp::Added => "added",
+            ChangeTyp::Removed => "removed",
+            ChangeTyp::Changed => "changed",
+            ChangeTyp::Reordered => "reordered",
+        };
+
+        write!(f, "{}", typ)
+    }
+}
+
+/// A difference between two documents, found by [`Node::diff`]. The spans of
+/// both sides are the spans of `old` and `new`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Change<'a> {
+    pub typ: ChangeTyp,
+    /// The path to the changed node. Indices of removed entries refer to the
+    /// old array, all other indices to the new one.
+    pub path: Vec<PathSegment>,
+    /// `None` if the node was added.
+    pub old: Option<&'a Node>,
+    /// `None` if the node was removed.
+    pub new: Option<&'a Node>,
+}
+
+impl Change<'_> {
+    /// Returns the path as a JSON Pointer (`/servers/0/port`).
+    pub fn pointer(&self) -> String {
+        to_pointer(&self.path)
+    }
+
+    /// Describes the change, and shows where the old and the new node are
+    /// located in their sources.
+    pub fn msg(
+        &self,
+        old_toks: &[Token],
+        old_inp: &str,
+        new_toks: &[Token],
+        new_inp: &str,
+    ) -> String {
+        let mut msg = format!("{} `{}`\n", self.typ, self.pointer());
+
+        if let Some(old) = self.old {
+            msg += "\n";
+            msg += &get_msg(
+                "old",
+                old.from(),
+                old.to(),
+                old.provenance(),
+                old_toks,
+                old_inp,
+            );
+        }
+
+        if let Some(new) = self.new {
+            msg += "\n";
+            msg += &get_msg(
+                "new",
+                new.from(),
+                new.to(),
+                new.provenance(),
+                new_toks,
+                new_inp,
+            );
+        }
+
+        msg
+    }
+}
+
+impl Node {
+    /// Returns the differences between the node (the old document) and the
+    /// other one (the new document), in the order in which they are found.
+    /// Objects are walked in sorted key order. Values are compared like by the
+    /// `==` operator of filters, so `1.0` equals `1`.
+    pub fn diff<'a>(&'a self, new: &'a Node, config: &DiffConfig) -> Vec<Change<'a>> {
+        let mut changes = vec![];
+        diff_node(self, new, &mut vec![], config, &mut changes);
+
+        changes
+    }
+}
+
+fn diff_node<'a>(
+    old: &'a Node,
+    new: &'a Node,
+    path: &mut Vec<PathSegment>,
+    config: &DiffConfig,
+    changes: &mut Vec<Change<'a>>,
+) {
+    match (old, new) {
+        (Node::Array(o), Node::Array(n)) => match get_keys(&o.entries, &n.entries, config) {
+            Some((old_keys, new_keys)) => diff_arrays_by_key(
+                &o.entries, &n.entries, &old_keys, &new_keys, path, config, changes,
+            ),
+            None => diff_arrays_by_idx(&o.entries, &n.entries, path, config, changes),
+        },
+        (Node::Object(o), Node::Object(n)) => {
+            diff_objects(&o.entries, &n.entries, path, config, changes);
+
+            if config.ordered_objects && !has_same_order(o, n) {
+                changes.push(new_change(ChangeTyp::Reordered, path, Some(old), Some(new)));
+            }
+        }
+        _ if eq_val(old, new) => {}
+        _ => changes.push(new_change(ChangeTyp::Changed, path, Some(old), Some(new))),
+    }
+}
+
+fn diff_arrays_by_idx<'a>(
+    old: &'a [Node],
+    new: &'a [Node],
+    path: &mut Vec<PathSegment>,
+    config: &DiffConfig,
+    changes: &mut Vec<Change<'a>>,
+) {
+    for (i, (o, n)) in old.iter().zip(new.iter()).enumerate() {
+        path.push(PathSegment::Index(i));
+        diff_node(o, n, path, config, changes);
+        path.pop();
+    }
+
+    for (i, o) in old.iter().enumerate().skip(new.len()) {
+        path.push(PathSegment::Index(i));
+        changes.push(new_change(ChangeTyp::Removed, path, Some(o), None));
+        path.pop();
+    }
+
+    for (i, n) in new.iter().enumerate().skip(old.len()) {
+        path.push(PathSegment::Index(i));
+        changes.push(new_change(ChangeTyp::Added, path, None, Some(n)));
+        path.pop();
+    }
+}
+
+/// Returns the values of [`DiffConfig::array_key`] of the entries of both
+/// arrays, or `None` if the arrays should be compared by index.
+fn get_keys<'a>(
+    old: &'a [Node],
+    new: &'a [Node],
+    config: &DiffConfig,
+) -> Option<(Vec<&'a Node>, Vec<&'a Node>)> {
+    let key = config.array_key.as_ref()?;
+    let get_keys = |entries: &'a [Node]| {
+        entries
+            .iter()
+            .map(|entry| entry.get(key).ok())
+            .collect::<Option<Vec<&Node>>>()
+    };
+
+    Some((get_keys(old)?, get_keys(new)?))
+}
+
+fn diff_arrays_by_key<'a>(
+    old: &'a [Node],
+    new: &'a [Node],
+    old_keys: &[&Node],
+    new_keys: &[&Node],
+    path: &mut Vec<PathSegment>,
+    config: &DiffConfig,
+    changes: &mut Vec<Change<'a>>,
+) {
+    let mut is_matched = vec![false; old.len()];
+
+    for (i, (n, new_key)) in new.iter().zip(new_keys.iter()).enumerate() {
+        path.push(PathSegment::Index(i));
+
+        let matching = (0..old.len()).position(|j| !is_matched[j] && eq_val(old_keys[j], new_key));
+
+        match matching {
+            Some(j) => {
+                is_matched[j] = true;
+                diff_node(&old[j], n, path, config, changes);
+            }
+            None => changes.push(new_change(ChangeTyp::Added, path, None, Some(n))),
+        }
+
+        path.pop();
+    }
+
+    for (j, o) in old.iter().enumerate().filter(|(j, _)| !is_matched[*j]) {
+        path.push(PathSegment::Index(j));
+        changes.push(new_change(ChangeTyp::Removed, path, Some(o), None));
+        path.pop();
+    }
+}
+
+fn diff_objects<'a>(
+    old: &'a HashMap<String, Node>,
+    new: &'a HashMap<String, Node>,
+    path: &mut Vec<PathSegment>,
+    config: &DiffConfig,
+    changes: &mut Vec<Change<'a>>,
+) {
+    let mut keys = old.keys().chain(new.keys()).collect::<Vec<&String>>();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        path.push(PathSegment::Key(key.clone()));
+
+        match (old.get(key), new.get(key)) {
+            (Some(o), Some(n)) => diff_node(o, n, path, config, changes),
+            (Some(o), None) => changes.push(new_change(ChangeTyp::Removed, path, Some(o), None)),
+            (None, Some(n)) => changes.push(new_change(ChangeTyp::Added, path, None, Some(n))),
+            (None, None) => unreachable!(),
+        }
+
+        path.pop();
+    }
+}
+
+/// Whether the keys both objects have in common are in the same order (see
+/// [`KeyOrder::Source`]).
+fn has_same_order(old: &ObjectNode, new: &ObjectNode) -> bool {
+    get_common_keys_in_order(old, new).eq(get_common_keys_in_order(new, old))
+}
+
+fn get_common_keys_in_order<'a>(
+    obj: &'a ObjectNode,
+    other: &'a ObjectNode,
+) -> impl Iterator<Item = &'a String> {
+    obj.entries_ordered(KeyOrder::Source)
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(move |key| other.entries.contains_key(*key))
+}
+
+fn new_change<'a>(
+    typ: ChangeTyp,
+    path: &[PathSegment],
+    old: Option<&'a Node>,
+    new: Option<&'a Node>,
+) -> Change<'a> {
+    Change {
+        typ,
+        path: path.to_vec(),
+        old,
+        new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tokenizer::tokenize,
+        treebuilder::{build, testing::parse, Config},
+    };
+
+    use super::*;
+
+    /// Returns the changes as `typ pointer`.
+    fn diff(old: &str, new: &str, config: &DiffConfig) -> Vec<String> {
+        let (old, new) = (parse(old), parse(new));
+
+        old.diff(&new, config)
+            .iter()
+            .map(|c| format!("{} {}", c.typ, c.pointer()))
+            .collect()
+    }
+
+    #[test]
+    fn equal() {
+        assert_eq!(
+            diff(
+                r#"{"a": [1, {"b": null}], "c": 1.0}"#,
+                r#"{"c": 1, "a": [1, {"b": null}]}"#,
+                &DiffConfig::DEFAULT
+            ),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn objects() {
+        assert_eq!(
+            diff(
+                r#"{"host": "db", "port": 80, "debug": true}"#,
+                r#"{"host": "db", "port": 8080, "tls": {}}"#,
+                &DiffConfig::DEFAULT
+            ),
+            ["removed /debug", "changed /port", "added /tls"]
+        );
+    }
+
+    #[test]
+    fn changed_typ() {
+        assert_eq!(
+            diff(r#"{"a": [1]}"#, r#"{"a": {"0": 1}}"#, &DiffConfig::DEFAULT),
+            ["changed /a"]
+        );
+    }
+
+    #[test]
+    fn arrays_by_idx() {
+        assert_eq!(
+            diff("[1, 2, 3]", "[1, 5]", &DiffConfig::DEFAULT),
+            ["changed /1", "removed /2"]
+        );
+        assert_eq!(diff("[1]", "[1, [2]]", &DiffConfig::DEFAULT), ["added /1"]);
+    }
+
+    #[test]
+    fn arrays_by_key() {
+        let config = DiffConfig {
+            array_key: Some("name".to_owned()),
+            ..DiffConfig::DEFAULT
+        };
+        let old =
+            r#"[{"name": "web", "port": 80}, {"name": "db", "port": 5432}, {"name": "cache"}]"#;
+        let new = r#"[{"name": "db", "port": 5433}, {"name": "api"}, {"name": "web", "port": 80}]"#;
+
+        assert_eq!(
+            diff(old, new, &config),
+            ["changed /0/port", "added /1", "removed /2"]
+        );
+        assert_eq!(
+            diff(old, new, &DiffConfig::DEFAULT).len(),
+            6,
+            "compared by index"
+        );
+    }
+
+    #[test]
+    fn arrays_without_key_are_compared_by_idx() {
+        let config = DiffConfig {
+            array_key: Some("name".to_owned()),
+            ..DiffConfig::DEFAULT
+        };
+
+        assert_eq!(
+            diff("[1, 2]", "[2, 1]", &config),
+            ["changed /0", "changed /1"]
+        );
+    }
+
+    #[test]
+    fn ordered_objects() {
+        let config = DiffConfig {
+            ordered_objects: true,
+            ..DiffConfig::DEFAULT
+        };
+        let old = r#"{"a": {"x": 1, "y": 2}, "b": 1}"#;
+
+        assert_eq!(
+            diff(old, r#"{"a": {"y": 2, "x": 1}, "b": 1}"#, &config),
+            ["reordered /a"]
+        );
+        assert_eq!(
+            diff(
+                old,
+                r#"{"a": {"y": 2, "x": 1}, "b": 1}"#,
+                &DiffConfig::DEFAULT
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            diff(old, r#"{"a": {"x": 1, "z": 0, "y": 2}, "b": 1}"#, &config),
+            ["added /a/z"]
+        );
+
+        // The values of `y` have the span of the variable definition.
+        assert_eq!(
+            diff(
+                old,
+                r#"{let v = 2, "a": {"x": 1, "y": v}, "b": 1}"#,
+                &config
+            ),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            diff(
+                old,
+                r#"{let v = 1, "a": {"y": 2, "x": v}, "b": 1}"#,
+                &config
+            ),
+            ["reordered /a"]
+        );
+    }
+
+    #[test]
+    fn spans_of_both_sides() {
+        let (old, new) = (
+            parse(r#"{"port": 80}"#),
+            parse(r#"{"tls": true, "port": 81}"#),
+        );
+        let changes = old.diff(&new, &DiffConfig::DEFAULT);
+
+        assert_eq!(changes[0].typ, ChangeTyp::Changed);
+        assert_eq!(changes[0].old.map(|n| (n.from(), n.to())), Some((3, 4)));
+        assert_eq!(changes[0].new.map(|n| (n.from(), n.to())), Some((7, 8)));
+    }
+
+    #[test]
+    fn msg() {
+        let (old_inp, new_inp) = (r#"{"port": 80}"#, r#"{"port": 8080}"#);
+        let old_toks = tokenize(old_inp, &Config::DEFAULT).unwrap();
+        let new_toks = tokenize(new_inp, &Config::DEFAULT).unwrap();
+        let old = build(&old_toks, &Config::DEFAULT).unwrap().unwrap();
+        let new = build(&new_toks, &Config::DEFAULT).unwrap().unwrap();
+
+        assert_eq!(
+            old.diff(&new, &DiffConfig::DEFAULT)[0].msg(&old_toks, old_inp, &new_toks, new_inp),
+            "changed `/port`\n\nold, line: 1, char: 10\n\n{\"port\": 80}\n         ^^\n\nnew, line: 1, char: 10\n\n{\"port\": 8080}\n         ^^^^\n"
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use std::{collections::HashMap, mem, rc::Rc};
+
+use super::{
+    access_error::AccessErr,
+    node::{ArrayNode, BoolNode, Node, NodeTyp, NullNode, NumberNode, ObjectNode, StringNode},
+    number::Number,
+    pointer::PointerErr,
+};
+
+/// Creates a [`Node`] with [`SYNTHETIC`](super::node::SYNTHETIC) spans from a
+/// JSON-like literal. Values that are not literals are converted with
+/// [`Into<Node>`], so variables can be used as values, and parenthesized
+/// expressions as keys.
+///
+/// ```
+/// use lazyjson::json;
+///
+/// let port = 8080;
+/// let node = json!({
+///     "name": "api",
+///     "port": port,
+///     "tags": ["internal", null, -1.5],
+/// });
+///
+/// assert_eq!(node["port"].as_u64(), Ok(8080));
+/// ```
+#[macro_export]
+macro_rules! json {
+    (null) => {
+        $crate::treebuilder::node::Node::Null($crate::treebuilder::node::NullNode::synthetic())
+    };
+    ([ $($tt:tt)* ]) => {
+        $crate::treebuilder::node::Node::Array(
+            $crate::treebuilder::node::ArrayNode::synthetic($crate::json!(@array [] $($tt)*))
+        )
+    };
+    ({ $($tt:tt)* }) => {{
+        #[allow(unused_mut)]
+        let mut obj = $crate::treebuilder::node::ObjectNode::synthetic(
+            ::std::collections::HashMap::new()
+        );
+        $crate::json!(@object obj $($tt)*);
+
+        $crate::treebuilder::node::Node::Object(obj)
+    }};
+    ($other:expr) => {
+        ::std::convert::Into::<$crate::treebuilder::node::Node>::into($other)
+    };
+
+    // Collects the entries of an array. Arrays, objects and `null` have to be
+    // matched before `expr`, because they are not valid Rust expressions.
+    (@array [$($elems:expr,)*]) => {
+        vec![$($elems,)*]
+    };
+    (@array [$($elems:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elems,)* $crate::json!(null),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] [$($arr:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elems,)* $crate::json!([$($arr)*]),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] {$($obj:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elems,)* $crate::json!({$($obj)*}),] $($($rest)*)?)
+    };
+    (@array [$($elems:expr,)*] $next:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($elems,)* $crate::json!($next),] $($($rest)*)?)
+    };
+
+    // Inserts the entries into the object, in order.
+    (@object $target:ident) => {};
+    (@object $target:ident $key:tt : null $(, $($rest:tt)*)?) => {
+        $target.insert(::std::convert::Into::<String>::into($key), $crate::json!(null));
+        $crate::json!(@object $target $($($rest)*)?);
+    };
+    (@object $target:ident $key:tt : [$($arr:tt)*] $(, $($rest:tt)*)?) => {
+        $target.insert(::std::convert::Into::<String>::into($key), $crate::json!([$($arr)*]));
+        $crate::json!(@object $target $($($rest)*)?);
+    };
+    (@object $target:ident $key:tt : {$($obj:tt)*} $(, $($rest:tt)*)?) => {
+        $target.insert(::std::convert::Into::<String>::into($key), $crate::json!({$($obj)*}));
+        $crate::json!(@object $target $($($rest)*)?);
+    };
+    (@object $target:ident $key:tt : $val:expr $(, $($rest:tt)*)?) => {
+        $target.insert(::std::convert::Into::<String>::into($key), $crate::json!($val));
+        $crate::json!(@object $target $($($rest)*)?);
+    };
+}
+
+impl From<bool> for Node {
+    fn from(val: bool) -> Self {
+        Node::Bool(BoolNode::synthetic(val))
+    }
+}
+
+impl From<&str> for Node {
+    fn from(val: &str) -> Self {
+        Node::String(StringNode::synthetic(val.to_owned()))
+    }
+}
+
+impl From<String> for Node {
+    fn from(val: String) -> Self {
+        Node::String(StringNode::synthetic(val))
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($typ:ty),*) => {
+        $(
+            impl From<$typ> for Node {
+                fn from(val: $typ) -> Self {
+                    Node::Number(NumberNode::synthetic(val.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i32, i64, i128, u32, u64, u128, usize);
+
+/// `NaN` and infinity can not be represented in JSON, and are converted to
+/// `null`.
+impl From<f64> for Node {
+    fn from(val: f64) -> Self {
+        match val.is_finite() {
+            true => Node::Number(NumberNode::synthetic(val.to_string())),
+            false => Node::Null(NullNode::synthetic()),
+        }
+    }
+}
+
+impl From<Number> for Node {
+    fn from(val: Number) -> Self {
+        Node::Number(NumberNode::synthetic(val.lexeme().to_owned()))
+    }
+}
+
+/// `None` is converted to `null`.
+impl<T: Into<Node>> From<Option<T>> for Node {
+    fn from(val: Option<T>) -> Self {
+        match val {
+            Some(val) => val.into(),
+            None => Node::Null(NullNode::synthetic()),
+        }
+    }
+}
+
+impl<T: Into<Node>> From<Vec<T>> for Node {
+    fn from(entries: Vec<T>) -> Self {
+        Node::Array(ArrayNode::synthetic(
+            entries.into_iter().map(Into::into).collect(),
+        ))
+    }
+}
+
+impl<T: Into<Node>> From<HashMap<String, T>> for Node {
+    fn from(entries: HashMap<String, T>) -> Self {
+        Node::Object(ObjectNode::synthetic(
+            entries.into_iter().map(|(k, v)| (k, v.into())).collect(),
+        ))
+    }
+}
+
+/// Editing of arrays and objects. The spans of the edited node are kept, and
+/// entries that are shared with other nodes are copied before they are
+/// modified (see [`ArrayNode::entries`]).
+impl Node {
+    /// Inserts the entry into the object, and returns the replaced entry.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        val: impl Into<Node>,
+    ) -> Result<Option<Node>, AccessErr> {
+        match self {
+            Node::Object(o) => Ok(o.insert(key.into(), val.into())),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Object)),
+        }
+    }
+    /// Removes the entry from the object, and returns it.
+    pub fn remove(&mut self, key: &str) -> Result<Option<Node>, AccessErr> {
+        match self {
+            Node::Object(o) => Ok(o.remove(key)),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Object)),
+        }
+    }
+    /// Appends the entry to the array.
+    pub fn push(&mut self, val: impl Into<Node>) -> Result<(), AccessErr> {
+        match self {
+            Node::Array(a) => {
+                Rc::make_mut(&mut a.entries).push(val.into());
+
+                Ok(())
+            }
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Array)),
+        }
+    }
+    /// Removes the entry at the index from the array, and returns it. The
+    /// following entries are shifted to the left.
+    pub fn remove_index(&mut self, i: usize) -> Result<Node, AccessErr> {
+        let len = self.as_array()?.len();
+
+        match self {
+            Node::Array(a) if i < len => Ok(Rc::make_mut(&mut a.entries).remove(i)),
+            _ => Err(AccessErr::new_missing_index(self, i, len)),
+        }
+    }
+    /// Replaces the node the pointer refers to (see [`Node::pointer`]), and
+    /// returns the replaced node.
+    #[allow(clippy::result_large_err)]
+    pub fn replace(&mut self, pointer: &str, val: impl Into<Node>) -> Result<Node, PointerErr> {
+        Ok(mem::replace(self.pointer_mut(pointer)?, val.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::treebuilder::{access_error::AccessErrTyp, node::SYNTHETIC};
+
+    use super::*;
+
+    fn num(val: &str) -> Node {
+        Node::Number(NumberNode::synthetic(val.to_owned()))
+    }
+
+    fn str(val: &str) -> Node {
+        Node::String(StringNode::synthetic(val.to_owned()))
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(json!(null), Node::Null(NullNode::synthetic()));
+        assert_eq!(json!(true), Node::Bool(BoolNode::synthetic(true)));
+        assert_eq!(json!(-1), num("-1"));
+        assert_eq!(json!(1.5), num("1.5"));
+        assert_eq!(json!("a"), str("a"));
+    }
+
+    #[test]
+    fn nested() {
+        let port = 8080;
+        let key = "dynamic";
+
+        let node = json!({
+            "servers": [{"port": port, "tls": null}, [], {}],
+            (key): -2,
+            "name": format!("{}-{}", "a", 1),
+        });
+
+        assert_eq!(
+            node,
+            Node::Object(ObjectNode::synthetic(HashMap::from([
+                (
+                    "servers".to_owned(),
+                    Node::Array(ArrayNode::synthetic(vec![
+                        Node::Object(ObjectNode::synthetic(HashMap::from([
+                            ("port".to_owned(), num("8080")),
+                            ("tls".to_owned(), Node::Null(NullNode::synthetic())),
+                        ]))),
+                        Node::Array(ArrayNode::synthetic(vec![])),
+                        Node::Object(ObjectNode::synthetic(HashMap::new())),
+                    ]))
+                ),
+                ("dynamic".to_owned(), num("-2")),
+                ("name".to_owned(), str("a-1")),
+            ])))
+        );
+    }
+
+    #[test]
+    fn trailing_commas() {
+        assert_eq!(json!([1, 2,]), json!([1, 2]));
+        assert_eq!(json!({"a": 1,}), json!({"a": 1}));
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(json!(f64::NAN), json!(null));
+        assert_eq!(json!(None::<i32>), json!(null));
+        assert_eq!(json!(Some("a")), json!("a"));
+        assert_eq!(json!(vec![1, 2]), json!([1, 2]));
+        assert_eq!(
+            json!(HashMap::from([("a".to_owned(), true)])),
+            json!({"a": true})
+        );
+    }
+
+    #[test]
+    fn spans_are_synthetic() {
+        let node = json!([1]);
+
+        assert!(node.is_synthetic());
+        assert!(node[0].is_synthetic());
+        assert_eq!((node.from(), node.to()), (SYNTHETIC, SYNTHETIC));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut node = json!({"a": 1});
+
+        assert_eq!(node.insert("a", 2), Ok(Some(num("1"))));
+        assert_eq!(node.insert("b", "x"), Ok(None));
+        assert_eq!(node.remove("a"), Ok(Some(num("2"))));
+        assert_eq!(node.remove("a"), Ok(None));
+        assert_eq!(node, json!({"b": "x"}));
+    }
+
+    #[test]
+    fn insert_into_array() {
+        assert_eq!(
+            json!([]).insert("a", 1).unwrap_err().typ,
+            AccessErrTyp::InvalidTyp {
+                expected: NodeTyp::Object,
+                received: NodeTyp::Array,
+            }
+        );
+    }
+
+    #[test]
+    fn push_and_remove_index() {
+        let mut node = json!([1, 2]);
+
+        node.push(json!({"a": null})).unwrap();
+
+        assert_eq!(node.remove_index(0), Ok(num("1")));
+        assert_eq!(node, json!([2, {"a": null}]));
+        assert_eq!(
+            node.remove_index(2).unwrap_err().typ,
+            AccessErrTyp::MissingIndex { idx: 2, len: 2 }
+        );
+    }
+
+    #[test]
+    fn replace_subtree() {
+        let mut node = json!({"servers": [{"port": 80}]});
+
+        assert_eq!(node.replace("/servers/0/port", 443), Ok(num("80")));
+        assert_eq!(node, json!({"servers": [{"port": 443}]}));
+    }
+
+    #[test]
+    fn editing_does_not_affect_clones() {
+        let original = json!({"a": [1]});
+        let mut edited = original.clone();
+
+        edited.insert("b", 2).unwrap();
+        edited.pointer_mut("/a").unwrap().push(3).unwrap();
+
+        assert_eq!(original, json!({"a": [1]}));
+        assert_eq!(edited, json!({"a": [1, 3], "b": 2}));
+    }
+
+    #[test]
+    fn msg_of_synthetic_node() {
+        assert_eq!(
+            json!(1).as_str().unwrap_err().msg(&[], ""),
+            "expected a `string` but received a `number` (in a generated node)\n"
+        );
+    }
+}
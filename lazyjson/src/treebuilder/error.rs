@@ -1,4 +1,9 @@
 use crate::tokenizer::{Token, TokenType};
+
+use super::{
+    builtins::get_builtin,
+    node::{NodeTyp, Provenance, SYNTHETIC},
+};
 use std::{
     error::Error,
     fmt::{self, Debug},
@@ -6,18 +11,38 @@ use std::{
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum TreebuilderErrTyp {
+    /// An argument of a built-in function has an unexpected type.
+    InvalidArgTyp {
+        expected: &'static str,
+        received: NodeTyp,
+    },
+    /// An argument of a built-in function has an invalid value.
+    InvalidArgVal {
+        reason: &'static str,
+    },
+    NotABool,
     NotAKey,
     NotASep,
     NotAVal,
     // TODO: rename to NotJsonAssignment
     NotAnAssignment,
+    NotArrCls,
     NotEqualAssignment,
+    NotInKwd,
+    NotIterable,
     NotVariableName,
     OutOfBounds,
+    ReservedVarName,
+    ShadowedBuiltin,
+    /// The nodes do not fit into an [`Arena`](super::Arena).
+    TooLarge,
     TrailingSep,
     UndeclaredVariable,
+    UnknownFunction,
     UnterminatedArr,
+    UnterminatedCall,
     UnterminatedObj,
+    WrongArgCnt,
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -25,6 +50,9 @@ pub struct TreebuilderErr {
     pub typ: TreebuilderErrTyp,
     pub from: usize,
     pub to: usize,
+    /// Set if the error was caused by a node that was produced by using a
+    /// variable.
+    pub provenance: Option<Provenance>,
 }
 
 impl fmt::Display for TreebuilderErr {
@@ -36,12 +64,45 @@ impl fmt::Display for TreebuilderErr {
 impl Error for TreebuilderErr {}
 
 impl TreebuilderErr {
+    /// Creates a new error of the typ [`TreebuilderErrTyp::InvalidArgTyp`].
+    pub fn new_invalid_arg_typ(
+        from: usize,
+        to: usize,
+        expected: &'static str,
+        received: NodeTyp,
+    ) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::InvalidArgTyp { expected, received },
+            from,
+            to,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::InvalidArgVal`].
+    pub fn new_invalid_arg_val(from: usize, to: usize, reason: &'static str) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::InvalidArgVal { reason },
+            from,
+            to,
+            provenance: None,
+        }
+    }
     /// Creates a new error of the typ [`TreebuilderErrTyp::ExpectedVariableName`].
     pub fn new_not_var_name(i: usize) -> TreebuilderErr {
         TreebuilderErr {
             from: i,
             to: i + 1,
             typ: TreebuilderErrTyp::NotVariableName,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::NotABool`].
+    pub fn new_not_a_bool(from: usize, to: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::NotABool,
+            from,
+            to,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::NotAKey`].
@@ -50,6 +111,7 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::NotAKey,
             from: i,
             to: i + 1,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::NotASep`].
@@ -58,6 +120,7 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::NotASep,
             from: i,
             to: i + 1,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::NotAVal`].
@@ -66,6 +129,7 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::NotAVal,
             from: i,
             to: i + 1,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::NotAnAssignment`].
@@ -74,6 +138,16 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::NotAnAssignment,
             from: i,
             to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::NotArrCls`].
+    pub fn new_not_arr_cls(i: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::NotArrCls,
+            from: i,
+            to: i + 1,
+            provenance: None,
         }
     }
     pub fn new_not_equals_assignment(i: usize) -> TreebuilderErr {
@@ -81,6 +155,52 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::NotEqualAssignment,
             from: i,
             to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::NotInKwd`].
+    pub fn new_not_in_kwd(i: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::NotInKwd,
+            from: i,
+            to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::NotIterable`].
+    pub fn new_not_iterable(from: usize, to: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::NotIterable,
+            from,
+            to,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::ReservedVarName`].
+    pub fn new_reserved_var_name(i: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::ReservedVarName,
+            from: i,
+            to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::ShadowedBuiltin`].
+    pub fn new_shadowed_builtin(i: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::ShadowedBuiltin,
+            from: i,
+            to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::TooLarge`].
+    pub fn new_too_large(from: usize, to: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::TooLarge,
+            from,
+            to,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::TrailingSep`].
@@ -89,6 +209,7 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::TrailingSep,
             from: i,
             to: i + 1,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::UndeclaredVariable`].
@@ -97,6 +218,16 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::UndeclaredVariable,
             from: i,
             to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::UnknownFunction`].
+    pub fn new_unknown_function(i: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::UnknownFunction,
+            from: i,
+            to: i + 1,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::OutOfBounds`].
@@ -105,6 +236,7 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::OutOfBounds,
             from: usize::MAX,
             to: usize::MAX,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::UnterminatedArr`].
@@ -113,6 +245,16 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::UnterminatedArr,
             from: i,
             to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::UnterminatedCall`].
+    pub fn new_unterminated_call(i: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::UnterminatedCall,
+            from: i,
+            to: i + 1,
+            provenance: None,
         }
     }
     /// Creates a new error of the typ [`TreebuilderErrTyp::UnterminatedObj`].
@@ -121,37 +263,135 @@ impl TreebuilderErr {
             typ: TreebuilderErrTyp::UnterminatedObj,
             from: i,
             to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a new error of the typ [`TreebuilderErrTyp::WrongArgCnt`]. The
+    /// error has to start at the name of the called function.
+    pub fn new_wrong_arg_cnt(from: usize, to: usize) -> TreebuilderErr {
+        TreebuilderErr {
+            typ: TreebuilderErrTyp::WrongArgCnt,
+            from,
+            to,
+            provenance: None,
         }
     }
 
+    /// Attaches the provenance of the node that caused the error, so the message
+    /// also points to the definition of the variable.
+    pub fn with_provenance(mut self, provenance: Option<&Provenance>) -> TreebuilderErr {
+        self.provenance = provenance.cloned();
+        self
+    }
+
     pub fn msg(&self, toks: &[Token], inp: &str) -> String {
-        let toks_of_err = &toks[self.from..self.to];
-        let first_err_tok = toks_of_err.first().unwrap();
-        let err_from = first_err_tok.from;
-        let err_to = toks_of_err.last().unwrap().to;
-
-        let verbal_hint = get_verbal_hint(self.typ, first_err_tok);
-        let FilePosition { line, char } = get_err_pos_in_file(inp, err_from);
-        let visual_hint = get_visual_hint(inp, err_from, err_to);
-
-        format!(
-            "{}, line: {}, char: {}\n\n{}\n",
-            verbal_hint, line, char, visual_hint
+        let verbal_hint = get_verbal_hint(self.typ, &toks[self.from]);
+
+        get_msg(
+            &verbal_hint,
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
         )
     }
 }
 
+/// Builds the message of an error that spans the tokens `from..to`. It consists
+/// of the verbal hint, the position and the visual hint, and, if the error was
+/// caused by using a variable, also points to the definition of the variable.
+/// Errors of [`SYNTHETIC`] nodes have no position, so only the verbal hint is
+/// returned for them.
+pub(super) fn get_msg(
+    verbal_hint: &str,
+    from: usize,
+    to: usize,
+    provenance: Option<&Provenance>,
+    toks: &[Token],
+    inp: &str,
+) -> String {
+    if from == SYNTHETIC {
+        return format!("{} (in a generated node)\n", verbal_hint);
+    }
+
+    let toks_of_err = &toks[from..to];
+    let err_from = toks_of_err.first().unwrap().from;
+    let err_to = toks_of_err.last().unwrap().to;
+
+    let FilePosition { line, char } = get_err_pos_in_file(inp, err_from);
+    let visual_hint = get_visual_hint(inp, err_from, err_to);
+
+    let mut msg = format!(
+        "{}, line: {}, char: {}\n\n{}\n",
+        verbal_hint, line, char, visual_hint
+    );
+
+    if let Some(provenance) = provenance {
+        let def_from = toks[provenance.def_from].from;
+        let def_to = toks[provenance.def_to - 1].to;
+
+        let FilePosition { line, char } = get_err_pos_in_file(inp, def_from);
+        let visual_hint = get_visual_hint(inp, def_from, def_to);
+
+        msg += &format!(
+            "\nin value of variable `{}` defined at line: {}, char: {}\n\n{}\n",
+            provenance.var_name, line, char, visual_hint
+        );
+    }
+
+    msg
+}
+
 fn get_verbal_hint(typ: TreebuilderErrTyp, err_tok: &Token) -> String {
     match typ {
+        TreebuilderErrTyp::InvalidArgTyp { expected, received } => {
+            format!("expected {} but received a `{}`", expected, received)
+        }
+        TreebuilderErrTyp::InvalidArgVal { reason } => format!("invalid argument: {}", reason),
+        TreebuilderErrTyp::NotABool => "expected the condition to be a `true` or `false`".to_string(),
+        TreebuilderErrTyp::NotArrCls => format!(
+            "expected a `]` (a comprehension has to be the only entry of an array) but received a `{:?}`",
+            err_tok.typ,
+        ),
+        TreebuilderErrTyp::NotInKwd => {
+            format!("expected the keyword `in` but received a `{:?}`", err_tok.typ)
+        }
+        TreebuilderErrTyp::NotIterable => "expected an array or object to iterate over".to_string(),
         TreebuilderErrTyp::NotVariableName => "expected a variable name".to_string(),
+        TreebuilderErrTyp::ReservedVarName => format!(
+            "`{}` is a reserved keyword and can not be used as a variable name",
+            err_tok.val
+        ),
         TreebuilderErrTyp::UnterminatedArr => "array was not terminated".to_string(),
         TreebuilderErrTyp::UnterminatedObj => "object was not terminated".to_string(),
+        TreebuilderErrTyp::ShadowedBuiltin => format!(
+            "`{}` is a built-in function and can not be used as a variable name",
+            err_tok.val
+        ),
+        TreebuilderErrTyp::TooLarge => {
+            "the document is too large to be stored in an arena".to_string()
+        }
         TreebuilderErrTyp::TrailingSep => {
             "expected the next value or close (trailing separator not allowed)".to_string()
         }
         TreebuilderErrTyp::UndeclaredVariable => {
             format!("undeclared variable with name: `{}`", err_tok.val).into()
         }
+        TreebuilderErrTyp::UnknownFunction => {
+            format!("unknown function with name: `{}`", err_tok.val)
+        }
+        TreebuilderErrTyp::UnterminatedCall => "function call was not terminated".to_string(),
+        TreebuilderErrTyp::WrongArgCnt => match get_builtin(&err_tok.val) {
+            Some(b) if b.min_args == b.max_args => {
+                format!("`{}` expects {} argument(s)", b.name, b.min_args)
+            }
+            Some(b) => format!(
+                "`{}` expects {} to {} arguments",
+                b.name, b.min_args, b.max_args
+            ),
+            None => "wrong amount of arguments".to_string(),
+        },
         TreebuilderErrTyp::NotAKey => format!(
             "expected a `{:?}` but received a `{:?}`",
             TokenType::StringLiteral,
@@ -159,7 +399,8 @@ fn get_verbal_hint(typ: TreebuilderErrTyp, err_tok: &Token) -> String {
         ),
         TreebuilderErrTyp::NotASep => format!("expected a `,` but received a `{:?}`", err_tok.typ),
         TreebuilderErrTyp::NotAVal => format!(
-            "expected one of `[`, `{{`, `{:?}`, `{:?}`, or `{:?}` but received a `{:?}`",
+            "expected one of `[`, `{{`, `{:?}`, `{:?}`, `{:?}`, or `{:?}` but received a `{:?}`",
+            TokenType::Identifier,
             TokenType::KeywordLiteral,
             TokenType::NumberLiteral,
             TokenType::StringLiteral,
@@ -338,7 +579,8 @@ mod tests {
         assert_eq!(
             TreebuilderErr::new_not_a_val(3).msg(&toks, inp),
             format!(
-                "expected one of `[`, `{{`, `{:?}`, `{:?}`, or `{:?}` but received a `{:?}`, line: 2, char: 21\n\n            \"city\": ,\n                    ^\n",
+                "expected one of `[`, `{{`, `{:?}`, `{:?}`, `{:?}`, or `{:?}` but received a `{:?}`, line: 2, char: 21\n\n            \"city\": ,\n                    ^\n",
+                TokenType::Identifier,
                 TokenType::KeywordLiteral,
                 TokenType::NumberLiteral,
                 TokenType::StringLiteral,
@@ -419,7 +661,7 @@ mod tests {
             Token::new_delimiter("{", 0, 1),
             Token::new_str("foo", 1, 6),
             Token::new_json_assignment_op(6),
-            Token::new_kwd("bar", 8, 11),
+            Token::new_ident("bar", 8, 11),
             Token::new_delimiter("}", 11, 12),
         ];
 
@@ -430,4 +672,104 @@ mod tests {
             format!("undeclared variable with name: `bar`, line: 1, char: 9\n\n{{\"foo\": bar}}\n        ^^^\n")
         );
     }
+
+    #[test]
+    fn not_iterable_msg() {
+        let inp = "[for v in 10: v]";
+        let toks = [
+            Token::new_delimiter("[", 0, 1),
+            Token::new_kwd("for", 1, 4),
+            Token::new_ident("v", 5, 6),
+            Token::new_kwd("in", 7, 9),
+            Token::new_num("10", 10, 12),
+            Token::new_json_assignment_op(12),
+            Token::new_ident("v", 14, 15),
+            Token::new_delimiter("]", 15, 16),
+        ];
+
+        assert_eq!(
+            TreebuilderErr::new_not_iterable(4, 5).msg(&toks, inp),
+            "expected an array or object to iterate over, line: 1, char: 11\n\n[for v in 10: v]\n          ^^\n"
+        );
+    }
+
+    #[test]
+    fn wrong_arg_cnt_msg() {
+        let inp = "len()";
+        let toks = [
+            Token::new_ident("len", 0, 3),
+            Token::new_delimiter("(", 3, 4),
+            Token::new_delimiter(")", 4, 5),
+        ];
+
+        assert_eq!(
+            TreebuilderErr::new_wrong_arg_cnt(0, 3).msg(&toks, inp),
+            "`len` expects 1 argument(s), line: 1, char: 1\n\nlen()\n^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn invalid_arg_typ_msg() {
+        let inp = "upper(1)";
+        let toks = [
+            Token::new_ident("upper", 0, 5),
+            Token::new_delimiter("(", 5, 6),
+            Token::new_num("1", 6, 7),
+            Token::new_delimiter(")", 7, 8),
+        ];
+
+        assert_eq!(
+            TreebuilderErr::new_invalid_arg_typ(2, 3, "a string", NodeTyp::Number).msg(&toks, inp),
+            "expected a string but received a `number`, line: 1, char: 7\n\nupper(1)\n      ^\n"
+        );
+    }
+
+    #[test]
+    fn reserved_var_name_msg() {
+        let inp = "{let true = 1}";
+        let toks = [
+            Token::new_delimiter("{", 0, 1),
+            Token::new_kwd("let", 1, 4),
+            Token::new_kwd("true", 5, 9),
+            Token::new_equal_assignment_op(10),
+            Token::new_num("1", 12, 13),
+            Token::new_delimiter("}", 13, 14),
+        ];
+
+        assert_eq!(
+            TreebuilderErr::new_reserved_var_name(2).msg(&toks, inp),
+            "`true` is a reserved keyword and can not be used as a variable name, line: 1, char: 6\n\n{let true = 1}\n     ^^^^\n"
+        );
+    }
+
+    #[test]
+    fn msg_with_provenance() {
+        let inp = "{\n    let port = 80,\n    \"p\": [for p in port: p]\n}";
+        let toks = [
+            Token::new_delimiter("{", 0, 1),
+            Token::new_kwd("let", 6, 9),
+            Token::new_ident("port", 10, 14),
+            Token::new_equal_assignment_op(15),
+            Token::new_num("80", 17, 19),
+            Token::new_sep(",", 19, 20),
+            Token::new_str("p", 25, 28),
+            Token::new_json_assignment_op(28),
+            Token::new_delimiter("[", 30, 31),
+            Token::new_kwd("for", 31, 34),
+            Token::new_ident("p", 35, 36),
+            Token::new_kwd("in", 37, 39),
+            Token::new_ident("port", 40, 44),
+            Token::new_json_assignment_op(44),
+            Token::new_ident("p", 46, 47),
+            Token::new_delimiter("]", 47, 48),
+            Token::new_delimiter("}", 49, 50),
+        ];
+
+        assert_eq!(
+            TreebuilderErr::new_not_iterable(12, 13)
+                .with_provenance(Some(&Provenance::new("port", 12, 1, 5)))
+                .msg(&toks, inp),
+            "expected an array or object to iterate over, line: 3, char: 20\n\n    \"p\": [for p in port: p]\n                   ^^^^\n\nin value of variable `port` defined at line: 2, char: 5\n\n    let port = 80,\n    ^^^^^^^^^^^^^\n"
+        );
+    }
 }
@@ -0,0 +1,401 @@
+use std::rc::Rc;
+
+use crate::{
+    queue::Queue,
+    tokenizer::{Token, TokenType},
+};
+
+use super::{
+    builtins::{get_builtin, BuiltinErr},
+    value_consumer, Config, Node, TreebuilderErr, VarDict,
+};
+
+/// Consumes a call of a built-in function (`len(list)`), and returns the result
+/// of the call.
+pub fn function_call_consumer(
+    inp: &mut Queue<Token>,
+    var_dict: &Rc<VarDict>,
+    config: &Config,
+) -> Result<Option<Node>, TreebuilderErr> {
+    let name_i = inp.idx();
+    let name = match (inp.peek(), inp.get(name_i + 1)) {
+        (Some(name), Some(opn)) if name.typ == TokenType::Identifier && is_call_opn(opn) => {
+            name.val.clone()
+        }
+        _ => return Ok(None),
+    };
+
+    let builtin = get_builtin(&name).ok_or(TreebuilderErr::new_unknown_function(name_i))?;
+
+    // Skip the name and the opening parenthesis.
+    inp.next();
+    inp.next();
+
+    let args = consume_args(inp, name_i, var_dict, config)?;
+
+    if args.len() < builtin.min_args || args.len() > builtin.max_args {
+        return Err(TreebuilderErr::new_wrong_arg_cnt(name_i, inp.idx()));
+    }
+
+    let vals = args
+        .iter()
+        .map(|(_, _, n)| n.clone())
+        .collect::<Vec<Node>>();
+
+    builtin
+        .call(&vals, name_i, inp.idx())
+        .map_err(|e| match e {
+            BuiltinErr::Typ { arg, expected } => {
+                let (from, to, n) = &args[arg];
+
+                TreebuilderErr::new_invalid_arg_typ(*from, *to, expected, n.typ())
+                    .with_provenance(n.provenance())
+            }
+            BuiltinErr::Val { arg, reason } => {
+                let (from, to, n) = &args[arg];
+
+                TreebuilderErr::new_invalid_arg_val(*from, *to, reason)
+                    .with_provenance(n.provenance())
+            }
+        })
+        .map(Some)
+}
+
+fn is_call_opn(t: &Token) -> bool {
+    t.typ == TokenType::Delimiter && t.val == "("
+}
+
+/// Consumes the arguments including the closing parenthesis. The ok path returns
+/// the arguments, together with the indices of the tokens they span.
+fn consume_args(
+    inp: &mut Queue<Token>,
+    name_i: usize,
+    var_dict: &Rc<VarDict>,
+    config: &Config,
+) -> Result<Vec<(usize, usize, Node)>, TreebuilderErr> {
+    let mut args = Vec::new();
+
+    if consume_call_cls(inp, name_i)? {
+        return Ok(args);
+    }
+
+    loop {
+        let from = inp.idx();
+        let arg = value_consumer(inp, var_dict, config)?.unwrap();
+
+        args.push((from, inp.idx(), arg));
+
+        if consume_call_cls(inp, name_i)? {
+            return Ok(args);
+        }
+
+        consume_arg_sep(inp)?;
+
+        // Check if the next token is a close, if yes, we have a trailing
+        // separator.
+        if consume_call_cls(inp, name_i)? {
+            if !config.allow_trailing_commas {
+                return Err(TreebuilderErr::new_trailing_sep(inp.idx() - 2));
+            }
+
+            return Ok(args);
+        }
+    }
+}
+
+fn consume_call_cls(inp: &mut Queue<Token>, name_i: usize) -> Result<bool, TreebuilderErr> {
+    let t = inp
+        .peek()
+        .ok_or(TreebuilderErr::new_unterminated_call(name_i))?;
+
+    if t.typ == TokenType::Delimiter && t.val == ")" {
+        inp.next();
+
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+fn consume_arg_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
+    let t = inp.peek().unwrap();
+
+    if t.typ != TokenType::Separator || t.val != "," {
+        return Err(TreebuilderErr::new_not_a_sep(inp.idx()));
+    }
+
+    inp.next();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::treebuilder::{
+        node::{ArrayNode, BoolNode, NodeTyp, NumberNode, StringNode},
+        testing::{new_delimiter, new_ident, new_kwd, new_num, new_sep, new_str},
+    };
+
+    use super::*;
+
+    fn call(toks: Vec<Token>) -> Result<Option<Node>, TreebuilderErr> {
+        let inp = &mut Queue::new(toks);
+        let res = function_call_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT);
+
+        if res.is_ok() {
+            assert_eq!(inp.next(), None);
+        }
+
+        res
+    }
+
+    fn new_call_num(from: usize, to: usize, val: &str) -> Node {
+        let mut n = NumberNode::new(from, val.to_owned());
+        n.to = to;
+        n.into()
+    }
+
+    fn new_call_str(from: usize, to: usize, val: &str) -> Node {
+        let mut n = StringNode::new(from, val.to_owned());
+        n.to = to;
+        n.into()
+    }
+
+    #[test]
+    fn non_call() {
+        let inp = &mut Queue::new(vec![new_ident("len"), new_sep(",")]);
+
+        assert_eq!(
+            function_call_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Ok(None)
+        );
+        assert_eq!(inp.idx(), 0);
+    }
+
+    #[test]
+    fn unknown_function() {
+        assert_eq!(
+            call(vec![
+                new_ident("foo"),
+                new_delimiter("("),
+                new_delimiter(")")
+            ]),
+            Err(TreebuilderErr::new_unknown_function(0))
+        );
+    }
+
+    #[test]
+    fn unterminated() {
+        assert_eq!(
+            call(vec![new_ident("len"), new_delimiter("("), new_str("foo")]),
+            Err(TreebuilderErr::new_unterminated_call(0))
+        );
+    }
+
+    #[test]
+    fn wrong_arg_cnt() {
+        assert_eq!(
+            call(vec![
+                new_ident("len"),
+                new_delimiter("("),
+                new_delimiter(")")
+            ]),
+            Err(TreebuilderErr::new_wrong_arg_cnt(0, 3))
+        );
+    }
+
+    #[test]
+    fn invalid_arg_typ() {
+        assert_eq!(
+            call(vec![
+                new_ident("upper"),
+                new_delimiter("("),
+                new_num("1"),
+                new_delimiter(")"),
+            ]),
+            Err(TreebuilderErr::new_invalid_arg_typ(
+                2,
+                3,
+                "a string",
+                NodeTyp::Number
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_arg_val() {
+        assert_eq!(
+            call(vec![
+                new_ident("to_number"),
+                new_delimiter("("),
+                new_str("abc"),
+                new_delimiter(")"),
+            ]),
+            Err(TreebuilderErr::new_invalid_arg_val(
+                2,
+                3,
+                "the string does not contain a number"
+            ))
+        );
+    }
+
+    #[test]
+    fn len() {
+        assert_eq!(
+            call(vec![
+                new_ident("len"),
+                new_delimiter("("),
+                new_delimiter("["),
+                new_num("1"),
+                new_sep(","),
+                new_num("2"),
+                new_delimiter("]"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(new_call_num(0, 8, "2")))
+        );
+    }
+
+    #[test]
+    fn join() {
+        assert_eq!(
+            call(vec![
+                new_ident("join"),
+                new_delimiter("("),
+                new_delimiter("["),
+                new_str("a"),
+                new_sep(","),
+                new_str("b"),
+                new_delimiter("]"),
+                new_sep(","),
+                new_str(", "),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(new_call_str(0, 10, "a, b")))
+        );
+    }
+
+    #[test]
+    fn nested_call() {
+        assert_eq!(
+            call(vec![
+                new_ident("upper"),
+                new_delimiter("("),
+                new_ident("trim"),
+                new_delimiter("("),
+                new_str(" eu "),
+                new_delimiter(")"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(new_call_str(0, 7, "EU")))
+        );
+    }
+
+    #[test]
+    fn range() {
+        assert_eq!(
+            call(vec![
+                new_ident("range"),
+                new_delimiter("("),
+                new_num("1"),
+                new_sep(","),
+                new_num("3"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(
+                ArrayNode::new(0, 6, vec![new_call_num(0, 6, "1"), new_call_num(0, 6, "2")]).into()
+            ))
+        );
+    }
+
+    #[test]
+    fn contains_compares_values() {
+        assert_eq!(
+            call(vec![
+                new_ident("contains"),
+                new_delimiter("("),
+                new_delimiter("["),
+                new_num("1.0"),
+                new_sep(","),
+                new_kwd("true"),
+                new_delimiter("]"),
+                new_sep(","),
+                new_num("1"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some({
+                let mut n = BoolNode::new(0, true);
+                n.to = 10;
+                n.into()
+            }))
+        );
+    }
+
+    #[test]
+    fn sort_and_unique() {
+        assert_eq!(
+            call(vec![
+                new_ident("sort"),
+                new_delimiter("("),
+                new_ident("unique"),
+                new_delimiter("("),
+                new_delimiter("["),
+                new_num("3"),
+                new_sep(","),
+                new_num("1"),
+                new_sep(","),
+                new_num("3"),
+                new_delimiter("]"),
+                new_delimiter(")"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(
+                ArrayNode::new(
+                    0,
+                    13,
+                    vec![
+                        NumberNode::new(7, "1".to_owned()).into(),
+                        NumberNode::new(5, "3".to_owned()).into(),
+                    ]
+                )
+                .into()
+            ))
+        );
+    }
+
+    #[test]
+    fn sum() {
+        assert_eq!(
+            call(vec![
+                new_ident("sum"),
+                new_delimiter("("),
+                new_delimiter("["),
+                new_num("1"),
+                new_sep(","),
+                new_num("2.5"),
+                new_delimiter("]"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(new_call_num(0, 8, "3.5")))
+        );
+    }
+
+    #[test]
+    fn max() {
+        assert_eq!(
+            call(vec![
+                new_ident("max"),
+                new_delimiter("("),
+                new_delimiter("["),
+                new_num("1"),
+                new_sep(","),
+                new_num("10"),
+                new_sep(","),
+                new_num("2"),
+                new_delimiter("]"),
+                new_delimiter(")"),
+            ]),
+            Ok(Some(NumberNode::new(5, "10".to_owned()).into()))
+        );
+    }
+}
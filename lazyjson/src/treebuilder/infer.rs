@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::node::{ArrayNode, Node, ObjectNode, StringNode};
+
+/// How a schema is inferred by [`infer_schema`].
+#[derive(Clone)]
+pub struct InferConfig {
+    /// Strings are described by an `enum` if they have at most this many
+    /// distinct values, and were observed at least twice. `0` disables enums.
+    pub max_enum_values: usize,
+}
+
+impl InferConfig {
+    pub const DEFAULT: InferConfig = InferConfig { max_enum_values: 5 };
+}
+
+/// Everything that was observed at one location of the samples.
+#[derive(Default)]
+struct Shape {
+    observations: usize,
+    has_bool: bool,
+    has_integer: bool,
+    /// Numbers that are not integers.
+    has_number: bool,
+    has_null: bool,
+    strings: usize,
+    /// The distinct strings, `None` once there are more than fit into an
+    /// `enum`.
+    string_vals: Option<BTreeSet<String>>,
+    /// The shape of all entries of all arrays.
+    items: Option<Box<Shape>>,
+    object: Option<ObjectShape>,
+}
+
+#[derive(Default)]
+struct ObjectShape {
+    observations: usize,
+    entries: BTreeMap<String, Shape>,
+}
+
+impl Shape {
+    fn observe(&mut self, node: &Node, config: &InferConfig) {
+        self.observations += 1;
+
+        match node {
+            Node::Array(a) => {
+                let items = self.items.get_or_insert_with(Default::default);
+
+                for entry in a.entries.iter() {
+                    items.observe(entry, config);
+                }
+            }
+            Node::Bool(_) => self.has_bool = true,
+            Node::Null(_) => self.has_null = true,
+            Node::Number(n) => match n.number().to_i128() {
+                Ok(_) => self.has_integer = true,
+                Err(_) => self.has_number = true,
+            },
+            Node::Object(o) => {
+                let object = self.object.get_or_insert_with(Default::default);
+                object.observations += 1;
+
+                for (key, val) in o.entries.iter() {
+                    object
+                        .entries
+                        .entry(key.clone())
+                        .or_default()
+                        .observe(val, config);
+                }
+            }
+            Node::String(s) => {
+                if self.strings == 0 {
+                    self.string_vals = Some(BTreeSet::new());
+                }
+
+                self.strings += 1;
+
+                if let Some(vals) = &mut self.string_vals {
+                    vals.insert(s.val.clone());
+
+                    if vals.len() > config.max_enum_values {
+                        self.string_vals = None;
+                    }
+                }
+            }
+        }
+    }
+
+    fn to_schema(&self) -> HashMap<String, Node> {
+        let mut schema = HashMap::new();
+
+        let typs = [
+            ("array", self.items.is_some()),
+            ("boolean", self.has_bool),
+            ("integer", self.has_integer && !self.has_number),
+            ("null", self.has_null),
+            ("number", self.has_number),
+            ("object", self.object.is_some()),
+            ("string", self.strings > 0),
+        ]
+        .iter()
+        .filter(|(_, is_observed)| *is_observed)
+        .map(|(typ, _)| *typ)
+        .collect::<Vec<&str>>();
+
+        match typs.as_slice() {
+            [] => {}
+            [typ] => {
+                schema.insert("type".to_owned(), new_str(typ));
+            }
+            _ => {
+                let typs = typs.iter().map(|typ| new_str(typ)).collect();
+                schema.insert("type".to_owned(), Node::Array(ArrayNode::synthetic(typs)));
+            }
+        }
+
+        if let (["string"], Some(vals)) = (typs.as_slice(), &self.string_vals) {
+            if self.strings >= 2 {
+                let vals = vals.iter().map(|val| new_str(val)).collect();
+                schema.insert("enum".to_owned(), Node::Array(ArrayNode::synthetic(vals)));
+            }
+        }
+
+        if let Some(items) = self.items.as_ref().filter(|items| items.observations > 0) {
+            schema.insert("items".to_owned(), new_obj(items.to_schema()));
+        }
+
+        if let Some(object) = &self.object {
+            let properties = object
+                .entries
+                .iter()
+                .map(|(key, shape)| (key.clone(), new_obj(shape.to_schema())))
+                .collect();
+
+            let required = object
+                .entries
+                .iter()
+                .filter(|(_, shape)| shape.observations == object.observations)
+                .map(|(key, _)| new_str(key))
+                .collect::<Vec<Node>>();
+
+            schema.insert("properties".to_owned(), new_obj(properties));
+
+            if !required.is_empty() {
+                schema.insert(
+                    "required".to_owned(),
+                    Node::Array(ArrayNode::synthetic(required)),
+                );
+            }
+        }
+
+        schema
+    }
+}
+
+/// Infers a JSON Schema (draft 2020-12) that all samples conform to. It
+/// describes the observed types (integers and other numbers are merged into
+/// `number`), the keys that are present in every object (`required`), the
+/// shape of the entries of arrays (`items`), and strings with few distinct
+/// values (`enum`, see [`InferConfig::max_enum_values`]).
+///
+/// The schema is meant as a starting point, and is made of generated nodes.
+pub fn infer_schema<'a>(samples: impl IntoIterator<Item = &'a Node>, config: &InferConfig) -> Node {
+    let mut shape = Shape::default();
+
+    for sample in samples {
+        shape.observe(sample, config);
+    }
+
+    let mut schema = shape.to_schema();
+    schema.insert(
+        "$schema".to_owned(),
+        new_str("https://json-schema.org/draft/2020-12/schema"),
+    );
+
+    new_obj(schema)
+}
+
+fn new_str(val: &str) -> Node {
+    Node::String(StringNode::synthetic(val.to_owned()))
+}
+
+fn new_obj(entries: HashMap<String, Node>) -> Node {
+    Node::Object(ObjectNode::synthetic(entries))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::treebuilder::{builtins::eq_val, testing::parse};
+
+    use super::*;
+
+    fn infer(samples: &[&str]) -> Node {
+        let samples = samples.iter().map(|s| parse(s)).collect::<Vec<Node>>();
+        let mut schema = infer_schema(&samples, &InferConfig::DEFAULT);
+
+        if let Node::Object(o) = &mut schema {
+            std::rc::Rc::make_mut(&mut o.entries).remove("$schema");
+        }
+
+        schema
+    }
+
+    fn assert_schema(samples: &[&str], expected: &str) {
+        let schema = infer(samples);
+
+        assert!(eq_val(&schema, &parse(expected)), "{:?}", schema);
+    }
+
+    #[test]
+    fn object() {
+        assert_schema(
+            &[r#"{"host": "a", "port": 80, "ratio": 0.5, "debug": true, "proxy": null}"#],
+            r#"{
+                "type": "object",
+                "properties": {
+                    "host": {"type": "string"},
+                    "port": {"type": "integer"},
+                    "ratio": {"type": "number"},
+                    "debug": {"type": "boolean"},
+                    "proxy": {"type": "null"}
+                },
+                "required": ["debug", "host", "port", "proxy", "ratio"]
+            }"#,
+        );
+    }
+
+    #[test]
+    fn merged_samples() {
+        assert_schema(
+            &[
+                r#"{"env": "dev", "port": 80}"#,
+                r#"{"env": "prod", "port": 8.5, "user": "x"}"#,
+            ],
+            r#"{
+                "type": "object",
+                "properties": {
+                    "env": {"type": "string", "enum": ["dev", "prod"]},
+                    "port": {"type": "number"},
+                    "user": {"type": "string"}
+                },
+                "required": ["env", "port"]
+            }"#,
+        );
+    }
+
+    #[test]
+    fn items() {
+        assert_schema(
+            &[r#"[{"id": 1, "tags": []}, {"id": 2, "tags": ["a", 1]}, null]"#],
+            r#"{
+                "type": "array",
+                "items": {
+                    "type": ["null", "object"],
+                    "properties": {
+                        "id": {"type": "integer"},
+                        "tags": {"type": "array", "items": {"type": ["integer", "string"]}}
+                    },
+                    "required": ["id", "tags"]
+                }
+            }"#,
+        );
+    }
+
+    #[test]
+    fn too_many_strings_for_enum() {
+        assert_schema(
+            &[r#"["a", "b", "c", "d", "e", "f"]"#],
+            r#"{"type": "array", "items": {"type": "string"}}"#,
+        );
+        assert_schema(
+            &[r#"["a", "b", "a"]"#],
+            r#"{"type": "array", "items": {"type": "string", "enum": ["a", "b"]}}"#,
+        );
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn samples_are_valid() {
+        use crate::treebuilder::Schema;
+
+        let samples = [
+            r#"{"a": [1, {"b": "x"}], "c": "y"}"#,
+            r#"{"a": [], "c": "z", "d": null}"#,
+        ]
+        .iter()
+        .map(|s| parse(s))
+        .collect::<Vec<Node>>();
+
+        let schema = Schema::new(&infer_schema(&samples, &InferConfig::DEFAULT)).unwrap();
+
+        assert!(samples.iter().all(|sample| schema.is_valid(sample)));
+    }
+}
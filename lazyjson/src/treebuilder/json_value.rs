@@ -0,0 +1,250 @@
+use std::convert::{TryFrom, TryInto};
+
+use serde_json::{Number as JsonNumber, Value};
+
+use super::{
+    access_error::AccessErr,
+    node::{ArrayNode, BoolNode, Node, NullNode, NumberNode, ObjectNode, StringNode, SYNTHETIC},
+};
+
+/// What to do with numbers that do not fit into a `serde_json::Number`, which
+/// holds an `i64`, an `u64` or a finite `f64`.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum UnfitNumbers {
+    /// Return an error of the typ
+    /// [`AccessErrTyp::NumberOutOfRange`](super::access_error::AccessErrTyp::NumberOutOfRange).
+    Reject,
+    /// Convert them to the nearest `f64`. Numbers outside of its range (`1e400`)
+    /// are still rejected.
+    Round,
+    /// Convert them to a string that contains the original lexeme, so no
+    /// precision is lost.
+    Stringify,
+}
+
+/// The spans of nodes converted from a `serde_json::Value`.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum ValueSpans {
+    /// Mark them as [`SYNTHETIC`].
+    Synthetic,
+    /// Use the span of an existing node, for example the one that is replaced
+    /// by the converted value, so errors still point into the source.
+    Inherit { from: usize, to: usize },
+}
+
+/// How nodes are converted to and from a `serde_json::Value`.
+#[derive(Clone)]
+pub struct ValueConfig {
+    pub unfit_numbers: UnfitNumbers,
+    pub spans: ValueSpans,
+}
+
+impl ValueConfig {
+    /// Rejects numbers that do not fit, and creates synthetic nodes. Used by
+    /// the `From` and `TryFrom` implementations.
+    pub const DEFAULT: ValueConfig = ValueConfig {
+        unfit_numbers: UnfitNumbers::Reject,
+        spans: ValueSpans::Synthetic,
+    };
+}
+
+impl Node {
+    pub fn from_json_value(val: Value, config: &ValueConfig) -> Node {
+        let (from, to) = match config.spans {
+            ValueSpans::Synthetic => (SYNTHETIC, SYNTHETIC),
+            ValueSpans::Inherit { from, to } => (from, to),
+        };
+
+        match val {
+            Value::Array(entries) => ArrayNode::new(
+                from,
+                to,
+                entries
+                    .into_iter()
+                    .map(|entry| Node::from_json_value(entry, config))
+                    .collect(),
+            )
+            .into(),
+            Value::Bool(val) => Node::Bool(BoolNode {
+                val,
+                from,
+                to,
+                provenance: None,
+            }),
+            Value::Null => Node::Null(NullNode {
+                from,
+                to,
+                provenance: None,
+            }),
+            Value::Number(n) => Node::Number(NumberNode {
+                val: n.to_string(),
+                from,
+                to,
+                provenance: None,
+            }),
+            Value::Object(entries) => ObjectNode::from_entries(
+                from,
+                to,
+                entries
+                    .into_iter()
+                    .map(|(key, val)| (key, Node::from_json_value(val, config))),
+            )
+            .into(),
+            Value::String(val) => Node::String(StringNode {
+                val,
+                from,
+                to,
+                provenance: None,
+            }),
+        }
+    }
+    pub fn to_json_value(&self, config: &ValueConfig) -> Result<Value, AccessErr> {
+        match self {
+            Node::Array(a) => Ok(Value::Array(
+                a.entries
+                    .iter()
+                    .map(|entry| entry.to_json_value(config))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Node::Bool(b) => Ok(Value::Bool(b.val)),
+            Node::Null(_) => Ok(Value::Null),
+            Node::Number(n) => number_to_json_value(self, n, config),
+            Node::Object(o) => Ok(Value::Object(
+                o.entries
+                    .iter()
+                    .map(|(key, val)| Ok((key.clone(), val.to_json_value(config)?)))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Node::String(s) => Ok(Value::String(s.val.clone())),
+        }
+    }
+}
+
+fn number_to_json_value(
+    node: &Node,
+    n: &NumberNode,
+    config: &ValueConfig,
+) -> Result<Value, AccessErr> {
+    let number = n.number();
+    let rounded = number
+        .lexeme()
+        .parse::<f64>()
+        .ok()
+        .and_then(JsonNumber::from_f64);
+
+    let fitting = match number.is_integer() {
+        true => number
+            .to_i64()
+            .map(JsonNumber::from)
+            .or_else(|_| number.to_u64().map(JsonNumber::from))
+            .ok(),
+        false => rounded.clone(),
+    };
+
+    let converted = match config.unfit_numbers {
+        _ if fitting.is_some() => fitting.map(Value::Number),
+        UnfitNumbers::Reject => None,
+        UnfitNumbers::Round => rounded.map(Value::Number),
+        UnfitNumbers::Stringify => Some(Value::String(n.val.clone())),
+    };
+
+    converted.ok_or_else(|| AccessErr::new_number_out_of_range(node, "serde_json::Number"))
+}
+
+/// Creates synthetic nodes (see [`ValueConfig::DEFAULT`]).
+impl From<Value> for Node {
+    fn from(val: Value) -> Self {
+        Node::from_json_value(val, &ValueConfig::DEFAULT)
+    }
+}
+
+/// Rejects numbers that do not fit (see [`ValueConfig::DEFAULT`]).
+impl TryFrom<&Node> for Value {
+    type Error = AccessErr;
+
+    fn try_from(node: &Node) -> Result<Self, Self::Error> {
+        node.to_json_value(&ValueConfig::DEFAULT)
+    }
+}
+
+impl TryFrom<Node> for Value {
+    type Error = AccessErr;
+
+    fn try_from(node: Node) -> Result<Self, Self::Error> {
+        (&node).try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json as json_value;
+
+    use crate::{json, treebuilder::access_error::AccessErrTyp};
+
+    use super::*;
+
+    fn num(val: &str) -> Node {
+        Node::Number(NumberNode::new(0, val.to_owned()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let val = json_value!({"a": [1, -2.5, null, true], "b": {"c": "x"}});
+        let node: Node = val.clone().into();
+
+        assert_eq!(node, json!({"a": [1, -2.5, null, true], "b": {"c": "x"}}));
+        assert_eq!(Value::try_from(node), Ok(val));
+    }
+
+    #[test]
+    fn inherit_spans() {
+        let config = ValueConfig {
+            spans: ValueSpans::Inherit { from: 3, to: 8 },
+            ..ValueConfig::DEFAULT
+        };
+        let node = Node::from_json_value(json_value!({"a": [1]}), &config);
+
+        assert_eq!((node.from(), node.to()), (3, 8));
+        assert_eq!((node["a"][0].from(), node["a"][0].to()), (3, 8));
+    }
+
+    #[test]
+    fn reject_unfit_numbers() {
+        for val in ["18446744073709551616", "1e400"] {
+            assert_eq!(
+                Value::try_from(num(val)).unwrap_err().typ,
+                AccessErrTyp::NumberOutOfRange {
+                    expected: "serde_json::Number"
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn round_unfit_numbers() {
+        let config = ValueConfig {
+            unfit_numbers: UnfitNumbers::Round,
+            ..ValueConfig::DEFAULT
+        };
+
+        assert_eq!(
+            num("18446744073709551616").to_json_value(&config),
+            Ok(json_value!(18446744073709551616.0))
+        );
+        assert!(num("1e400").to_json_value(&config).is_err());
+    }
+
+    #[test]
+    fn stringify_unfit_numbers() {
+        let config = ValueConfig {
+            unfit_numbers: UnfitNumbers::Stringify,
+            ..ValueConfig::DEFAULT
+        };
+
+        assert_eq!(
+            num("123456789012345678901234567890").to_json_value(&config),
+            Ok(json_value!("123456789012345678901234567890"))
+        );
+        assert_eq!(num("1.5").to_json_value(&config), Ok(json_value!(1.5)));
+    }
+}
@@ -1,15 +1,74 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Debug},
+    ops::Index,
+    rc::Rc,
+};
+
+use super::{access_error::AccessErr, number::Number};
+
+/// The `from` and `to` of nodes that were not parsed from a source, but created
+/// programmatically (for example with [`json!`](crate::json)).
+pub const SYNTHETIC: usize = usize::MAX;
+
+/// Where a node, that was produced by using a variable, originates from.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Provenance {
+    /// The name of the variable.
+    pub var_name: String,
+    /// Index of the first token of the variable usage. **Inclusive.**
+    pub usage_from: usize,
+    /// Index of the last token of the variable usage. **NON inclusive.**
+    pub usage_to: usize,
+    /// Index of the first token of the variable definition (`let`).
+    /// **Inclusive.**
+    pub def_from: usize,
+    /// Index of the last token of the variable definition. **NON inclusive.**
+    pub def_to: usize,
+}
+
+impl Provenance {
+    pub fn new(var_name: &str, usage_i: usize, def_from: usize, def_to: usize) -> Provenance {
+        Provenance {
+            var_name: var_name.to_owned(),
+            usage_from: usage_i,
+            usage_to: usage_i + 1,
+            def_from,
+            def_to,
+        }
+    }
+}
+
+/// A step of the path from a node to one of its descendants.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum PathSegment {
+    Index(usize),
+    Key(String),
+}
 
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub struct ArrayNode {
-    pub entries: Vec<Node>,
+    /// The entries are shared between clones of the node (for example when a
+    /// variable is used multiple times). Use [`Rc::make_mut`] to modify them,
+    /// which only copies the entries if they are actually shared.
+    pub entries: Rc<Vec<Node>>,
     pub from: usize,
     pub to: usize,
+    pub provenance: Option<Box<Provenance>>,
 }
 
 impl ArrayNode {
     pub fn new(from: usize, to: usize, entries: Vec<Node>) -> ArrayNode {
-        ArrayNode { entries, from, to }
+        ArrayNode {
+            entries: Rc::new(entries),
+            from,
+            to,
+            provenance: None,
+        }
+    }
+    /// Creates an array with [`SYNTHETIC`] spans.
+    pub fn synthetic(entries: Vec<Node>) -> ArrayNode {
+        ArrayNode::new(SYNTHETIC, SYNTHETIC, entries)
     }
 }
 
@@ -18,6 +77,7 @@ pub struct BoolNode {
     pub val: bool,
     pub from: usize,
     pub to: usize,
+    pub provenance: Option<Box<Provenance>>,
 }
 
 impl BoolNode {
@@ -25,6 +85,16 @@ impl BoolNode {
         BoolNode {
             from: i,
             to: i + 1,
+            provenance: None,
+            val,
+        }
+    }
+    /// Creates a bool with [`SYNTHETIC`] spans.
+    pub fn synthetic(val: bool) -> BoolNode {
+        BoolNode {
+            from: SYNTHETIC,
+            to: SYNTHETIC,
+            provenance: None,
             val,
         }
     }
@@ -34,11 +104,24 @@ impl BoolNode {
 pub struct NullNode {
     pub from: usize,
     pub to: usize,
+    pub provenance: Option<Box<Provenance>>,
 }
 
 impl NullNode {
     pub fn new(i: usize) -> NullNode {
-        NullNode { from: i, to: i + 1 }
+        NullNode {
+            from: i,
+            to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a null with [`SYNTHETIC`] spans.
+    pub fn synthetic() -> NullNode {
+        NullNode {
+            from: SYNTHETIC,
+            to: SYNTHETIC,
+            provenance: None,
+        }
     }
 }
 
@@ -47,6 +130,7 @@ pub struct NumberNode {
     pub val: String,
     pub from: usize,
     pub to: usize,
+    pub provenance: Option<Box<Provenance>>,
 }
 
 impl NumberNode {
@@ -54,29 +138,165 @@ impl NumberNode {
         NumberNode {
             from: i,
             to: i + 1,
+            provenance: None,
             val,
         }
     }
+    /// Creates a number with [`SYNTHETIC`] spans. The value has to be a valid
+    /// JSON number.
+    pub fn synthetic(val: String) -> NumberNode {
+        NumberNode {
+            from: SYNTHETIC,
+            to: SYNTHETIC,
+            provenance: None,
+            val,
+        }
+    }
+    /// Returns the typed representation of the value, which keeps the
+    /// original lexeme.
+    pub fn number(&self) -> Number {
+        Number::from_lexeme(self.val.clone())
+    }
 }
 
-#[derive(Eq, PartialEq, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct ObjectNode {
-    pub entries: HashMap<String, Node>,
+    /// The entries are shared between clones of the node (for example when a
+    /// variable is used multiple times). Use [`Rc::make_mut`] to modify them,
+    /// which only copies the entries if they are actually shared.
+    pub entries: Rc<HashMap<String, Node>>,
+    /// The keys in the order they were written in the source, or inserted with
+    /// [`ObjectNode::insert`]. Keys that were inserted into `entries` directly
+    /// are missing, and keys that were removed from it may still be contained
+    /// (see [`ObjectNode::entries_ordered`]).
+    pub keys: Rc<Vec<String>>,
     pub from: usize,
     pub to: usize,
+    pub provenance: Option<Box<Provenance>>,
 }
 
 impl ObjectNode {
+    /// Creates an object without a known order of the keys, so
+    /// [`KeyOrder::Source`] sorts them by key.
     pub fn new(from: usize, to: usize, entries: HashMap<String, Node>) -> ObjectNode {
-        ObjectNode { from, to, entries }
+        ObjectNode {
+            from,
+            to,
+            entries: Rc::new(entries),
+            keys: Rc::new(Vec::new()),
+            provenance: None,
+        }
+    }
+    /// Creates an object that keeps the order of the entries. An entry with
+    /// a key that occurred before replaces the value, but keeps the position.
+    pub fn from_entries(
+        from: usize,
+        to: usize,
+        entries: impl IntoIterator<Item = (String, Node)>,
+    ) -> ObjectNode {
+        let mut obj = ObjectNode::new(from, to, HashMap::new());
+
+        for (key, val) in entries {
+            obj.insert(key, val);
+        }
+
+        obj
+    }
+    /// Creates an object with [`SYNTHETIC`] spans.
+    pub fn synthetic(entries: HashMap<String, Node>) -> ObjectNode {
+        ObjectNode::new(SYNTHETIC, SYNTHETIC, entries)
+    }
+    /// Inserts the entry, and returns the replaced value. New keys are
+    /// appended to [`ObjectNode::keys`].
+    pub fn insert(&mut self, key: String, val: Node) -> Option<Node> {
+        if !self.entries.contains_key(&key) {
+            Rc::make_mut(&mut self.keys).push(key.clone());
+        }
+
+        Rc::make_mut(&mut self.entries).insert(key, val)
+    }
+    /// Removes the entry, and returns its value.
+    pub fn remove(&mut self, key: &str) -> Option<Node> {
+        if !self.entries.contains_key(key) {
+            return None;
+        }
+
+        Rc::make_mut(&mut self.keys).retain(|k| k != key);
+        Rc::make_mut(&mut self.entries).remove(key)
+    }
+    /// Returns the entries in the order.
+    pub fn entries_ordered(&self, key_order: KeyOrder) -> Vec<(&String, &Node)> {
+        match key_order {
+            KeyOrder::Source => {
+                // The keys may contain removed keys, or the same key twice if
+                // it was removed from `entries` directly and inserted again.
+                let mut seen = HashSet::new();
+
+                let mut entries = self
+                    .keys
+                    .iter()
+                    .filter_map(|key| self.entries.get_key_value(key))
+                    .filter(|(key, _)| seen.insert(*key))
+                    .collect::<Vec<(&String, &Node)>>();
+
+                if entries.len() < self.entries.len() {
+                    let mut rest = self
+                        .entries
+                        .iter()
+                        .filter(|(key, _)| !seen.contains(key))
+                        .collect::<Vec<(&String, &Node)>>();
+                    rest.sort_by_key(|&(key, _)| key);
+
+                    entries.extend(rest);
+                }
+
+                entries
+            }
+            KeyOrder::Sorted => {
+                let mut entries = self.entries.iter().collect::<Vec<(&String, &Node)>>();
+                entries.sort_by_key(|&(key, _)| key);
+
+                entries
+            }
+            KeyOrder::None => self.entries.iter().collect(),
+        }
     }
 }
 
+/// The order of the keys is not compared, like the entries are compared as
+/// a map.
+impl PartialEq for ObjectNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+            && self.from == other.from
+            && self.to == other.to
+            && self.provenance == other.provenance
+    }
+}
+
+impl Eq for ObjectNode {}
+
+/// The order in which the entries of an object are visited (see
+/// [`ObjectNode::entries_ordered`]).
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum KeyOrder {
+    /// The order the keys were written in the source, or inserted in (see
+    /// [`ObjectNode::keys`]). The order of entries with an unknown position
+    /// is sorted by their key, and they come last.
+    Source,
+    /// Sorted by key.
+    Sorted,
+    /// The order of the `HashMap` the entries are stored in. It is unspecified
+    /// and may differ between runs, but nothing needs to be sorted.
+    None,
+}
+
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub struct StringNode {
     pub val: String,
     pub from: usize,
     pub to: usize,
+    pub provenance: Option<Box<Provenance>>,
 }
 
 impl StringNode {
@@ -85,10 +305,46 @@ impl StringNode {
             val,
             from: i,
             to: i + 1,
+            provenance: None,
+        }
+    }
+    /// Creates a string with [`SYNTHETIC`] spans.
+    pub fn synthetic(val: String) -> StringNode {
+        StringNode {
+            val,
+            from: SYNTHETIC,
+            to: SYNTHETIC,
+            provenance: None,
         }
     }
 }
 
+/// The type of a [`Node`], without any of its data.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum NodeTyp {
+    Array,
+    Bool,
+    Null,
+    Number,
+    Object,
+    String,
+}
+
+impl fmt::Display for NodeTyp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NodeTyp::Array => "array",
+            NodeTyp::Bool => "bool",
+            NodeTyp::Null => "null",
+            NodeTyp::Number => "number",
+            NodeTyp::Object => "object",
+            NodeTyp::String => "string",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub enum Node {
     Array(ArrayNode),
@@ -100,6 +356,16 @@ pub enum Node {
 }
 
 impl Node {
+    pub fn typ(&self) -> NodeTyp {
+        match &self {
+            Node::Array(_) => NodeTyp::Array,
+            Node::Bool(_) => NodeTyp::Bool,
+            Node::Null(_) => NodeTyp::Null,
+            Node::Number(_) => NodeTyp::Number,
+            Node::Object(_) => NodeTyp::Object,
+            Node::String(_) => NodeTyp::String,
+        }
+    }
     /// Returns the index of the starting token that was used to create this node.
     /// **The index is inclusive.**
     pub fn from(&self) -> usize {
@@ -124,6 +390,154 @@ impl Node {
             Node::String(s) => s.to,
         }
     }
+    /// Returns true if the node was created programmatically, and therefore has
+    /// no position in a source (see [`SYNTHETIC`]).
+    pub fn is_synthetic(&self) -> bool {
+        self.from() == SYNTHETIC
+    }
+    /// Returns where this node originates from, if it was produced by using a
+    /// variable. [`Node::from`] and [`Node::to`] still refer to the value in
+    /// the variable definition.
+    pub fn provenance(&self) -> Option<&Provenance> {
+        let provenance = match &self {
+            Node::Array(a) => &a.provenance,
+            Node::Bool(b) => &b.provenance,
+            Node::Null(n) => &n.provenance,
+            Node::Number(n) => &n.provenance,
+            Node::Object(o) => &o.provenance,
+            Node::String(s) => &s.provenance,
+        };
+
+        provenance.as_deref()
+    }
+    /// Returns the node with the provenance replaced.
+    pub fn with_provenance(mut self, provenance: Provenance) -> Node {
+        let provenance = Some(Box::new(provenance));
+
+        match &mut self {
+            Node::Array(a) => a.provenance = provenance,
+            Node::Bool(b) => b.provenance = provenance,
+            Node::Null(n) => n.provenance = provenance,
+            Node::Number(n) => n.provenance = provenance,
+            Node::Object(o) => o.provenance = provenance,
+            Node::String(s) => s.provenance = provenance,
+        }
+
+        self
+    }
+}
+
+/// Typed accessors. Accessing a node as a different type than it has returns an
+/// [`AccessErr`] with the span of the node, so it can point into the source.
+impl Node {
+    pub fn as_str(&self) -> Result<&str, AccessErr> {
+        match self {
+            Node::String(s) => Ok(&s.val),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::String)),
+        }
+    }
+    pub fn as_bool(&self) -> Result<bool, AccessErr> {
+        match self {
+            Node::Bool(b) => Ok(b.val),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Bool)),
+        }
+    }
+    /// Returns the number as an `i64`. Numbers with an exponent or a fraction
+    /// are accepted, as long as their value is an integer (`1e3`, `2.0`).
+    pub fn as_i64(&self) -> Result<i64, AccessErr> {
+        self.get_number()?
+            .to_i64()
+            .map_err(|_| AccessErr::new_number_out_of_range(self, "i64"))
+    }
+    /// Returns the number as an `u64`. Numbers with an exponent or a fraction
+    /// are accepted, as long as their value is an integer (`1e3`, `2.0`).
+    pub fn as_u64(&self) -> Result<u64, AccessErr> {
+        self.get_number()?
+            .to_u64()
+            .map_err(|_| AccessErr::new_number_out_of_range(self, "u64"))
+    }
+    /// Returns the number as an `i128`, for integers that do not fit into an
+    /// `i64`.
+    pub fn as_i128(&self) -> Result<i128, AccessErr> {
+        self.get_number()?
+            .to_i128()
+            .map_err(|_| AccessErr::new_number_out_of_range(self, "i128"))
+    }
+    /// Returns the number as an `f64`. Numbers that are too large to be
+    /// represented (`1e400`), and integers that would lose precision, are
+    /// rejected (see [`Number::to_f64`]).
+    pub fn as_f64(&self) -> Result<f64, AccessErr> {
+        self.get_number()?
+            .to_f64()
+            .map_err(|_| AccessErr::new_number_out_of_range(self, "f64"))
+    }
+    /// Returns the typed representation of the number (see
+    /// [`NumberNode::number`]).
+    pub fn as_number(&self) -> Result<Number, AccessErr> {
+        self.get_number()
+    }
+    pub fn as_array(&self) -> Result<&[Node], AccessErr> {
+        match self {
+            Node::Array(a) => Ok(&a.entries),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Array)),
+        }
+    }
+    pub fn as_object(&self) -> Result<&HashMap<String, Node>, AccessErr> {
+        match self {
+            Node::Object(o) => Ok(&o.entries),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Object)),
+        }
+    }
+    pub fn is_null(&self) -> bool {
+        matches!(self, Node::Null(_))
+    }
+    /// Returns the entry of the object with the key.
+    pub fn get(&self, key: &str) -> Result<&Node, AccessErr> {
+        self.as_object()?
+            .get(key)
+            .ok_or_else(|| AccessErr::new_missing_key(self, key))
+    }
+    /// Returns the entry of the array at the index.
+    pub fn get_index(&self, i: usize) -> Result<&Node, AccessErr> {
+        let entries = self.as_array()?;
+
+        entries
+            .get(i)
+            .ok_or_else(|| AccessErr::new_missing_index(self, i, entries.len()))
+    }
+
+    fn get_number(&self) -> Result<Number, AccessErr> {
+        match self {
+            Node::Number(n) => Ok(n.number()),
+            _ => Err(AccessErr::new_invalid_typ(self, NodeTyp::Number)),
+        }
+    }
+}
+
+/// Panics if the node is not an object, or has no entry with the key. Use
+/// [`Node::get`] to handle these cases.
+impl Index<&str> for Node {
+    type Output = Node;
+
+    fn index(&self, key: &str) -> &Node {
+        match self.get(key) {
+            Ok(n) => n,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+
+/// Panics if the node is not an array, or has no entry at the index. Use
+/// [`Node::get_index`] to handle these cases.
+impl Index<usize> for Node {
+    type Output = Node;
+
+    fn index(&self, i: usize) -> &Node {
+        match self.get_index(i) {
+            Ok(n) => n,
+            Err(e) => panic!("{}", e),
+        }
+    }
 }
 
 impl From<ArrayNode> for Node {
@@ -161,3 +575,174 @@ impl From<StringNode> for Node {
         Node::String(str)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::treebuilder::access_error::AccessErrTyp;
+
+    use super::*;
+
+    fn num(i: usize, val: &str) -> Node {
+        NumberNode::new(i, val.to_owned()).into()
+    }
+
+    fn config() -> Node {
+        ObjectNode::new(
+            0,
+            9,
+            HashMap::from([
+                (
+                    "host".to_owned(),
+                    StringNode::new(3, "db".to_owned()).into(),
+                ),
+                (
+                    "ports".to_owned(),
+                    ArrayNode::new(6, 9, vec![num(7, "80")]).into(),
+                ),
+            ]),
+        )
+        .into()
+    }
+
+    #[test]
+    fn get_and_index() {
+        let config = config();
+
+        assert_eq!(config.get("host").and_then(Node::as_str), Ok("db"));
+        assert_eq!(config["ports"][0].as_u64(), Ok(80));
+    }
+
+    #[test]
+    fn invalid_typ() {
+        assert_eq!(
+            config()["host"].as_bool(),
+            Err(AccessErr {
+                typ: AccessErrTyp::InvalidTyp {
+                    expected: NodeTyp::Bool,
+                    received: NodeTyp::String,
+                },
+                from: 3,
+                to: 4,
+                provenance: None,
+            })
+        );
+    }
+
+    #[test]
+    fn missing_key() {
+        let err = config().get("user").unwrap_err();
+
+        assert_eq!(
+            err.typ,
+            AccessErrTyp::MissingKey {
+                key: "user".to_owned()
+            }
+        );
+        assert_eq!((err.from, err.to), (0, 9));
+    }
+
+    #[test]
+    fn missing_index() {
+        let err = config()["ports"].get_index(1).unwrap_err();
+
+        assert_eq!(err.typ, AccessErrTyp::MissingIndex { idx: 1, len: 1 });
+        assert_eq!((err.from, err.to), (6, 9));
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_of_non_object() {
+        let _ = &num(0, "1")["foo"];
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(num(0, "-3").as_i64(), Ok(-3));
+        assert_eq!(num(0, "1e3").as_u64(), Ok(1000));
+        assert_eq!(num(0, "2.0").as_i64(), Ok(2));
+        assert_eq!(num(0, "18446744073709551615").as_u64(), Ok(u64::MAX));
+        assert_eq!(
+            num(0, "-18446744073709551616").as_i128(),
+            Ok(-18446744073709551616)
+        );
+    }
+
+    #[test]
+    fn integers_out_of_range() {
+        for (val, expected) in [("1.5", "i64"), ("9223372036854775808", "i64")] {
+            assert_eq!(
+                num(0, val).as_i64().unwrap_err().typ,
+                AccessErrTyp::NumberOutOfRange { expected }
+            );
+        }
+
+        assert_eq!(
+            num(0, "-1").as_u64().unwrap_err().typ,
+            AccessErrTyp::NumberOutOfRange { expected: "u64" }
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(num(0, "1.5e1").as_f64(), Ok(15.0));
+        assert_eq!(
+            num(0, "1e400").as_f64().unwrap_err().typ,
+            AccessErrTyp::NumberOutOfRange { expected: "f64" }
+        );
+        assert_eq!(
+            num(0, "9007199254740993").as_f64().unwrap_err().typ,
+            AccessErrTyp::NumberOutOfRange { expected: "f64" }
+        );
+    }
+
+    #[test]
+    fn number_keeps_lexeme() {
+        let number = num(0, "1.50e2").as_number().unwrap();
+
+        assert_eq!(number.lexeme(), "1.50e2");
+        assert_eq!(number.to_i64(), Ok(150));
+    }
+
+    #[test]
+    fn null() {
+        assert!(Node::Null(NullNode::new(0)).is_null());
+        assert!(!num(0, "0").is_null());
+    }
+
+    #[test]
+    fn error_carries_provenance() {
+        let provenance = Provenance::new("port", 5, 0, 4);
+        let node = num(3, "1.5").with_provenance(provenance.clone());
+
+        assert_eq!(node.as_u64().unwrap_err().provenance, Some(provenance));
+    }
+
+    #[test]
+    fn entries_ordered() {
+        let mut obj = ObjectNode::from_entries(
+            0,
+            9,
+            [("b", 1), ("c", 2), ("a", 3), ("c", 4)]
+                .iter()
+                .map(|&(key, val)| (key.to_owned(), val.into())),
+        );
+        let keys = |obj: &ObjectNode, key_order| {
+            obj.entries_ordered(key_order)
+                .into_iter()
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<&str>>()
+                .join("")
+        };
+
+        assert_eq!(keys(&obj, KeyOrder::Source), "bca");
+        assert_eq!(keys(&obj, KeyOrder::Sorted), "abc");
+        assert_eq!(obj.entries["c"], 4.into());
+
+        obj.remove("b");
+        obj.insert("b".to_owned(), 5.into());
+        Rc::make_mut(&mut obj.entries).insert("0".to_owned(), 6.into());
+        Rc::make_mut(&mut obj.entries).remove("c");
+
+        assert_eq!(keys(&obj, KeyOrder::Source), "ab0");
+    }
+}
@@ -0,0 +1,440 @@
+use std::{
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+
+/// Whether the literal of a [`Number`] is written as an integer (`12`) or as a
+/// float (`12.0`, `1.2e1`). The classification only depends on the literal,
+/// `2.0` is a float even though its value is an integer.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum NumberKind {
+    Integer,
+    Float,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum NumberErr {
+    /// The string is not a valid JSON number.
+    Invalid,
+    /// The value is too large (or too small) for the target type.
+    Overflow { target: &'static str },
+    /// The value can not be represented exactly by the target type, for
+    /// example `1.5` as an `i64`, or `2^53 + 1` as an `f64`.
+    PrecisionLoss { target: &'static str },
+}
+
+impl fmt::Display for NumberErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for NumberErr {}
+
+/// A JSON number that keeps its original lexeme (`1.50`, `1e3`), so it can be
+/// emitted exactly as it was written, no matter how large or precise it is.
+/// The conversions are checked, and are exact: an integer is only returned if
+/// the value of the literal is an integer that fits into the type.
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+pub struct Number {
+    lexeme: String,
+}
+
+impl Number {
+    /// Parses a JSON number (`-?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?`).
+    pub fn parse(lexeme: &str) -> Result<Number, NumberErr> {
+        match decompose(lexeme) {
+            Some(_) => Ok(Number {
+                lexeme: lexeme.to_owned(),
+            }),
+            None => Err(NumberErr::Invalid),
+        }
+    }
+    /// Wraps a lexeme that is known to be a valid JSON number, for example the
+    /// value of a parsed [`NumberNode`](super::node::NumberNode). Conversions
+    /// of an invalid lexeme return [`NumberErr::Invalid`].
+    pub(crate) fn from_lexeme(lexeme: String) -> Number {
+        Number { lexeme }
+    }
+
+    /// Returns the number exactly as it was written.
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+    pub fn kind(&self) -> NumberKind {
+        match self.lexeme.contains(['.', 'e', 'E']) {
+            true => NumberKind::Float,
+            false => NumberKind::Integer,
+        }
+    }
+    pub fn is_integer(&self) -> bool {
+        self.kind() == NumberKind::Integer
+    }
+
+    pub fn to_i64(&self) -> Result<i64, NumberErr> {
+        let n = self.to_int("i64")?;
+
+        i64::try_from(n).map_err(|_| NumberErr::Overflow { target: "i64" })
+    }
+    pub fn to_u64(&self) -> Result<u64, NumberErr> {
+        let n = self.to_int("u64")?;
+
+        u64::try_from(n).map_err(|_| NumberErr::Overflow { target: "u64" })
+    }
+    pub fn to_i128(&self) -> Result<i128, NumberErr> {
+        self.to_int("i128")
+    }
+    /// Returns the nearest `f64`. Rounding of decimal fractions (`0.1`) is not
+    /// reported, but integers that can not be represented exactly are, as well
+    /// as non-zero values that are rounded to zero.
+    pub fn to_f64(&self) -> Result<f64, NumberErr> {
+        let (is_negative, digits, exp) = decompose(&self.lexeme).ok_or(NumberErr::Invalid)?;
+        let n = self.lexeme.parse::<f64>().map_err(|_| NumberErr::Invalid)?;
+
+        if !n.is_finite() {
+            return Err(NumberErr::Overflow { target: "f64" });
+        }
+
+        let is_zero = digits.is_empty();
+
+        if n == 0.0 && !is_zero {
+            return Err(NumberErr::PrecisionLoss { target: "f64" });
+        }
+
+        if let Ok(magnitude) = get_int_magnitude(&digits, exp, "f64") {
+            if n.abs() as u128 != magnitude || (n.abs() >= 2f64.powi(128)) {
+                return Err(NumberErr::PrecisionLoss { target: "f64" });
+            }
+        }
+
+        match is_negative && is_zero {
+            true => Ok(-0.0),
+            false => Ok(n),
+        }
+    }
+
+    /// Returns whether both numbers have the same value (`1.0`, `1` and `10e-1`
+    /// are equal). Unlike the conversions, this is exact for any lexeme.
+    pub fn eq_val(&self, other: &Number) -> bool {
+        self.canonical() == other.canonical()
+    }
+    /// Hashes the value of the number, consistently with [`Number::eq_val`].
+    pub fn hash_val<H: Hasher>(&self, state: &mut H) {
+        self.canonical().hash(state)
+    }
+
+    /// Returns the value exactly as `mantissa * 10^exp`, or `None` if the
+    /// mantissa does not fit into an `i128`.
+    pub(crate) fn to_decimal(&self) -> Option<(i128, i64)> {
+        let (is_negative, digits, exp) = self.canonical().ok()?;
+
+        if digits.is_empty() {
+            return Some((0, 0));
+        }
+
+        let mantissa = digits.parse::<i128>().ok()?;
+
+        match is_negative {
+            true => Some((-mantissa, exp)),
+            false => Some((mantissa, exp)),
+        }
+    }
+
+    /// Returns the sign, the significant digits without trailing zeros and the
+    /// exponent (see [`decompose`]), with a positive zero. Invalid lexemes are
+    /// kept as they are.
+    fn canonical(&self) -> Result<(bool, String, i64), &str> {
+        let (is_negative, digits, exp) = decompose(&self.lexeme).ok_or(self.lexeme.as_str())?;
+        let trimmed = digits.trim_end_matches('0');
+
+        match trimmed.is_empty() {
+            true => Ok((false, String::new(), 0)),
+            false => Ok((
+                is_negative,
+                trimmed.to_owned(),
+                exp + (digits.len() - trimmed.len()) as i64,
+            )),
+        }
+    }
+
+    fn to_int(&self, target: &'static str) -> Result<i128, NumberErr> {
+        let (is_negative, digits, exp) = decompose(&self.lexeme).ok_or(NumberErr::Invalid)?;
+        let magnitude = get_int_magnitude(&digits, exp, target)?;
+
+        match is_negative {
+            // `i128::MIN` has no positive counterpart, so it is handled by
+            // wrapping.
+            true if magnitude <= i128::MAX as u128 + 1 => Ok((magnitude as i128).wrapping_neg()),
+            false if magnitude <= i128::MAX as u128 => Ok(magnitude as i128),
+            _ => Err(NumberErr::Overflow { target }),
+        }
+    }
+}
+
+/// Splits a JSON number into its sign, its significant digits (without leading
+/// zeros, empty for zero) and the exponent of the last digit, so the value is
+/// `digits * 10^exp`. Returns `None` if the lexeme is not a JSON number.
+fn decompose(lexeme: &str) -> Option<(bool, String, i64)> {
+    let (is_negative, rest) = match lexeme.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, lexeme),
+    };
+
+    let (mantissa, exp) = match rest.find(['e', 'E']) {
+        Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+        None => (rest, None),
+    };
+
+    let (int, frac) = match mantissa.split_once('.') {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (mantissa, None),
+    };
+
+    let is_digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+
+    if !is_digits(int) || (int.len() > 1 && int.starts_with('0')) {
+        return None;
+    }
+
+    if frac.is_some_and(|frac| !is_digits(frac)) {
+        return None;
+    }
+
+    let exp = match exp {
+        None => 0,
+        Some(exp) => {
+            let digits = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+
+            if !is_digits(digits) {
+                return None;
+            }
+
+            // Exponents that do not fit are clamped, the value is out of range
+            // of every supported type anyway.
+            exp.parse::<i64>().unwrap_or(match exp.starts_with('-') {
+                true => i64::MIN / 2,
+                false => i64::MAX / 2,
+            })
+        }
+    };
+
+    let frac = frac.unwrap_or("");
+    let digits = format!("{}{}", int, frac);
+    let digits = digits.trim_start_matches('0').to_owned();
+
+    Some((is_negative, digits, exp - frac.len() as i64))
+}
+
+/// Returns the absolute value of `digits * 10^exp` if it is an integer.
+fn get_int_magnitude(digits: &str, exp: i64, target: &'static str) -> Result<u128, NumberErr> {
+    if digits.is_empty() {
+        return Ok(0);
+    }
+
+    let (digits, exp) = match exp < 0 {
+        true => {
+            let frac_len = exp.unsigned_abs() as usize;
+
+            if frac_len > digits.len()
+                || !digits[digits.len() - frac_len..].bytes().all(|b| b == b'0')
+            {
+                return Err(NumberErr::PrecisionLoss { target });
+            }
+
+            (&digits[..digits.len() - frac_len], 0)
+        }
+        false => (digits, exp),
+    };
+
+    // `u128::MAX` has 39 digits.
+    if digits.len() as i64 + exp > 39 {
+        return Err(NumberErr::Overflow { target });
+    }
+
+    digits
+        .parse::<u128>()
+        .ok()
+        .and_then(|n| n.checked_mul(10u128.checked_pow(exp as u32)?))
+        .ok_or(NumberErr::Overflow { target })
+}
+
+impl FromStr for Number {
+    type Err = NumberErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Number::parse(s)
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lexeme)
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($typ:ty),*) => {
+        $(
+            impl From<$typ> for Number {
+                fn from(n: $typ) -> Self {
+                    Number::from_lexeme(n.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i32, i64, i128, u32, u64, u128, usize);
+
+/// `NaN` and infinity can not be represented in JSON.
+impl TryFrom<f64> for Number {
+    type Error = NumberErr;
+
+    fn try_from(n: f64) -> Result<Self, Self::Error> {
+        match n.is_finite() {
+            // The shortest representation that parses back to the same value.
+            true => Ok(Number::from_lexeme(n.to_string())),
+            false => Err(NumberErr::Invalid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(lexeme: &str) -> Number {
+        Number::parse(lexeme).unwrap()
+    }
+
+    #[test]
+    fn invalid() {
+        for lexeme in [
+            "", "-", "01", "1.", ".1", "1e", "1e+", "+1", "1.5.2", "0x10", "1_000", "NaN",
+        ] {
+            assert_eq!(Number::parse(lexeme), Err(NumberErr::Invalid), "{}", lexeme);
+        }
+    }
+
+    #[test]
+    fn kind() {
+        assert_eq!(num("-12").kind(), NumberKind::Integer);
+        assert_eq!(num("2.0").kind(), NumberKind::Float);
+        assert_eq!(num("1E3").kind(), NumberKind::Float);
+    }
+
+    #[test]
+    fn lexeme_is_preserved() {
+        assert_eq!(num("1.50e+02").lexeme(), "1.50e+02");
+        assert_eq!(num("-0.0").to_string(), "-0.0");
+    }
+
+    #[test]
+    fn snowflake_ids_are_exact() {
+        let id = num("1234567890123456789");
+
+        assert_eq!(id.to_i64(), Ok(1234567890123456789));
+        assert_eq!(id.to_f64(), Err(NumberErr::PrecisionLoss { target: "f64" }));
+        assert_eq!(id.lexeme(), "1234567890123456789");
+    }
+
+    #[test]
+    fn integral_floats() {
+        assert_eq!(num("2.0").to_i64(), Ok(2));
+        assert_eq!(num("1e3").to_u64(), Ok(1000));
+        assert_eq!(num("1.25e2").to_i64(), Ok(125));
+        assert_eq!(num("-0").to_i64(), Ok(0));
+        assert_eq!(num("0e999999999999999999999").to_i64(), Ok(0));
+        assert_eq!(num("12300e-2").to_i64(), Ok(123));
+    }
+
+    #[test]
+    fn precision_loss() {
+        for lexeme in ["1.5", "1e-1", "123e-2"] {
+            assert_eq!(
+                num(lexeme).to_i64(),
+                Err(NumberErr::PrecisionLoss { target: "i64" })
+            );
+        }
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(num("9223372036854775807").to_i64(), Ok(i64::MAX));
+        assert_eq!(num("-9223372036854775808").to_i64(), Ok(i64::MIN));
+        assert_eq!(
+            num("9223372036854775808").to_i64(),
+            Err(NumberErr::Overflow { target: "i64" })
+        );
+        assert_eq!(num("18446744073709551615").to_u64(), Ok(u64::MAX));
+        assert_eq!(
+            num("-1").to_u64(),
+            Err(NumberErr::Overflow { target: "u64" })
+        );
+        assert_eq!(
+            num("-170141183460469231731687303715884105728").to_i128(),
+            Ok(i128::MIN)
+        );
+        assert_eq!(
+            num("170141183460469231731687303715884105728").to_i128(),
+            Err(NumberErr::Overflow { target: "i128" })
+        );
+        assert_eq!(
+            num("1e400").to_i128(),
+            Err(NumberErr::Overflow { target: "i128" })
+        );
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(num("0.1").to_f64(), Ok(0.1));
+        assert_eq!(num("-1.5e1").to_f64(), Ok(-15.0));
+        assert_eq!(num("9007199254740992").to_f64(), Ok(9007199254740992.0));
+        assert_eq!(
+            num("9007199254740993").to_f64(),
+            Err(NumberErr::PrecisionLoss { target: "f64" })
+        );
+        assert_eq!(
+            num("1e400").to_f64(),
+            Err(NumberErr::Overflow { target: "f64" })
+        );
+        assert_eq!(
+            num("1e-400").to_f64(),
+            Err(NumberErr::PrecisionLoss { target: "f64" })
+        );
+        assert!(num("-0").to_f64().unwrap().is_sign_negative());
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Number::from(i128::MAX).to_i128(), Ok(i128::MAX));
+        assert_eq!(
+            Number::try_from(0.5).map(|n| n.to_string()),
+            Ok("0.5".to_owned())
+        );
+        assert_eq!(Number::try_from(f64::NAN), Err(NumberErr::Invalid));
+        assert_eq!("12".parse::<Number>(), Ok(num("12")));
+    }
+
+    #[test]
+    fn eq_val() {
+        for (a, b) in [
+            ("1", "1.0"),
+            ("10e-1", "1"),
+            ("-0", "0.0e5"),
+            ("1.50", "15e-1"),
+        ] {
+            assert!(num(a).eq_val(&num(b)), "{} {}", a, b);
+        }
+
+        for (a, b) in [
+            ("1", "-1"),
+            ("0.1", "0.10000000000000001"),
+            ("1e400", "2e400"),
+        ] {
+            assert!(!num(a).eq_val(&num(b)), "{} {}", a, b);
+        }
+    }
+}
@@ -1,8 +1,8 @@
 use super::{
     config::Config,
     error::TreebuilderErr,
-    node::{Node, ObjectNode},
-    value_consumer::value_consumer,
+    node::Node,
+    value_consumer::{NodeBuilder, ValBuilder},
     var_dict::VarDict,
     variable_definition_consumer::variable_definition_consumer,
 };
@@ -10,7 +10,7 @@ use crate::{
     queue::Queue,
     tokenizer::{Token, TokenType},
 };
-use std::{collections::HashMap, rc::Rc};
+use std::rc::Rc;
 
 pub fn object_consumer(
     inp: &mut Queue<Token>,
@@ -23,40 +23,46 @@ pub fn object_consumer(
         return Ok(None);
     }
 
-    let mut entries = HashMap::new();
-    let mut var_dict = VarDict::new_with_parent(var_dict);
+    consume_obj(&mut NodeBuilder, inp, opn_i, var_dict, config).map(Some)
+}
+
+/// Consumes the rest of an object after the opening brace at `opn_i`, and
+/// builds it with the builder.
+pub(super) fn consume_obj<B: ValBuilder>(
+    builder: &mut B,
+    inp: &mut Queue<Token>,
+    opn_i: usize,
+    var_dict: &Rc<VarDict>,
+    config: &Config,
+) -> Result<B::Val, TreebuilderErr> {
+    let mut entries = Vec::new();
+    let mut var_dict = Rc::new(VarDict::new_with_parent(var_dict));
 
     // Check if the object is immediately closed again (empty).
     if consume_obj_cls(inp, opn_i)? {
-        return Ok(Some(ObjectNode::new(opn_i, inp.idx(), entries).into()));
+        return builder.obj(entries, opn_i, inp.idx());
     }
 
     loop {
-        if let Some((var_key, var_val)) =
-            // TODO: figure out how to do this without cloning
-            variable_definition_consumer(inp, &Rc::new(var_dict.clone()), config)?
-        {
-            var_dict.insert(var_key, var_val);
+        if let Some((var_key, var_def)) = variable_definition_consumer(inp, &var_dict, config)? {
+            // The nested consumers are done with the scope, so it is no
+            // longer shared and can be extended without copying it.
+            Rc::make_mut(&mut var_dict).insert_def(var_key, var_def);
         } else {
             let key_i = inp.idx();
             let key = consume_key(inp)?;
 
             consume_assignment(inp, key_i)?;
 
-            let val = match value_consumer(
-                inp,
-                // TODO: figure out how to do this without cloning
-                &Rc::new(var_dict.clone()),
-                &Config::DEFAULT,
-            )? {
+            let val = match builder.val(inp, &var_dict, &Config::DEFAULT)? {
                 None => return Err(TreebuilderErr::new_not_a_val(inp.idx())),
                 Some(v) => v,
             };
-            entries.insert(key, val);
+            entries.push((key, val));
         }
 
         if consume_obj_cls(inp, opn_i)? {
-            return Ok(Some(ObjectNode::new(opn_i, inp.idx(), entries).into()));
+            return builder.obj(entries, opn_i, inp.idx());
         }
 
         consume_val_sep(inp)?;
@@ -68,13 +74,13 @@ pub fn object_consumer(
                 return Err(TreebuilderErr::new_trailing_sep(inp.idx() - 2));
             }
 
-            return Ok(Some(ObjectNode::new(opn_i, inp.idx(), entries).into()));
+            return builder.obj(entries, opn_i, inp.idx());
         }
     }
 }
 
 /// Returns the token if a object open delimiter was found.
-fn consume_obj_opn<'a>(inp: &'a mut Queue<Token>) -> bool {
+pub(super) fn consume_obj_opn<'a>(inp: &'a mut Queue<Token>) -> bool {
     let t = inp.peek().unwrap();
 
     if t.typ == TokenType::Delimiter && t.val == "{" {
@@ -86,7 +92,10 @@ fn consume_obj_opn<'a>(inp: &'a mut Queue<Token>) -> bool {
     false
 }
 
-fn consume_obj_cls<'a>(inp: &'a mut Queue<Token>, opn_i: usize) -> Result<bool, TreebuilderErr> {
+pub(super) fn consume_obj_cls<'a>(
+    inp: &'a mut Queue<Token>,
+    opn_i: usize,
+) -> Result<bool, TreebuilderErr> {
     let t = inp
         .peek()
         .ok_or(TreebuilderErr::new_unterminated_obj(opn_i))?;
@@ -100,7 +109,7 @@ fn consume_obj_cls<'a>(inp: &'a mut Queue<Token>, opn_i: usize) -> Result<bool,
     Ok(false)
 }
 
-fn consume_key<'a>(inp: &'a mut Queue<Token>) -> Result<String, TreebuilderErr> {
+pub(super) fn consume_key<'a>(inp: &'a mut Queue<Token>) -> Result<String, TreebuilderErr> {
     let t = inp.peek().unwrap();
 
     if t.typ == TokenType::StringLiteral {
@@ -110,7 +119,10 @@ fn consume_key<'a>(inp: &'a mut Queue<Token>) -> Result<String, TreebuilderErr>
     Err(TreebuilderErr::new_not_a_key(inp.idx()))
 }
 
-fn consume_assignment(inp: &mut Queue<Token>, key_i: usize) -> Result<(), TreebuilderErr> {
+pub(super) fn consume_assignment(
+    inp: &mut Queue<Token>,
+    key_i: usize,
+) -> Result<(), TreebuilderErr> {
     let i = inp.idx();
     let t = inp
         .next()
@@ -123,7 +135,7 @@ fn consume_assignment(inp: &mut Queue<Token>, key_i: usize) -> Result<(), Treebu
     Ok(())
 }
 
-fn consume_val_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
+pub(super) fn consume_val_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
     let t = inp.peek().unwrap();
 
     if t.typ != TokenType::Separator || t.val != "," {
@@ -138,12 +150,13 @@ fn consume_val_sep(inp: &mut Queue<Token>) -> Result<(), TreebuilderErr> {
 #[cfg(test)]
 mod tests {
     use crate::treebuilder::{
-        node::{ArrayNode, BoolNode, NumberNode, StringNode},
+        node::{ArrayNode, BoolNode, NumberNode, ObjectNode, Provenance, StringNode},
         testing::{
-            new_delimiter, new_equal_assignment_op, new_json_assignment_op, new_kwd, new_num,
-            new_sep, new_str,
+            new_delimiter, new_equal_assignment_op, new_ident, new_json_assignment_op, new_kwd,
+            new_num, new_sep, new_str,
         },
     };
+    use std::collections::HashMap;
 
     use super::*;
 
@@ -361,7 +374,7 @@ mod tests {
         let inp = &mut Queue::new(vec![
             new_delimiter("{"),
             new_kwd("let"),
-            new_kwd("foo"),
+            new_ident("foo"),
             new_equal_assignment_op(),
             new_str("foo"),
             new_sep(","),
@@ -388,18 +401,22 @@ mod tests {
         let inp = &mut Queue::new(vec![
             new_delimiter("{"),
             new_kwd("let"),
-            new_kwd("var"),
+            new_ident("var"),
             new_equal_assignment_op(),
             new_num("10"),
             new_sep(","),
             new_str("num"),
             new_json_assignment_op(),
-            new_kwd("var"),
+            new_ident("var"),
             new_delimiter("}"),
         ]);
 
         let mut exp_entries = HashMap::new();
-        exp_entries.insert("num".to_owned(), NumberNode::new(4, "10".to_owned()).into());
+        exp_entries.insert(
+            "num".to_owned(),
+            Node::Number(NumberNode::new(4, "10".to_owned()))
+                .with_provenance(Provenance::new("var", 8, 1, 5)),
+        );
 
         assert_eq!(
             object_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
@@ -415,19 +432,23 @@ mod tests {
         let inp = &mut Queue::new(vec![
             new_delimiter("{"),
             new_kwd("let"),
-            new_kwd("var"),
+            new_ident("var"),
             new_equal_assignment_op(),
             new_num("10"),
             new_sep(","),
             new_str("num"),
             new_json_assignment_op(),
-            new_kwd("var"),
+            new_ident("var"),
             new_sep(","),
             new_delimiter("}"),
         ]);
 
         let mut exp_entries = HashMap::new();
-        exp_entries.insert("num".to_owned(), NumberNode::new(4, "10".to_owned()).into());
+        exp_entries.insert(
+            "num".to_owned(),
+            Node::Number(NumberNode::new(4, "10".to_owned()))
+                .with_provenance(Provenance::new("var", 8, 1, 5)),
+        );
 
         assert_eq!(
             object_consumer(inp, &Rc::new(VarDict::new()), &config),
@@ -0,0 +1,732 @@
+// The errors carry their provenance unboxed, like the other errors of the crate.
+#![allow(clippy::result_large_err)]
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+use crate::tokenizer::Token;
+
+use super::{
+    access_error::{self, AccessErr, AccessErrTyp},
+    builtins::eq_val,
+    error::get_msg,
+    node::{
+        ArrayNode, BoolNode, KeyOrder, Node, NullNode, NumberNode, ObjectNode, PathSegment,
+        Provenance, StringNode,
+    },
+    pointer::{self, parse_idx, to_pointer, unescape, PointerErrTyp},
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum PatchErrTyp {
+    /// The patch is not an array, or an operation is not an object, lacks a
+    /// member or has a member of the wrong type.
+    Access(AccessErrTyp),
+    /// The `op` member is none of `add`, `remove`, `replace`, `move`, `copy`
+    /// and `test`.
+    UnknownOp { op: String },
+    /// The `path` or `from` pointer is invalid, or does not refer to an
+    /// existing location.
+    Pointer(PointerErrTyp),
+    /// The whole document can not be removed (or moved).
+    RemoveRoot,
+    /// A node can not be moved into one of its own children.
+    MoveIntoChild,
+    /// The value of a `test` operation differs from the value at the path.
+    TestFailed,
+}
+
+/// Returned by [`Node::apply_patch`]. The span refers to the patch, either to
+/// the failed operation or to its offending member.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PatchErr {
+    pub typ: PatchErrTyp,
+    /// The index of the failed operation. `None` if the patch is not an array.
+    pub op_idx: Option<usize>,
+    /// The `path` of the failed operation, if it could be read.
+    pub path: Option<String>,
+    pub from: usize,
+    pub to: usize,
+    /// Set if the node was produced by using a variable.
+    pub provenance: Option<Provenance>,
+}
+
+impl fmt::Display for PatchErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for PatchErr {}
+
+impl PatchErr {
+    fn new(typ: PatchErrTyp, op: &Op) -> PatchErr {
+        PatchErr {
+            typ,
+            op_idx: Some(op.idx),
+            path: op.path.map(str::to_owned),
+            from: op.node.from(),
+            to: op.node.to(),
+            provenance: op.node.provenance().cloned(),
+        }
+    }
+
+    fn new_access(err: AccessErr, op_idx: Option<usize>, path: Option<&str>) -> PatchErr {
+        PatchErr {
+            typ: PatchErrTyp::Access(err.typ),
+            op_idx,
+            path: path.map(str::to_owned),
+            from: err.from,
+            to: err.to,
+            provenance: err.provenance,
+        }
+    }
+
+    pub fn msg(&self, toks: &[Token], inp: &str) -> String {
+        let verbal_hint = match (&self.op_idx, &self.path) {
+            (None, _) => format!("the patch is invalid: {}", get_verbal_hint(&self.typ)),
+            (Some(i), None) => format!("operation {} failed: {}", i, get_verbal_hint(&self.typ)),
+            (Some(i), Some(path)) => format!(
+                "operation {} (at `{}`) failed: {}",
+                i,
+                path,
+                get_verbal_hint(&self.typ)
+            ),
+        };
+
+        get_msg(
+            &verbal_hint,
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
+        )
+    }
+}
+
+fn get_verbal_hint(typ: &PatchErrTyp) -> String {
+    match typ {
+        PatchErrTyp::Access(typ) => access_error::get_verbal_hint(typ),
+        PatchErrTyp::UnknownOp { op } => format!(
+            "expected `add`, `remove`, `replace`, `move`, `copy` or `test` but received `{}`",
+            op
+        ),
+        PatchErrTyp::Pointer(typ) => pointer::get_verbal_hint(typ),
+        PatchErrTyp::RemoveRoot => "the whole document can not be removed".to_string(),
+        PatchErrTyp::MoveIntoChild => "a node can not be moved into itself".to_string(),
+        PatchErrTyp::TestFailed => "the value differs from the expected one".to_string(),
+    }
+}
+
+/// An operation of a JSON Patch.
+struct Op<'a> {
+    idx: usize,
+    node: &'a Node,
+    path: Option<&'a str>,
+}
+
+impl<'a> Op<'a> {
+    fn get(&self, member: &str) -> Result<&'a Node, PatchErr> {
+        self.node
+            .get(member)
+            .map_err(|e| PatchErr::new_access(e, Some(self.idx), self.path))
+    }
+    fn get_str(&self, member: &str) -> Result<&'a str, PatchErr> {
+        self.get(member)?
+            .as_str()
+            .map_err(|e| PatchErr::new_access(e, Some(self.idx), self.path))
+    }
+}
+
+/// [JSON Patch (RFC 6902)](https://www.rfc-editor.org/rfc/rfc6902) and
+/// [JSON Merge Patch (RFC 7396)](https://www.rfc-editor.org/rfc/rfc7396).
+/// Values taken from a patch are inserted as generated nodes (see
+/// [`SYNTHETIC`](super::node::SYNTHETIC)), as their spans refer to the source
+/// of the patch.
+impl Node {
+    /// Applies the operations of the JSON Patch in order. If an operation
+    /// fails, the node is left unchanged.
+    pub fn apply_patch(&mut self, patch: &Node) -> Result<(), PatchErr> {
+        let ops = patch
+            .as_array()
+            .map_err(|e| PatchErr::new_access(e, None, None))?;
+
+        // Cloning is cheap, as the entries are only copied once they are
+        // modified.
+        let mut patched = self.clone();
+
+        for (idx, node) in ops.iter().enumerate() {
+            apply_op(&mut patched, node, idx)?;
+        }
+
+        *self = patched;
+
+        Ok(())
+    }
+    /// Applies the JSON Merge Patch: the entries of an object patch are merged
+    /// recursively, `null` removes an entry, and any other patch replaces the
+    /// node.
+    pub fn apply_merge_patch(&mut self, patch: &Node) {
+        let patch_obj = match patch {
+            Node::Object(o) => o,
+            _ => {
+                *self = to_synthetic(patch);
+                return;
+            }
+        };
+
+        if !matches!(self, Node::Object(_)) {
+            *self = Node::Object(ObjectNode::synthetic(HashMap::new()));
+        }
+
+        let obj = match self {
+            Node::Object(o) => o,
+            _ => unreachable!(),
+        };
+
+        for (key, val) in patch_obj.entries_ordered(KeyOrder::Source) {
+            match val {
+                Node::Null(_) => {
+                    obj.remove(key);
+                }
+                _ => {
+                    if !obj.entries.contains_key(key) {
+                        obj.insert(key.clone(), Node::Null(NullNode::synthetic()));
+                    }
+
+                    Rc::make_mut(&mut obj.entries)
+                        .get_mut(key)
+                        .unwrap()
+                        .apply_merge_patch(val);
+                }
+            }
+        }
+    }
+    /// Returns a JSON Patch that turns the node into the other one. Arrays are
+    /// compared by index.
+    pub fn diff_patch(&self, new: &Node) -> Node {
+        let mut ops = vec![];
+        push_ops(self, new, &mut vec![], &mut ops);
+
+        Node::Array(ArrayNode::synthetic(ops))
+    }
+    /// Returns a JSON Merge Patch that turns the node into the other one. Merge
+    /// patches can not set an entry to `null`, such entries are removed
+    /// instead.
+    pub fn diff_merge_patch(&self, new: &Node) -> Node {
+        match (self, new) {
+            (Node::Object(o), Node::Object(n)) => {
+                let mut entries = HashMap::new();
+
+                for (key, new_val) in n.entries.iter() {
+                    match o.entries.get(key) {
+                        Some(old_val) if eq_val(old_val, new_val) => {}
+                        Some(old_val) => {
+                            entries.insert(key.clone(), old_val.diff_merge_patch(new_val));
+                        }
+                        None => {
+                            entries.insert(key.clone(), to_synthetic(new_val));
+                        }
+                    }
+                }
+
+                for key in o.entries.keys().filter(|k| !n.entries.contains_key(*k)) {
+                    entries.insert(key.clone(), Node::Null(NullNode::synthetic()));
+                }
+
+                Node::Object(ObjectNode::synthetic(entries))
+            }
+            _ => to_synthetic(new),
+        }
+    }
+}
+
+fn apply_op(doc: &mut Node, node: &Node, idx: usize) -> Result<(), PatchErr> {
+    let mut op = Op {
+        idx,
+        node,
+        path: None,
+    };
+
+    let typ = op.get_str("op")?;
+    op.path = Some(op.get_str("path")?);
+    let path = op.path.unwrap();
+
+    match typ {
+        "add" => add(doc, path, to_synthetic(op.get("value")?), &op),
+        "remove" => remove(doc, path, &op).map(|_| ()),
+        "replace" => {
+            let val = to_synthetic(op.get("value")?);
+            *get_mut(doc, path, &op)? = val;
+
+            Ok(())
+        }
+        "move" => {
+            let from = op.get_str("from")?;
+
+            // Moving a node onto itself changes nothing, but the node still
+            // has to exist.
+            if from == path {
+                return get(doc, from, &op).map(|_| ());
+            }
+
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(PatchErr::new(PatchErrTyp::MoveIntoChild, &op));
+            }
+
+            let val = remove(doc, from, &op)?;
+            add(doc, path, val, &op)
+        }
+        "copy" => {
+            let val = get(doc, op.get_str("from")?, &op)?.clone();
+            add(doc, path, val, &op)
+        }
+        "test" => match eq_val(get(doc, path, &op)?, op.get("value")?) {
+            true => Ok(()),
+            false => Err(PatchErr::new(PatchErrTyp::TestFailed, &op)),
+        },
+        _ => Err(PatchErr::new(
+            PatchErrTyp::UnknownOp { op: typ.to_owned() },
+            &op,
+        )),
+    }
+}
+
+fn get<'a>(doc: &'a Node, pointer: &str, op: &Op) -> Result<&'a Node, PatchErr> {
+    doc.pointer(pointer)
+        .map_err(|e| PatchErr::new(PatchErrTyp::Pointer(e.typ), op))
+}
+
+fn get_mut<'a>(doc: &'a mut Node, pointer: &str, op: &Op) -> Result<&'a mut Node, PatchErr> {
+    doc.pointer_mut(pointer)
+        .map_err(|e| PatchErr::new(PatchErrTyp::Pointer(e.typ), op))
+}
+
+/// Splits the pointer into the pointer to the parent and the unescaped last
+/// segment. Returns `None` for the empty pointer, which refers to the root.
+fn split_last<'a>(pointer: &'a str, op: &Op) -> Result<Option<(&'a str, String)>, PatchErr> {
+    let invalid_syntax = || PatchErr::new(PatchErrTyp::Pointer(PointerErrTyp::InvalidSyntax), op);
+
+    if pointer.is_empty() {
+        return Ok(None);
+    }
+
+    if !pointer.starts_with('/') {
+        return Err(invalid_syntax());
+    }
+
+    let i = pointer.rfind('/').unwrap();
+    let last = unescape(&pointer[i + 1..]).ok_or_else(invalid_syntax)?;
+
+    Ok(Some((&pointer[..i], last)))
+}
+
+/// Adds the value to an object, or inserts it into an array (`-` appends it).
+/// The empty pointer replaces the whole document.
+fn add(doc: &mut Node, pointer: &str, val: Node, op: &Op) -> Result<(), PatchErr> {
+    let (parent, last) = match split_last(pointer, op)? {
+        Some(split) => split,
+        None => {
+            *doc = val;
+            return Ok(());
+        }
+    };
+
+    let err = |typ| Err(PatchErr::new(PatchErrTyp::Pointer(typ), op));
+
+    match get_mut(doc, parent, op)? {
+        Node::Object(o) => {
+            o.insert(last, val);
+
+            Ok(())
+        }
+        Node::Array(a) => {
+            let len = a.entries.len();
+
+            match parse_idx(&last, len) {
+                None => err(PointerErrTyp::InvalidIndex),
+                Some(idx) if idx > len => err(PointerErrTyp::IndexOutOfRange { idx, len }),
+                Some(idx) => {
+                    Rc::make_mut(&mut a.entries).insert(idx, val);
+
+                    Ok(())
+                }
+            }
+        }
+        node => err(PointerErrTyp::NotAContainer { typ: node.typ() }),
+    }
+}
+
+fn remove(doc: &mut Node, pointer: &str, op: &Op) -> Result<Node, PatchErr> {
+    let (parent, last) =
+        split_last(pointer, op)?.ok_or_else(|| PatchErr::new(PatchErrTyp::RemoveRoot, op))?;
+
+    let err = |typ| Err(PatchErr::new(PatchErrTyp::Pointer(typ), op));
+
+    match get_mut(doc, parent, op)? {
+        Node::Object(o) if o.entries.contains_key(&last) => Ok(o.remove(&last).unwrap()),
+        Node::Object(_) => err(PointerErrTyp::MissingKey),
+        Node::Array(a) => {
+            let len = a.entries.len();
+
+            match parse_idx(&last, len) {
+                None => err(PointerErrTyp::InvalidIndex),
+                Some(idx) if idx >= len => err(PointerErrTyp::IndexOutOfRange { idx, len }),
+                Some(idx) => Ok(Rc::make_mut(&mut a.entries).remove(idx)),
+            }
+        }
+        node => err(PointerErrTyp::NotAContainer { typ: node.typ() }),
+    }
+}
+
+/// Pushes the operations that turn `old` into `new`. Trailing entries of arrays
+/// are removed from the back, so the indices of the following operations stay
+/// valid.
+fn push_ops(old: &Node, new: &Node, path: &mut Vec<PathSegment>, ops: &mut Vec<Node>) {
+    match (old, new) {
+        (Node::Object(o), Node::Object(n)) => {
+            let mut keys = o
+                .entries
+                .keys()
+                .chain(n.entries.keys())
+                .collect::<Vec<&String>>();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                path.push(PathSegment::Key(key.clone()));
+
+                match (o.entries.get(key), n.entries.get(key)) {
+                    (Some(o), Some(n)) => push_ops(o, n, path, ops),
+                    (Some(_), None) => ops.push(new_op("remove", path, None)),
+                    (None, Some(n)) => ops.push(new_op("add", path, Some(n))),
+                    (None, None) => unreachable!(),
+                }
+
+                path.pop();
+            }
+        }
+        (Node::Array(o), Node::Array(n)) => {
+            for (i, (o, n)) in o.entries.iter().zip(n.entries.iter()).enumerate() {
+                path.push(PathSegment::Index(i));
+                push_ops(o, n, path, ops);
+                path.pop();
+            }
+
+            for i in (n.entries.len()..o.entries.len()).rev() {
+                path.push(PathSegment::Index(i));
+                ops.push(new_op("remove", path, None));
+                path.pop();
+            }
+
+            for (i, n) in n.entries.iter().enumerate().skip(o.entries.len()) {
+                path.push(PathSegment::Index(i));
+                ops.push(new_op("add", path, Some(n)));
+                path.pop();
+            }
+        }
+        _ if eq_val(old, new) => {}
+        _ => ops.push(new_op("replace", path, Some(new))),
+    }
+}
+
+fn new_op(op: &str, path: &[PathSegment], val: Option<&Node>) -> Node {
+    let mut entries = HashMap::from([
+        ("op".to_owned(), StringNode::synthetic(op.to_owned()).into()),
+        (
+            "path".to_owned(),
+            StringNode::synthetic(to_pointer(path)).into(),
+        ),
+    ]);
+
+    if let Some(val) = val {
+        entries.insert("value".to_owned(), to_synthetic(val));
+    }
+
+    Node::Object(ObjectNode::synthetic(entries))
+}
+
+/// Copies the node, and marks the copy and all of its descendants as
+/// generated.
+fn to_synthetic(node: &Node) -> Node {
+    match node {
+        Node::Array(a) => Node::Array(ArrayNode::synthetic(
+            a.entries.iter().map(to_synthetic).collect(),
+        )),
+        Node::Bool(b) => Node::Bool(BoolNode::synthetic(b.val)),
+        Node::Null(_) => Node::Null(NullNode::synthetic()),
+        Node::Number(n) => Node::Number(NumberNode::synthetic(n.val.clone())),
+        Node::Object(o) => Node::Object(ObjectNode::synthetic(
+            o.entries
+                .iter()
+                .map(|(key, val)| (key.clone(), to_synthetic(val)))
+                .collect(),
+        )),
+        Node::String(s) => Node::String(StringNode::synthetic(s.val.clone())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tokenizer::tokenize,
+        treebuilder::{build, testing::parse, Config},
+    };
+
+    use super::*;
+
+    /// Applies the patch, and compares the result to `expected` by value.
+    fn assert_patched(doc: &str, patch: &str, expected: &str) {
+        let mut doc = parse(doc);
+        doc.apply_patch(&parse(patch)).unwrap();
+
+        assert!(eq_val(&doc, &parse(expected)), "{:?}", doc);
+    }
+
+    #[test]
+    fn add() {
+        assert_patched(
+            r#"{"foo": ["bar", "baz"]}"#,
+            r#"[
+                {"op": "add", "path": "/foo/1", "value": "qux"},
+                {"op": "add", "path": "/foo/-", "value": "end"},
+                {"op": "add", "path": "/child", "value": {"a": 1}}
+            ]"#,
+            r#"{"foo": ["bar", "qux", "baz", "end"], "child": {"a": 1}}"#,
+        );
+    }
+
+    #[test]
+    fn remove_and_replace() {
+        assert_patched(
+            r#"{"baz": "qux", "foo": ["a", "b", "c"]}"#,
+            r#"[
+                {"op": "remove", "path": "/foo/1"},
+                {"op": "replace", "path": "/baz", "value": "boo"}
+            ]"#,
+            r#"{"baz": "boo", "foo": ["a", "c"]}"#,
+        );
+    }
+
+    #[test]
+    fn move_and_copy() {
+        assert_patched(
+            r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+            r#"[
+                {"op": "move", "from": "/foo/waldo", "path": "/qux/thud"},
+                {"op": "copy", "from": "/qux", "path": "/copy"}
+            ]"#,
+            r#"{
+                "foo": {"bar": "baz"},
+                "qux": {"corge": "grault", "thud": "fred"},
+                "copy": {"corge": "grault", "thud": "fred"}
+            }"#,
+        );
+    }
+
+    #[test]
+    fn move_onto_itself() {
+        assert_patched(
+            r#"{"a": [1]}"#,
+            r#"[{"op": "move", "from": "/a/0", "path": "/a/0"}]"#,
+            r#"{"a": [1]}"#,
+        );
+    }
+
+    #[test]
+    fn replace_root() {
+        assert_patched(
+            r#"{"a": 1}"#,
+            r#"[{"op": "replace", "path": "", "value": [1]}]"#,
+            "[1]",
+        );
+    }
+
+    #[test]
+    fn test_op() {
+        assert_patched(
+            r#"{"a": [1, {"b": 2.0}]}"#,
+            r#"[{"op": "test", "path": "/a", "value": [1, {"b": 2}]}]"#,
+            r#"{"a": [1, {"b": 2}]}"#,
+        );
+    }
+
+    #[test]
+    fn failed_patch_is_atomic() {
+        let mut doc = parse(r#"{"a": 1}"#);
+        let patch = parse(
+            r#"[
+                {"op": "add", "path": "/b", "value": 2},
+                {"op": "test", "path": "/a", "value": 2}
+            ]"#,
+        );
+
+        let err = doc.apply_patch(&patch).unwrap_err();
+
+        assert_eq!(err.typ, PatchErrTyp::TestFailed);
+        assert_eq!((err.op_idx, err.path), (Some(1), Some("/a".to_owned())));
+        assert_eq!((err.from, err.to), (15, 28));
+        assert!(eq_val(&doc, &parse(r#"{"a": 1}"#)));
+    }
+
+    #[test]
+    fn errs() {
+        let cases = [
+            (
+                r#"{"op": "remove", "path": "/b"}"#,
+                PatchErrTyp::Pointer(PointerErrTyp::MissingKey),
+            ),
+            (
+                r#"{"op": "add", "path": "/l/3", "value": 1}"#,
+                PatchErrTyp::Pointer(PointerErrTyp::IndexOutOfRange { idx: 3, len: 1 }),
+            ),
+            (
+                r#"{"op": "remove", "path": "/l/-"}"#,
+                PatchErrTyp::Pointer(PointerErrTyp::IndexOutOfRange { idx: 1, len: 1 }),
+            ),
+            (r#"{"op": "remove", "path": ""}"#, PatchErrTyp::RemoveRoot),
+            (
+                r#"{"op": "move", "from": "/l", "path": "/l/0"}"#,
+                PatchErrTyp::MoveIntoChild,
+            ),
+            (
+                r#"{"op": "move", "from": "/b", "path": "/b"}"#,
+                PatchErrTyp::Pointer(PointerErrTyp::MissingKey),
+            ),
+            (
+                r#"{"op": "move", "from": "/l/1", "path": "/l/1"}"#,
+                PatchErrTyp::Pointer(PointerErrTyp::IndexOutOfRange { idx: 1, len: 1 }),
+            ),
+            (
+                r#"{"op": "swap", "path": "/a"}"#,
+                PatchErrTyp::UnknownOp {
+                    op: "swap".to_owned(),
+                },
+            ),
+            (
+                r#"{"op": "add", "path": "/a"}"#,
+                PatchErrTyp::Access(AccessErrTyp::MissingKey {
+                    key: "value".to_owned(),
+                }),
+            ),
+        ];
+
+        for (op, typ) in cases {
+            let mut doc = parse(r#"{"a": 1, "l": [0]}"#);
+            let err = doc.apply_patch(&parse(&format!("[{}]", op))).unwrap_err();
+
+            assert_eq!(err.typ, typ, "{}", op);
+            assert_eq!(err.op_idx, Some(0));
+        }
+    }
+
+    #[test]
+    fn msg() {
+        let inp = r#"[{"op": "replace", "path": "/b", "value": 1}]"#;
+        let toks = tokenize(inp, &Config::DEFAULT).unwrap();
+        let patch = build(&toks, &Config::DEFAULT).unwrap().unwrap();
+
+        assert_eq!(
+            parse(r#"{"a": 1}"#).apply_patch(&patch).unwrap_err().msg(&toks, inp),
+            "operation 0 (at `/b`) failed: the object has no entry with the key, line: 1, char: 2\n\n[{\"op\": \"replace\", \"path\": \"/b\", \"value\": 1}]\n ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn patched_values_are_synthetic() {
+        let mut doc = parse(r#"{"a": 1}"#);
+        doc.apply_patch(&parse(r#"[{"op": "add", "path": "/b", "value": [2]}]"#))
+            .unwrap();
+
+        assert!(doc["b"].is_synthetic() && doc["b"][0].is_synthetic());
+        assert!(!doc["a"].is_synthetic());
+    }
+
+    #[test]
+    fn merge_patch() {
+        let mut doc = parse(
+            r#"{"title": "Goodbye!", "author": {"givenName": "John", "familyName": "Doe"}, "tags": ["example", "sample"], "content": "This will be unchanged"}"#,
+        );
+
+        doc.apply_merge_patch(&parse(
+            r#"{"title": "Hello!", "phoneNumber": "+01-123-456-7890", "author": {"familyName": null}, "tags": ["example"]}"#,
+        ));
+
+        assert!(eq_val(
+            &doc,
+            &parse(
+                r#"{"title": "Hello!", "author": {"givenName": "John"}, "tags": ["example"], "content": "This will be unchanged", "phoneNumber": "+01-123-456-7890"}"#
+            )
+        ));
+    }
+
+    #[test]
+    fn merge_patch_of_non_objects() {
+        let mut doc = parse("[1]");
+        doc.apply_merge_patch(&parse(r#"{"a": {"b": null, "c": 1}}"#));
+
+        assert!(eq_val(&doc, &parse(r#"{"a": {"c": 1}}"#)));
+    }
+
+    const OLD: &str = r#"{"a": [1, 2, 3, 4], "b": {"c": true, "d": null}, "e": "x"}"#;
+    const NEW: &str = r#"{"a": [1, 5], "b": {"c": false, "f": [1]}, "g": 1}"#;
+
+    #[test]
+    fn diff_patch() {
+        let (old, new) = (parse(OLD), parse(NEW));
+        let patch = old.diff_patch(&new);
+
+        let ops = patch
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|op| {
+                format!(
+                    "{} {}",
+                    op["op"].as_str().unwrap(),
+                    op["path"].as_str().unwrap()
+                )
+            })
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            ops,
+            [
+                "replace /a/1",
+                "remove /a/3",
+                "remove /a/2",
+                "replace /b/c",
+                "remove /b/d",
+                "add /b/f",
+                "remove /e",
+                "add /g"
+            ]
+        );
+
+        let mut patched = old.clone();
+        patched.apply_patch(&patch).unwrap();
+
+        assert!(eq_val(&patched, &new));
+    }
+
+    #[test]
+    fn diff_merge_patch() {
+        let (old, new) = (parse(OLD), parse(NEW));
+        let patch = old.diff_merge_patch(&new);
+
+        assert!(eq_val(
+            &patch,
+            &parse(r#"{"a": [1, 5], "b": {"c": false, "d": null, "f": [1]}, "e": null, "g": 1}"#)
+        ));
+
+        let mut patched = old.clone();
+        patched.apply_merge_patch(&patch);
+
+        assert!(eq_val(&patched, &new));
+    }
+}
@@ -0,0 +1,439 @@
+// The errors carry their provenance unboxed, like the other errors of the crate.
+#![allow(clippy::result_large_err)]
+
+use std::{
+    error::Error,
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+use crate::tokenizer::Token;
+
+use super::{
+    error::get_msg,
+    node::{Node, NodeTyp, PathSegment, Provenance},
+};
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum PointerErrTyp {
+    /// The segment is not a valid array index (`01`, `foo`).
+    InvalidIndex,
+    /// The pointer is neither empty nor starts with a `/`, or contains a `~`
+    /// that is not followed by a `0` or `1`.
+    InvalidSyntax,
+    /// The array has no entry at the index. `-` (the entry after the last one)
+    /// is reported with the index `len`.
+    IndexOutOfRange { idx: usize, len: usize },
+    /// The object has no entry with the key.
+    MissingKey,
+    /// The segment is applied to a node that is neither an array nor an
+    /// object.
+    NotAContainer { typ: NodeTyp },
+}
+
+/// Returned by [`Node::pointer`] and [`Node::pointer_mut`]. The span refers to
+/// the node the failed segment was applied to.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PointerErr {
+    pub typ: PointerErrTyp,
+    /// The unescaped segment that failed.
+    pub segment: String,
+    /// The index of the failed segment, the first segment has the index `0`.
+    pub segment_idx: usize,
+    pub from: usize,
+    pub to: usize,
+    /// Set if the node was produced by using a variable.
+    pub provenance: Option<Provenance>,
+}
+
+impl fmt::Display for PointerErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for PointerErr {}
+
+impl PointerErr {
+    fn new(typ: PointerErrTyp, segment: &str, segment_idx: usize, node: &Node) -> PointerErr {
+        PointerErr {
+            typ,
+            segment: segment.to_owned(),
+            segment_idx,
+            from: node.from(),
+            to: node.to(),
+            provenance: node.provenance().cloned(),
+        }
+    }
+
+    pub fn msg(&self, toks: &[Token], inp: &str) -> String {
+        let verbal_hint = format!(
+            "segment {} (`{}`) of the pointer failed: {}",
+            self.segment_idx,
+            self.segment,
+            get_verbal_hint(&self.typ)
+        );
+
+        get_msg(
+            &verbal_hint,
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
+        )
+    }
+}
+
+pub(super) fn get_verbal_hint(typ: &PointerErrTyp) -> String {
+    match typ {
+        PointerErrTyp::InvalidIndex => "expected an array index".to_string(),
+        PointerErrTyp::InvalidSyntax => {
+            "expected the pointer to start with `/`, and `~` to be followed by `0` or `1`"
+                .to_string()
+        }
+        PointerErrTyp::IndexOutOfRange { idx, len } => format!(
+            "index {} is out of range for an array with {} entries",
+            idx, len
+        ),
+        PointerErrTyp::MissingKey => "the object has no entry with the key".to_string(),
+        PointerErrTyp::NotAContainer { typ } => {
+            format!("expected an array or object but received a `{}`", typ)
+        }
+    }
+}
+
+/// Lookup by [JSON Pointer (RFC 6901)](https://www.rfc-editor.org/rfc/rfc6901).
+impl Node {
+    /// Returns the node the pointer (`/servers/0/port`) refers to. The empty
+    /// pointer refers to the node itself.
+    pub fn pointer(&self, pointer: &str) -> Result<&Node, PointerErr> {
+        let mut node = self;
+
+        for (i, segment) in parse_pointer(pointer, self)?.iter().enumerate() {
+            node = get_child(node, segment, i)?;
+        }
+
+        Ok(node)
+    }
+    /// Same as [`Node::pointer`], but returns a mutable reference. Entries that
+    /// are shared with other nodes are copied on the way down (see
+    /// [`ArrayNode::entries`](super::node::ArrayNode::entries)).
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut Node, PointerErr> {
+        let mut node = self;
+
+        for (i, segment) in parse_pointer(pointer, node)?.iter().enumerate() {
+            node = get_child_mut(node, segment, i)?;
+        }
+
+        Ok(node)
+    }
+}
+
+/// Splits the pointer into its unescaped segments.
+fn parse_pointer(pointer: &str, root: &Node) -> Result<Vec<String>, PointerErr> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if !pointer.starts_with('/') {
+        return Err(PointerErr::new(
+            PointerErrTyp::InvalidSyntax,
+            pointer,
+            0,
+            root,
+        ));
+    }
+
+    pointer[1..]
+        .split('/')
+        .enumerate()
+        .map(|(i, segment)| {
+            unescape(segment)
+                .ok_or_else(|| PointerErr::new(PointerErrTyp::InvalidSyntax, segment, i, root))
+        })
+        .collect()
+}
+
+/// Formats the path as a JSON Pointer (`/servers/0/port`).
+pub(super) fn to_pointer(path: &[PathSegment]) -> String {
+    path.iter()
+        .map(|segment| match segment {
+            PathSegment::Index(i) => format!("/{}", i),
+            PathSegment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+        })
+        .collect()
+}
+
+/// Replaces `~1` with `/` and `~0` with `~`. Returns `None` if a `~` is not
+/// part of one of these escape sequences.
+pub(super) fn unescape(segment: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(segment.len());
+    let mut chars = segment.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next()? {
+                '0' => unescaped.push('~'),
+                '1' => unescaped.push('/'),
+                _ => return None,
+            },
+            c => unescaped.push(c),
+        }
+    }
+
+    Some(unescaped)
+}
+
+fn get_child<'a>(node: &'a Node, segment: &str, i: usize) -> Result<&'a Node, PointerErr> {
+    match node {
+        Node::Object(o) => o
+            .entries
+            .get(segment)
+            .ok_or_else(|| PointerErr::new(PointerErrTyp::MissingKey, segment, i, node)),
+        Node::Array(a) => {
+            let len = a.entries.len();
+            let idx = parse_idx(segment, len)
+                .ok_or_else(|| PointerErr::new(PointerErrTyp::InvalidIndex, segment, i, node))?;
+
+            a.entries.get(idx).ok_or_else(|| {
+                PointerErr::new(
+                    PointerErrTyp::IndexOutOfRange { idx, len },
+                    segment,
+                    i,
+                    node,
+                )
+            })
+        }
+        _ => Err(PointerErr::new(
+            PointerErrTyp::NotAContainer { typ: node.typ() },
+            segment,
+            i,
+            node,
+        )),
+    }
+}
+
+fn get_child_mut<'a>(
+    node: &'a mut Node,
+    segment: &str,
+    i: usize,
+) -> Result<&'a mut Node, PointerErr> {
+    // Validate first, so shared entries are not copied for a failing lookup.
+    get_child(node, segment, i)?;
+
+    let child = match node {
+        Node::Object(o) => Rc::make_mut(&mut o.entries).get_mut(segment),
+        Node::Array(a) => {
+            let idx = parse_idx(segment, a.entries.len()).unwrap();
+
+            Rc::make_mut(&mut a.entries).get_mut(idx)
+        }
+        _ => None,
+    };
+
+    Ok(child.unwrap())
+}
+
+/// Parses an array index (`0`, or a number without leading zeros). `-` refers
+/// to the entry after the last one, which is `len`.
+pub(super) fn parse_idx(segment: &str, len: usize) -> Option<usize> {
+    if segment == "-" {
+        return Some(len);
+    }
+
+    let is_idx = !segment.is_empty()
+        && segment.chars().all(|c| c.is_ascii_digit())
+        && (segment == "0" || !segment.starts_with('0'));
+
+    match is_idx {
+        true => segment.parse().ok(),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::treebuilder::node::{ArrayNode, NumberNode, ObjectNode, StringNode};
+
+    use super::*;
+
+    fn num(i: usize, val: &str) -> Node {
+        NumberNode::new(i, val.to_owned()).into()
+    }
+
+    /// `{"servers": [{"port": 80}], "a/b": 1, "m~n": 2, "": 3}`
+    fn config() -> Node {
+        let server: Node =
+            ObjectNode::new(4, 9, HashMap::from([("port".to_owned(), num(7, "80"))])).into();
+
+        ObjectNode::new(
+            0,
+            23,
+            HashMap::from([
+                (
+                    "servers".to_owned(),
+                    ArrayNode::new(3, 10, vec![server]).into(),
+                ),
+                ("a/b".to_owned(), num(13, "1")),
+                ("m~n".to_owned(), num(17, "2")),
+                ("".to_owned(), num(21, "3")),
+            ]),
+        )
+        .into()
+    }
+
+    fn err(
+        typ: PointerErrTyp,
+        segment: &str,
+        segment_idx: usize,
+        from: usize,
+        to: usize,
+    ) -> PointerErr {
+        PointerErr {
+            typ,
+            segment: segment.to_owned(),
+            segment_idx,
+            from,
+            to,
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn root() {
+        let config = config();
+
+        assert_eq!(config.pointer(""), Ok(&config));
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(config().pointer("/servers/0/port"), Ok(&num(7, "80")));
+    }
+
+    #[test]
+    fn escaped() {
+        let config = config();
+
+        assert_eq!(config.pointer("/a~1b"), Ok(&num(13, "1")));
+        assert_eq!(config.pointer("/m~0n"), Ok(&num(17, "2")));
+        assert_eq!(config.pointer("/"), Ok(&num(21, "3")));
+    }
+
+    #[test]
+    fn unescapes_in_order() {
+        assert_eq!(unescape("~01"), Some("~1".to_owned()));
+    }
+
+    #[test]
+    fn invalid_syntax() {
+        assert_eq!(
+            config().pointer("servers"),
+            Err(err(PointerErrTyp::InvalidSyntax, "servers", 0, 0, 23))
+        );
+        assert_eq!(
+            config().pointer("/servers/~2"),
+            Err(err(PointerErrTyp::InvalidSyntax, "~2", 1, 0, 23))
+        );
+    }
+
+    #[test]
+    fn missing_key() {
+        assert_eq!(
+            config().pointer("/servers/0/host"),
+            Err(err(PointerErrTyp::MissingKey, "host", 2, 4, 9))
+        );
+    }
+
+    #[test]
+    fn index_out_of_range() {
+        assert_eq!(
+            config().pointer("/servers/1"),
+            Err(err(
+                PointerErrTyp::IndexOutOfRange { idx: 1, len: 1 },
+                "1",
+                1,
+                3,
+                10
+            ))
+        );
+        assert_eq!(
+            config().pointer("/servers/-"),
+            Err(err(
+                PointerErrTyp::IndexOutOfRange { idx: 1, len: 1 },
+                "-",
+                1,
+                3,
+                10
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_index() {
+        for idx in ["01", "+1", "port", ""] {
+            assert_eq!(
+                config().pointer(&format!("/servers/{}", idx)),
+                Err(err(PointerErrTyp::InvalidIndex, idx, 1, 3, 10))
+            );
+        }
+    }
+
+    #[test]
+    fn not_a_container() {
+        assert_eq!(
+            config().pointer("/servers/0/port/0"),
+            Err(err(
+                PointerErrTyp::NotAContainer {
+                    typ: NodeTyp::Number
+                },
+                "0",
+                3,
+                7,
+                8
+            ))
+        );
+    }
+
+    #[test]
+    fn mutation_copies_shared_entries() {
+        let original = config();
+        let mut modified = original.clone();
+
+        *modified.pointer_mut("/servers/0/port").unwrap() =
+            StringNode::new(7, "http".to_owned()).into();
+
+        assert_eq!(original.pointer("/servers/0/port"), Ok(&num(7, "80")));
+        assert_eq!(
+            modified.pointer("/servers/0/port"),
+            Ok(&StringNode::new(7, "http".to_owned()).into())
+        );
+    }
+
+    #[test]
+    fn failed_mutation() {
+        assert_eq!(
+            config().pointer_mut("/servers/0/host"),
+            Err(err(PointerErrTyp::MissingKey, "host", 2, 4, 9))
+        );
+    }
+
+    #[test]
+    fn msg() {
+        let inp = "[1]";
+        let toks = [
+            Token::new_delimiter("[", 0, 1),
+            Token::new_num("1", 1, 2),
+            Token::new_delimiter("]", 2, 3),
+        ];
+        let arr: Node = ArrayNode::new(0, 3, vec![num(1, "1")]).into();
+
+        assert_eq!(
+            arr.pointer("/2").unwrap_err().msg(&toks, inp),
+            "segment 0 (`2`) of the pointer failed: index 2 is out of range for an array with 1 entries, line: 1, char: 1\n\n[1]\n^^^\n"
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use std::fmt;
+
+use super::{
+    builtins::eq_val,
+    node::{Node, PathSegment},
+    pointer::to_pointer,
+};
+
+use parser::{CmpOp, Expr, Operand, Path, Segment, Selector};
+
+pub mod error;
+
+mod parser;
+
+pub use error::QueryErr;
+
+/// A node matched by [`Node::query`].
+#[derive(PartialEq, Debug, Clone)]
+pub struct QueryMatch<'a> {
+    pub node: &'a Node,
+    /// The concrete path from the queried node to the matched one.
+    pub path: Vec<PathSegment>,
+    /// Index of the first token of the matched node. **Inclusive.**
+    pub from: usize,
+    /// Index of the last token of the matched node. **NON inclusive.**
+    pub to: usize,
+}
+
+impl<'a> QueryMatch<'a> {
+    fn new(node: &'a Node, path: Vec<PathSegment>) -> QueryMatch<'a> {
+        QueryMatch {
+            from: node.from(),
+            to: node.to(),
+            node,
+            path,
+        }
+    }
+
+    /// Returns the path as a JSON Pointer (`/servers/0/port`), which can be
+    /// passed to [`Node::pointer`].
+    pub fn pointer(&self) -> String {
+        to_pointer(&self.path)
+    }
+}
+
+/// Prints the path in the normalized JSONPath form (`$['servers'][0]['port']`).
+impl fmt::Display for QueryMatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "$")?;
+
+        for segment in &self.path {
+            match segment {
+                PathSegment::Index(i) => write!(f, "[{}]", i)?,
+                PathSegment::Key(key) => {
+                    write!(f, "['{}']", key.replace('\\', "\\\\").replace('\'', "\\'"))?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Node {
+    /// Evaluates the JSONPath query (`$..servers[?(@.port > 1024)].name`) and
+    /// returns the matched nodes in document order. The entries of objects are
+    /// visited in the order of their keys.
+    ///
+    /// Supported are names (`.a`, `['a']`), wildcards (`*`), indices (`[0]`,
+    /// `[-1]`), slices (`[start:end:step]`), unions (`['a', 0]`), descendants
+    /// (`..a`) and filters (`[?(<expr>)]`). Filters support paths relative to
+    /// the current (`@`) or root (`$`) node, literals, `==`, `!=`, `<`, `<=`,
+    /// `>`, `>=`, `&&`, `||`, `!` and parentheses. A path on its own tests if
+    /// it matches anything.
+    pub fn query(&self, query: &str) -> Result<Vec<QueryMatch<'_>>, QueryErr> {
+        let path = parser::parse(query)?;
+
+        Ok(eval_path(
+            self,
+            QueryMatch::new(self, Vec::new()),
+            &path.segments,
+        ))
+    }
+}
+
+fn eval_path<'a>(
+    root: &'a Node,
+    start: QueryMatch<'a>,
+    segments: &[Segment],
+) -> Vec<QueryMatch<'a>> {
+    let mut matches = vec![start];
+
+    for segment in segments {
+        let mut next = Vec::new();
+
+        for m in matches {
+            match segment {
+                Segment::Child(selectors) => select(root, &m, selectors, &mut next),
+                Segment::Descendant(selectors) => {
+                    for descendant in get_descendants(m) {
+                        select(root, &descendant, selectors, &mut next);
+                    }
+                }
+            }
+        }
+
+        matches = next;
+    }
+
+    matches
+}
+
+/// Returns the node and all of its descendants, parents before their children.
+fn get_descendants(m: QueryMatch<'_>) -> Vec<QueryMatch<'_>> {
+    let mut descendants = Vec::new();
+    let mut stack = vec![m];
+
+    while let Some(m) = stack.pop() {
+        let mut children = get_children(&m);
+
+        children.reverse();
+        stack.append(&mut children);
+        descendants.push(m);
+    }
+
+    descendants
+}
+
+fn get_children<'a>(m: &QueryMatch<'a>) -> Vec<QueryMatch<'a>> {
+    match m.node {
+        Node::Array(a) => a
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| child(m, e, PathSegment::Index(i)))
+            .collect(),
+        Node::Object(o) => {
+            let mut keys = o.entries.keys().collect::<Vec<&String>>();
+            keys.sort();
+
+            keys.into_iter()
+                .map(|key| child(m, &o.entries[key], PathSegment::Key(key.clone())))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn child<'a>(parent: &QueryMatch<'a>, node: &'a Node, segment: PathSegment) -> QueryMatch<'a> {
+    let mut path = parent.path.clone();
+    path.push(segment);
+
+    QueryMatch::new(node, path)
+}
+
+fn select<'a>(
+    root: &'a Node,
+    m: &QueryMatch<'a>,
+    selectors: &[Selector],
+    out: &mut Vec<QueryMatch<'a>>,
+) {
+    for selector in selectors {
+        match (selector, m.node) {
+            (Selector::Name(name), Node::Object(o)) => {
+                if let Some(e) = o.entries.get(name) {
+                    out.push(child(m, e, PathSegment::Key(name.clone())));
+                }
+            }
+            (Selector::Wildcard, _) => out.append(&mut get_children(m)),
+            (Selector::Index(i), Node::Array(a)) => {
+                let len = a.entries.len() as i64;
+                let i = if *i < 0 { len + i } else { *i };
+
+                if (0..len).contains(&i) {
+                    let i = i as usize;
+                    out.push(child(m, &a.entries[i], PathSegment::Index(i)));
+                }
+            }
+            (Selector::Slice { start, end, step }, Node::Array(a)) => {
+                for i in get_slice_indices(a.entries.len() as i64, *start, *end, *step) {
+                    out.push(child(m, &a.entries[i], PathSegment::Index(i)));
+                }
+            }
+            (Selector::Filter(expr), _) => out.extend(
+                get_children(m)
+                    .into_iter()
+                    .filter(|c| eval_expr(root, c.node, expr)),
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the indices of a slice, following the semantics of Python slices:
+/// negative bounds count from the end, and a negative step walks backwards.
+fn get_slice_indices(
+    len: i64,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+) -> Vec<usize> {
+    let step = step.unwrap_or(1);
+
+    if step == 0 {
+        return Vec::new();
+    }
+
+    let normalize = |i: i64| if i < 0 { len + i } else { i };
+    let mut indices = Vec::new();
+
+    if step > 0 {
+        let start = start.map_or(0, normalize).clamp(0, len);
+        let end = end.map_or(len, normalize).clamp(0, len);
+        let mut i = start;
+
+        while i < end {
+            indices.push(i as usize);
+
+            // The slice ends if the next index can not be represented.
+            i = match i.checked_add(step) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    } else {
+        let start = start.map_or(len - 1, normalize).clamp(-1, len - 1);
+        let end = end.map_or(-1, normalize).clamp(-1, len - 1);
+        let mut i = start;
+
+        while i > end {
+            indices.push(i as usize);
+
+            // The slice ends if the next index can not be represented.
+            i = match i.checked_add(step) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+
+    indices
+}
+
+fn eval_expr(root: &Node, current: &Node, expr: &Expr) -> bool {
+    match expr {
+        Expr::And(a, b) => eval_expr(root, current, a) && eval_expr(root, current, b),
+        Expr::Cmp(a, op, b) => compare(
+            eval_operand(root, current, a),
+            *op,
+            eval_operand(root, current, b),
+        ),
+        Expr::Exists(path) => !eval_filter_path(root, current, path).is_empty(),
+        Expr::Not(e) => !eval_expr(root, current, e),
+        Expr::Or(a, b) => eval_expr(root, current, a) || eval_expr(root, current, b),
+    }
+}
+
+fn eval_filter_path<'a>(root: &'a Node, current: &'a Node, path: &Path) -> Vec<QueryMatch<'a>> {
+    let start = if path.is_relative { current } else { root };
+
+    eval_path(root, QueryMatch::new(start, Vec::new()), &path.segments)
+}
+
+/// Returns the value of the operand, or `None` if a path does not match
+/// exactly one node.
+fn eval_operand<'a>(root: &'a Node, current: &'a Node, operand: &'a Operand) -> Option<&'a Node> {
+    match operand {
+        Operand::Lit(n) => Some(n),
+        Operand::Path(path) => match eval_filter_path(root, current, path).as_slice() {
+            [m] => Some(m.node),
+            _ => None,
+        },
+    }
+}
+
+/// Compares two operands. Two missing values are equal, and ordering only
+/// applies to two numbers or two strings.
+fn compare(a: Option<&Node>, op: CmpOp, b: Option<&Node>) -> bool {
+    let is_eq = match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => eq_val(a, b),
+        _ => false,
+    };
+
+    let is_lt = |a: Option<&Node>, b: Option<&Node>| match (a, b) {
+        (Some(Node::Number(a)), Some(Node::Number(b))) => {
+            match (a.val.parse::<f64>(), b.val.parse::<f64>()) {
+                (Ok(a), Ok(b)) => a < b,
+                _ => false,
+            }
+        }
+        (Some(Node::String(a)), Some(Node::String(b))) => a.val < b.val,
+        _ => false,
+    };
+
+    match op {
+        CmpOp::Eq => is_eq,
+        CmpOp::Ne => !is_eq,
+        CmpOp::Lt => is_lt(a, b),
+        CmpOp::Le => is_lt(a, b) || is_eq,
+        CmpOp::Gt => is_lt(b, a),
+        CmpOp::Ge => is_lt(b, a) || is_eq,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::treebuilder::testing::parse;
+
+    use super::*;
+
+    /// Returns the normalized paths of the matches.
+    fn query(inp: &str, query: &str) -> Vec<String> {
+        parse(inp)
+            .query(query)
+            .unwrap()
+            .iter()
+            .map(|m| m.to_string())
+            .collect()
+    }
+
+    const SERVERS: &str = r#"{
+        "servers": [
+            {"name": "web", "port": 80},
+            {"name": "api", "port": 8080, "internal": true},
+            {"name": "db", "port": 5432, "replica": {"name": "db-2", "port": 5433}}
+        ]
+    }"#;
+
+    #[test]
+    fn root() {
+        assert_eq!(query("[1]", "$"), vec!["$"]);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            query(SERVERS, "$.servers[0].name"),
+            vec!["$['servers'][0]['name']"]
+        );
+        assert_eq!(query(SERVERS, "$.missing"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn descendants() {
+        assert_eq!(
+            query(SERVERS, "$..name"),
+            vec![
+                "$['servers'][0]['name']",
+                "$['servers'][1]['name']",
+                "$['servers'][2]['name']",
+                "$['servers'][2]['replica']['name']",
+            ]
+        );
+    }
+
+    #[test]
+    fn wildcard_visits_keys_in_order() {
+        assert_eq!(
+            query(SERVERS, "$.servers[1].*"),
+            vec![
+                "$['servers'][1]['internal']",
+                "$['servers'][1]['name']",
+                "$['servers'][1]['port']",
+            ]
+        );
+    }
+
+    #[test]
+    fn indices_and_slices() {
+        let arr = "[0, 1, 2, 3, 4]";
+
+        assert_eq!(query(arr, "$[-1]"), vec!["$[4]"]);
+        assert_eq!(query(arr, "$[5]"), Vec::<String>::new());
+        assert_eq!(query(arr, "$[1:3]"), vec!["$[1]", "$[2]"]);
+        assert_eq!(query(arr, "$[::2]"), vec!["$[0]", "$[2]", "$[4]"]);
+        assert_eq!(query(arr, "$[-2:]"), vec!["$[3]", "$[4]"]);
+        assert_eq!(query(arr, "$[::-2]"), vec!["$[4]", "$[2]", "$[0]"]);
+        assert_eq!(query(arr, "$[3:1:-1]"), vec!["$[3]", "$[2]"]);
+        assert_eq!(query(arr, "$[::0]"), Vec::<String>::new());
+        assert_eq!(query(arr, "$[1:5:9223372036854775807]"), vec!["$[1]"]);
+        assert_eq!(query(arr, "$[-1::-9223372036854775807]"), vec!["$[4]"]);
+        assert_eq!(
+            query(SERVERS, "$.servers[1:5:9223372036854775807].name"),
+            vec!["$['servers'][1]['name']"]
+        );
+    }
+
+    #[test]
+    fn union() {
+        assert_eq!(
+            query(SERVERS, "$.servers[0]['port', 'name']"),
+            vec!["$['servers'][0]['port']", "$['servers'][0]['name']"]
+        );
+    }
+
+    #[test]
+    fn filter_comparison() {
+        assert_eq!(
+            query(SERVERS, "$.servers[?(@.port > 1024)].name"),
+            vec!["$['servers'][1]['name']", "$['servers'][2]['name']"]
+        );
+        assert_eq!(
+            query(SERVERS, "$..[?(@.name == 'db-2')]"),
+            vec!["$['servers'][2]['replica']"]
+        );
+    }
+
+    #[test]
+    fn filter_logic() {
+        assert_eq!(
+            query(
+                SERVERS,
+                "$.servers[?(@.port >= 80 && !@.internal || @.name == 'api')].name"
+            ),
+            vec![
+                "$['servers'][0]['name']",
+                "$['servers'][1]['name']",
+                "$['servers'][2]['name']",
+            ]
+        );
+        assert_eq!(
+            query(
+                SERVERS,
+                "$.servers[?(@.port > 100 && (@.internal || @.replica))]"
+            ),
+            vec!["$['servers'][1]", "$['servers'][2]"]
+        );
+    }
+
+    #[test]
+    fn filter_with_root_path() {
+        let inp = r#"{"min": 2, "vals": [1, 2, 3]}"#;
+
+        assert_eq!(
+            query(inp, "$.vals[?(@ >= $.min)]"),
+            vec!["$['vals'][1]", "$['vals'][2]"]
+        );
+    }
+
+    #[test]
+    fn filter_compares_types_strictly() {
+        let inp = r#"[1, "1", true, null]"#;
+
+        assert_eq!(query(inp, "$[?(@ == 1)]"), vec!["$[0]"]);
+        assert_eq!(query(inp, "$[?(@ < 2)]"), vec!["$[0]"]);
+        assert_eq!(query(inp, "$[?(@ == null)]"), vec!["$[3]"]);
+    }
+
+    #[test]
+    fn matches_carry_spans_and_pointers() {
+        let node = parse(SERVERS);
+        let matches = node.query("$.servers[2].replica.port").unwrap();
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].pointer(), "/servers/2/replica/port");
+        assert_eq!(node.pointer(&matches[0].pointer()), Ok(matches[0].node));
+        assert_eq!(
+            (matches[0].from, matches[0].to),
+            (matches[0].node.from(), matches[0].node.to())
+        );
+    }
+
+    #[test]
+    fn escaped_paths() {
+        let node = parse(r#"{"a/b": {"it's": 1}}"#);
+        let m = &node.query("$..*").unwrap()[1];
+
+        assert_eq!(m.to_string(), r"$['a/b']['it\'s']");
+        assert_eq!(m.pointer(), "/a~1b/it's");
+    }
+
+    #[test]
+    fn syntax_error() {
+        assert_eq!(
+            parse("[]").query("$[?(@.a >)]"),
+            Err(QueryErr::new_unexpected_char(9))
+        );
+    }
+}
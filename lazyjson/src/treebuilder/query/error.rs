@@ -0,0 +1,108 @@
+use core::fmt;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum QueryErrTyp {
+    InvalidNumber,
+    MissingRoot,
+    UnexpectedChar,
+    UnexpectedEnd,
+    UnterminatedStr,
+}
+
+/// A syntax error in a JSONPath query. `from` and `to` are char indices into the
+/// query.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct QueryErr {
+    pub typ: QueryErrTyp,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl QueryErr {
+    pub fn new_invalid_number(from: usize, to: usize) -> QueryErr {
+        QueryErr {
+            typ: QueryErrTyp::InvalidNumber,
+            from,
+            to,
+        }
+    }
+    pub fn new_missing_root(i: usize) -> QueryErr {
+        QueryErr {
+            typ: QueryErrTyp::MissingRoot,
+            from: i,
+            to: i + 1,
+        }
+    }
+    pub fn new_unexpected_char(i: usize) -> QueryErr {
+        QueryErr {
+            typ: QueryErrTyp::UnexpectedChar,
+            from: i,
+            to: i + 1,
+        }
+    }
+    pub fn new_unexpected_end(i: usize) -> QueryErr {
+        QueryErr {
+            typ: QueryErrTyp::UnexpectedEnd,
+            from: i,
+            to: i + 1,
+        }
+    }
+    pub fn new_unterminated_str(from: usize, to: usize) -> QueryErr {
+        QueryErr {
+            typ: QueryErrTyp::UnterminatedStr,
+            from,
+            to,
+        }
+    }
+
+    pub fn msg(&self, query: &str) -> String {
+        let verbal_hint = match self.typ {
+            QueryErrTyp::InvalidNumber => "invalid number".to_string(),
+            QueryErrTyp::MissingRoot => "expected the query to start with `$`".to_string(),
+            QueryErrTyp::UnexpectedChar => format!(
+                "unexpected character `{}`",
+                query.chars().nth(self.from).unwrap_or_default()
+            ),
+            QueryErrTyp::UnexpectedEnd => "unexpected end of the query".to_string(),
+            QueryErrTyp::UnterminatedStr => "unterminated string".to_string(),
+        };
+
+        format!(
+            "{}, char: {}\n\n{}\n{}{}\n",
+            verbal_hint,
+            self.from + 1,
+            query,
+            " ".repeat(self.from),
+            "^".repeat(self.to - self.from)
+        )
+    }
+}
+
+impl fmt::Display for QueryErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for QueryErr {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unexpected_char_msg() {
+        assert_eq!(
+            QueryErr::new_unexpected_char(3).msg("$.a#"),
+            "unexpected character `#`, char: 4\n\n$.a#\n   ^\n"
+        );
+    }
+
+    #[test]
+    fn unterminated_str_msg() {
+        assert_eq!(
+            QueryErr::new_unterminated_str(2, 5).msg("$['ab"),
+            "unterminated string, char: 3\n\n$['ab\n  ^^^\n"
+        );
+    }
+}
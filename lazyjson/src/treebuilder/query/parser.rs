@@ -0,0 +1,606 @@
+use crate::treebuilder::node::{BoolNode, Node, NullNode, NumberNode, StringNode};
+
+use super::error::QueryErr;
+
+/// A path that starts at the root (`$`) or, inside of filters, at the current
+/// node (`@`).
+#[derive(PartialEq, Debug)]
+pub struct Path {
+    pub is_relative: bool,
+    pub segments: Vec<Segment>,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Segment {
+    /// Applies the selectors to the children (`.name`, `[0, 1]`).
+    Child(Vec<Selector>),
+    /// Applies the selectors to the children of the node and all of its
+    /// descendants (`..name`).
+    Descendant(Vec<Selector>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Selector {
+    Name(String),
+    Wildcard,
+    /// Negative indices count from the end of the array.
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Box<Expr>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Cmp(Operand, CmpOp, Operand),
+    /// True if the path matches anything.
+    Exists(Path),
+    Not(Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Operand {
+    Lit(Node),
+    Path(Path),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum CmpOp {
+    Eq,
+    Ge,
+    Gt,
+    Le,
+    Lt,
+    Ne,
+}
+
+pub fn parse(query: &str) -> Result<Path, QueryErr> {
+    let p = &mut Parser {
+        chars: query.chars().collect(),
+        i: 0,
+    };
+
+    p.skip_whitespace();
+
+    if p.peek() != Some('$') {
+        return Err(QueryErr::new_missing_root(p.i));
+    }
+
+    let path = p.parse_path()?;
+
+    p.skip_whitespace();
+
+    match p.peek() {
+        None => Ok(path),
+        Some(_) => Err(QueryErr::new_unexpected_char(p.i)),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    i: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.i).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.i + offset).copied()
+    }
+
+    fn next(&mut self) -> Result<char, QueryErr> {
+        let c = self.peek().ok_or(QueryErr::new_unexpected_end(self.i))?;
+        self.i += 1;
+
+        Ok(c)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), QueryErr> {
+        match self.next()? {
+            c if c == expected => Ok(()),
+            _ => Err(QueryErr::new_unexpected_char(self.i - 1)),
+        }
+    }
+
+    fn consume_str(&mut self, s: &str) -> bool {
+        let matches = s
+            .chars()
+            .enumerate()
+            .all(|(offset, c)| self.peek_at(offset) == Some(c));
+
+        if matches {
+            self.i += s.chars().count();
+        }
+
+        matches
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.i += 1;
+        }
+    }
+
+    /// Parses `$` or `@`, followed by the segments.
+    fn parse_path(&mut self) -> Result<Path, QueryErr> {
+        let is_relative = self.next()? == '@';
+        let mut segments = Vec::new();
+
+        loop {
+            match (self.peek(), self.peek_at(1)) {
+                (Some('.'), Some('.')) => {
+                    self.i += 2;
+
+                    let selectors = match self.peek() {
+                        Some('[') => self.parse_bracket()?,
+                        _ => vec![self.parse_dot_selector()?],
+                    };
+
+                    segments.push(Segment::Descendant(selectors));
+                }
+                (Some('.'), _) => {
+                    self.i += 1;
+                    segments.push(Segment::Child(vec![self.parse_dot_selector()?]));
+                }
+                (Some('['), _) => segments.push(Segment::Child(self.parse_bracket()?)),
+                _ => {
+                    return Ok(Path {
+                        is_relative,
+                        segments,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Parses the selector after a `.` (`*` or a name).
+    fn parse_dot_selector(&mut self) -> Result<Selector, QueryErr> {
+        if self.peek() == Some('*') {
+            self.i += 1;
+
+            return Ok(Selector::Wildcard);
+        }
+
+        let from = self.i;
+
+        match self.peek() {
+            Some(c) if is_name_start(c) => {}
+            Some(_) => return Err(QueryErr::new_unexpected_char(from)),
+            None => return Err(QueryErr::new_unexpected_end(from)),
+        }
+
+        while self.peek().is_some_and(is_name_char) {
+            self.i += 1;
+        }
+
+        Ok(Selector::Name(self.chars[from..self.i].iter().collect()))
+    }
+
+    /// Parses the comma separated selectors in brackets (`['a', 0, 1:3]`).
+    fn parse_bracket(&mut self) -> Result<Vec<Selector>, QueryErr> {
+        self.expect('[')?;
+
+        let mut selectors = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.parse_bracket_selector()?);
+            self.skip_whitespace();
+
+            match self.next()? {
+                ',' => {}
+                ']' => return Ok(selectors),
+                _ => return Err(QueryErr::new_unexpected_char(self.i - 1)),
+            }
+        }
+    }
+
+    fn parse_bracket_selector(&mut self) -> Result<Selector, QueryErr> {
+        match self.peek() {
+            Some('\'') | Some('"') => Ok(Selector::Name(self.parse_str()?)),
+            Some('*') => {
+                self.i += 1;
+
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.i += 1;
+                self.skip_whitespace();
+
+                Ok(Selector::Filter(Box::new(self.parse_or()?)))
+            }
+            Some(c) if c == '-' || c == ':' || c.is_ascii_digit() => self.parse_idx_or_slice(),
+            Some(_) => Err(QueryErr::new_unexpected_char(self.i)),
+            None => Err(QueryErr::new_unexpected_end(self.i)),
+        }
+    }
+
+    fn parse_idx_or_slice(&mut self) -> Result<Selector, QueryErr> {
+        let start = self.parse_opt_int()?;
+
+        self.skip_whitespace();
+
+        if self.peek() != Some(':') {
+            return match start {
+                Some(idx) => Ok(Selector::Index(idx)),
+                None => Err(QueryErr::new_unexpected_char(self.i)),
+            };
+        }
+
+        self.i += 1;
+        self.skip_whitespace();
+
+        let end = self.parse_opt_int()?;
+        let mut step = None;
+
+        self.skip_whitespace();
+
+        if self.peek() == Some(':') {
+            self.i += 1;
+            self.skip_whitespace();
+
+            step = self.parse_opt_int()?;
+        }
+
+        Ok(Selector::Slice { start, end, step })
+    }
+
+    fn parse_opt_int(&mut self) -> Result<Option<i64>, QueryErr> {
+        let from = self.i;
+
+        if self.peek() == Some('-') {
+            self.i += 1;
+        }
+
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+
+        if self.i == from {
+            return Ok(None);
+        }
+
+        self.chars[from..self.i]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .map(Some)
+            .map_err(|_| QueryErr::new_invalid_number(from, self.i))
+    }
+
+    /// Parses a string in single or double quotes.
+    fn parse_str(&mut self) -> Result<String, QueryErr> {
+        let from = self.i;
+        let quote = self.next()?;
+        let mut s = String::new();
+
+        loop {
+            let c = self
+                .next()
+                .map_err(|_| QueryErr::new_unterminated_str(from, self.i))?;
+
+            match c {
+                c if c == quote => return Ok(s),
+                '\\' => s.push(self.parse_escape(from)?),
+                c => s.push(c),
+            }
+        }
+    }
+
+    fn parse_escape(&mut self, str_from: usize) -> Result<char, QueryErr> {
+        let c = self
+            .next()
+            .map_err(|_| QueryErr::new_unterminated_str(str_from, self.i))?;
+
+        match c {
+            'b' => Ok('\u{8}'),
+            'f' => Ok('\u{c}'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            'u' => {
+                let from = self.i;
+                let hex = self.chars.get(from..from + 4).unwrap_or_default();
+
+                self.i += hex.len();
+
+                u32::from_str_radix(&hex.iter().collect::<String>(), 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .and_then(char::from_u32)
+                    .ok_or(QueryErr::new_invalid_number(from, self.i))
+            }
+            '\\' | '/' | '\'' | '"' => Ok(c),
+            _ => Err(QueryErr::new_unexpected_char(self.i - 1)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryErr> {
+        let mut expr = self.parse_and()?;
+
+        while self.consume_str("||") {
+            self.skip_whitespace();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryErr> {
+        let mut expr = self.parse_unary()?;
+
+        while self.consume_str("&&") {
+            self.skip_whitespace();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    /// Parses a negation, a parenthesized expression, a comparison or an
+    /// existence test. Consumes the whitespace that follows.
+    fn parse_unary(&mut self) -> Result<Expr, QueryErr> {
+        if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+            self.i += 1;
+            self.skip_whitespace();
+
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        if self.peek() == Some('(') {
+            self.i += 1;
+            self.skip_whitespace();
+
+            let expr = self.parse_or()?;
+
+            self.expect(')')?;
+            self.skip_whitespace();
+
+            return Ok(expr);
+        }
+
+        let lhs_from = self.i;
+        let lhs = self.parse_operand()?;
+
+        self.skip_whitespace();
+
+        let op = match self.parse_cmp_op() {
+            Some(op) => op,
+            None => {
+                return match lhs {
+                    Operand::Path(path) => Ok(Expr::Exists(path)),
+                    Operand::Lit(_) => Err(QueryErr::new_unexpected_char(lhs_from)),
+                }
+            }
+        };
+
+        self.skip_whitespace();
+
+        let rhs = self.parse_operand()?;
+
+        self.skip_whitespace();
+
+        Ok(Expr::Cmp(lhs, op, rhs))
+    }
+
+    fn parse_cmp_op(&mut self) -> Option<CmpOp> {
+        // Two char operators have to be checked first.
+        let ops = [
+            ("==", CmpOp::Eq),
+            ("!=", CmpOp::Ne),
+            ("<=", CmpOp::Le),
+            (">=", CmpOp::Ge),
+            ("<", CmpOp::Lt),
+            (">", CmpOp::Gt),
+        ];
+
+        ops.iter()
+            .find(|(s, _)| self.consume_str(s))
+            .map(|(_, op)| *op)
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, QueryErr> {
+        let from = self.i;
+
+        match self.peek() {
+            Some('@') | Some('$') => Ok(Operand::Path(self.parse_path()?)),
+            Some('\'') | Some('"') => {
+                Ok(Operand::Lit(StringNode::new(0, self.parse_str()?).into()))
+            }
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                Ok(Operand::Lit(NumberNode::new(0, self.parse_num()?).into()))
+            }
+            Some(_) if self.consume_str("true") => Ok(Operand::Lit(BoolNode::new(0, true).into())),
+            Some(_) if self.consume_str("false") => {
+                Ok(Operand::Lit(BoolNode::new(0, false).into()))
+            }
+            Some(_) if self.consume_str("null") => Ok(Operand::Lit(NullNode::new(0).into())),
+            Some(_) => Err(QueryErr::new_unexpected_char(from)),
+            None => Err(QueryErr::new_unexpected_end(from)),
+        }
+    }
+
+    /// Parses a JSON number (`-1.5e3`).
+    fn parse_num(&mut self) -> Result<String, QueryErr> {
+        let from = self.i;
+
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            self.i += 1;
+        }
+
+        let num = self.chars[from..self.i].iter().collect::<String>();
+
+        match num.parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(num),
+            _ => Err(QueryErr::new_invalid_number(from, self.i)),
+        }
+    }
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn child(selectors: Vec<Selector>) -> Segment {
+        Segment::Child(selectors)
+    }
+
+    fn name(name: &str) -> Selector {
+        Selector::Name(name.to_owned())
+    }
+
+    fn path(is_relative: bool, segments: Vec<Segment>) -> Path {
+        Path {
+            is_relative,
+            segments,
+        }
+    }
+
+    #[test]
+    fn root() {
+        assert_eq!(parse("$"), Ok(path(false, vec![])));
+    }
+
+    #[test]
+    fn missing_root() {
+        assert_eq!(parse("a.b"), Err(QueryErr::new_missing_root(0)));
+    }
+
+    #[test]
+    fn dot_notation() {
+        assert_eq!(
+            parse("$.servers.*..port"),
+            Ok(path(
+                false,
+                vec![
+                    child(vec![name("servers")]),
+                    child(vec![Selector::Wildcard]),
+                    Segment::Descendant(vec![name("port")]),
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn bracket_notation() {
+        assert_eq!(
+            parse(r#"$['a\'b', "c", 0, -1, *]"#),
+            Ok(path(
+                false,
+                vec![child(vec![
+                    name("a'b"),
+                    name("c"),
+                    Selector::Index(0),
+                    Selector::Index(-1),
+                    Selector::Wildcard,
+                ])]
+            ))
+        );
+    }
+
+    #[test]
+    fn slices() {
+        assert_eq!(
+            parse("$[1:3, :2, ::-1, 1:]"),
+            Ok(path(
+                false,
+                vec![child(vec![
+                    Selector::Slice {
+                        start: Some(1),
+                        end: Some(3),
+                        step: None
+                    },
+                    Selector::Slice {
+                        start: None,
+                        end: Some(2),
+                        step: None
+                    },
+                    Selector::Slice {
+                        start: None,
+                        end: None,
+                        step: Some(-1)
+                    },
+                    Selector::Slice {
+                        start: Some(1),
+                        end: None,
+                        step: None
+                    },
+                ])]
+            ))
+        );
+    }
+
+    #[test]
+    fn filter() {
+        assert_eq!(
+            parse("$[?(@.port > 1024 && !@.internal)]"),
+            Ok(path(
+                false,
+                vec![child(vec![Selector::Filter(Box::new(Expr::And(
+                    Box::new(Expr::Cmp(
+                        Operand::Path(path(true, vec![child(vec![name("port")])])),
+                        CmpOp::Gt,
+                        Operand::Lit(NumberNode::new(0, "1024".to_owned()).into()),
+                    )),
+                    Box::new(Expr::Not(Box::new(Expr::Exists(path(
+                        true,
+                        vec![child(vec![name("internal")])]
+                    ))))),
+                )))])]
+            ))
+        );
+    }
+
+    #[test]
+    fn filter_without_parens() {
+        assert_eq!(
+            parse("$[?@.a != null]"),
+            Ok(path(
+                false,
+                vec![child(vec![Selector::Filter(Box::new(Expr::Cmp(
+                    Operand::Path(path(true, vec![child(vec![name("a")])])),
+                    CmpOp::Ne,
+                    Operand::Lit(NullNode::new(0).into()),
+                )))])]
+            ))
+        );
+    }
+
+    #[test]
+    fn lone_literal_in_filter() {
+        assert_eq!(parse("$[?(1)]"), Err(QueryErr::new_unexpected_char(4)));
+    }
+
+    #[test]
+    fn unterminated_str() {
+        assert_eq!(parse("$['ab"), Err(QueryErr::new_unterminated_str(2, 5)));
+    }
+
+    #[test]
+    fn unterminated_bracket() {
+        assert_eq!(parse("$[0"), Err(QueryErr::new_unexpected_end(3)));
+    }
+
+    #[test]
+    fn trailing_chars() {
+        assert_eq!(parse("$.a b"), Err(QueryErr::new_unexpected_char(4)));
+    }
+}
@@ -0,0 +1,1233 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Debug},
+};
+
+use regex::Regex;
+
+use crate::tokenizer::Token;
+
+use super::{
+    builtins::eq_val,
+    error::get_msg,
+    node::{Node, NodeTyp, PathSegment, Provenance},
+    pointer::to_pointer,
+    semantic::Semantic,
+};
+
+const TYPES: [&str; 7] = [
+    "array", "boolean", "integer", "null", "number", "object", "string",
+];
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum SchemaErrTyp {
+    /// A schema (the root or the value of a keyword like `items`) is neither
+    /// an object nor a bool.
+    NotASchema { received: NodeTyp },
+    /// The value of the keyword has an invalid type or value. `expected`
+    /// describes the accepted values, for example "a non-negative integer".
+    InvalidKeyword {
+        keyword: String,
+        expected: &'static str,
+    },
+    /// The value of `pattern`, or a key of `patternProperties`, is not a valid
+    /// regular expression.
+    InvalidPattern { pattern: String },
+    /// `$ref` does not refer to a location within the schema. Only references
+    /// of the form `#` and `#/json/pointer` are supported.
+    UnresolvedRef { reference: String },
+}
+
+/// Returned by [`Schema::new`]. The span refers to the schema.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SchemaErr {
+    pub typ: SchemaErrTyp,
+    /// The location in the schema, as a JSON Pointer.
+    pub schema_path: String,
+    pub from: usize,
+    pub to: usize,
+    /// Set if the node was produced by using a variable.
+    pub provenance: Option<Provenance>,
+}
+
+impl fmt::Display for SchemaErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Error for SchemaErr {}
+
+impl SchemaErr {
+    fn new(typ: SchemaErrTyp, node: &Node, schema_path: &[PathSegment]) -> SchemaErr {
+        SchemaErr {
+            typ,
+            schema_path: to_pointer(schema_path),
+            from: node.from(),
+            to: node.to(),
+            provenance: node.provenance().cloned(),
+        }
+    }
+    fn new_invalid_keyword(
+        keyword: &str,
+        expected: &'static str,
+        node: &Node,
+        schema_path: &[PathSegment],
+    ) -> SchemaErr {
+        let keyword = keyword.to_owned();
+
+        SchemaErr::new(
+            SchemaErrTyp::InvalidKeyword { keyword, expected },
+            node,
+            schema_path,
+        )
+    }
+
+    pub fn msg(&self, toks: &[Token], inp: &str) -> String {
+        let verbal_hint = match &self.typ {
+            SchemaErrTyp::NotASchema { received } => format!(
+                "expected a schema (an `Object` or a `Bool`) but received a `{}`",
+                received
+            ),
+            SchemaErrTyp::InvalidKeyword { keyword, expected } => {
+                format!("expected `{}` to be {}", keyword, expected)
+            }
+            SchemaErrTyp::InvalidPattern { pattern } => {
+                format!("`{}` is not a valid regular expression", pattern)
+            }
+            SchemaErrTyp::UnresolvedRef { reference } => {
+                format!("`{}` does not refer to a location in the schema", reference)
+            }
+        };
+
+        get_msg(
+            &format!("invalid schema at `{}`: {}", self.schema_path, verbal_hint),
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ViolationTyp {
+    /// The schema is `false`.
+    False,
+    Typ {
+        expected: Vec<String>,
+        received: NodeTyp,
+    },
+    Const,
+    Enum,
+    /// The object has no entry with the key.
+    Required {
+        key: String,
+    },
+    /// The entry is neither listed in `properties` nor matched by
+    /// `patternProperties`, and `additionalProperties` is `false`.
+    AdditionalProperty {
+        key: String,
+    },
+    Pattern {
+        pattern: String,
+    },
+    MinLength {
+        limit: usize,
+    },
+    MaxLength {
+        limit: usize,
+    },
+    /// The limits of numbers keep the lexeme they are written with.
+    Minimum {
+        limit: String,
+    },
+    ExclusiveMinimum {
+        limit: String,
+    },
+    Maximum {
+        limit: String,
+    },
+    ExclusiveMaximum {
+        limit: String,
+    },
+    MultipleOf {
+        divisor: String,
+    },
+    MinItems {
+        limit: usize,
+    },
+    MaxItems {
+        limit: usize,
+    },
+    UniqueItems,
+    MinProperties {
+        limit: usize,
+    },
+    MaxProperties {
+        limit: usize,
+    },
+    /// The value matches the schema of `not`.
+    Not,
+    /// The value matches none of the schemas of `anyOf`.
+    AnyOf,
+    /// The value matches none, or more than one, of the schemas of `oneOf`.
+    OneOf {
+        matched: usize,
+    },
+}
+
+/// A value that does not conform to the schema. The span refers to the
+/// offending node of the validated document.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Violation {
+    pub typ: ViolationTyp,
+    /// The path of the offending node, from the root of the document.
+    pub instance_path: Vec<PathSegment>,
+    /// The path of the violated keyword, from the root of the schema.
+    pub schema_path: Vec<PathSegment>,
+    pub from: usize,
+    pub to: usize,
+    /// Set if the node was produced by using a variable.
+    pub provenance: Option<Provenance>,
+}
+
+impl Violation {
+    /// Returns the instance path as a JSON Pointer (`/servers/0/port`).
+    pub fn instance_pointer(&self) -> String {
+        to_pointer(&self.instance_path)
+    }
+    /// Returns the schema path as a JSON Pointer
+    /// (`/properties/servers/items/properties/port/maximum`).
+    pub fn schema_pointer(&self) -> String {
+        to_pointer(&self.schema_path)
+    }
+
+    pub fn msg(&self, toks: &[Token], inp: &str) -> String {
+        let verbal_hint = format!(
+            "{} (at `{}`, schema: `{}`)",
+            get_verbal_hint(&self.typ),
+            self.instance_pointer(),
+            self.schema_pointer()
+        );
+
+        get_msg(
+            &verbal_hint,
+            self.from,
+            self.to,
+            self.provenance.as_ref(),
+            toks,
+            inp,
+        )
+    }
+}
+
+fn get_verbal_hint(typ: &ViolationTyp) -> String {
+    match typ {
+        ViolationTyp::False => "no value is allowed here".to_string(),
+        ViolationTyp::Typ { expected, received } => format!(
+            "expected {} but received a `{}`",
+            expected
+                .iter()
+                .map(|typ| format!("`{}`", typ))
+                .collect::<Vec<String>>()
+                .join(" or "),
+            received
+        ),
+        ViolationTyp::Const => "the value differs from the `const` of the schema".to_string(),
+        ViolationTyp::Enum => "the value is none of the values of the `enum`".to_string(),
+        ViolationTyp::Required { key } => format!("the required entry `{}` is missing", key),
+        ViolationTyp::AdditionalProperty { key } => {
+            format!("the entry `{}` is not allowed", key)
+        }
+        ViolationTyp::Pattern { pattern } => {
+            format!("the string does not match the pattern `{}`", pattern)
+        }
+        ViolationTyp::MinLength { limit } => {
+            format!("the string is shorter than {} chars", limit)
+        }
+        ViolationTyp::MaxLength { limit } => format!("the string is longer than {} chars", limit),
+        ViolationTyp::Minimum { limit } => format!("the number is less than {}", limit),
+        ViolationTyp::ExclusiveMinimum { limit } => {
+            format!("the number is not greater than {}", limit)
+        }
+        ViolationTyp::Maximum { limit } => format!("the number is greater than {}", limit),
+        ViolationTyp::ExclusiveMaximum { limit } => {
+            format!("the number is not less than {}", limit)
+        }
+        ViolationTyp::MultipleOf { divisor } => {
+            format!("the number is not a multiple of {}", divisor)
+        }
+        ViolationTyp::MinItems { limit } => format!("the array has less than {} entries", limit),
+        ViolationTyp::MaxItems { limit } => format!("the array has more than {} entries", limit),
+        ViolationTyp::UniqueItems => "the array contains duplicate entries".to_string(),
+        ViolationTyp::MinProperties { limit } => {
+            format!("the object has less than {} entries", limit)
+        }
+        ViolationTyp::MaxProperties { limit } => {
+            format!("the object has more than {} entries", limit)
+        }
+        ViolationTyp::Not => "the value matches the schema of `not`".to_string(),
+        ViolationTyp::AnyOf => "the value matches none of the schemas of `anyOf`".to_string(),
+        ViolationTyp::OneOf { matched } => format!(
+            "the value matches {} of the schemas of `oneOf` instead of exactly one",
+            matched
+        ),
+    }
+}
+
+/// A [JSON Schema](https://json-schema.org/draft/2020-12/json-schema-core)
+/// (draft 2020-12), which validates nodes. Supported are boolean schemas,
+/// `type`, `enum`, `const`, `properties`, `patternProperties`,
+/// `additionalProperties`, `required`, `minProperties`, `maxProperties`,
+/// `prefixItems`, `items`, `minItems`, `maxItems`, `uniqueItems`, `pattern`,
+/// `minLength`, `maxLength`, `minimum`, `maximum`, `exclusiveMinimum`,
+/// `exclusiveMaximum`, `multipleOf`, `allOf`, `anyOf`, `oneOf`, `not` and
+/// `$ref` within the schema (`#/$defs/port`). Other keywords are ignored.
+///
+/// Regular expressions are not anchored, and follow the syntax of the `regex`
+/// crate, which covers most of ECMA-262.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Node,
+    regexes: HashMap<String, Regex>,
+}
+
+impl Schema {
+    /// Checks the schema, and compiles its regular expressions.
+    // The error is not boxed, like the other errors of the crate. Schemas are
+    // compiled once, so the size of the result does not matter.
+    #[allow(clippy::result_large_err)]
+    pub fn new(schema: &Node) -> Result<Schema, SchemaErr> {
+        let mut regexes = HashMap::new();
+
+        compile(
+            schema,
+            schema,
+            &mut vec![],
+            &mut regexes,
+            &mut HashSet::new(),
+        )?;
+
+        Ok(Schema {
+            root: schema.clone(),
+            regexes,
+        })
+    }
+
+    /// Returns all violations, in the order of the (sorted) keywords of the
+    /// schema. The document is valid if there are none.
+    pub fn validate(&self, node: &Node) -> Vec<Violation> {
+        let mut violations = vec![];
+        let mut ctx = Ctx {
+            instance_path: vec![],
+            schema_path: vec![],
+        };
+
+        self.check(&self.root, node, &mut ctx, &[], &mut violations);
+
+        violations
+    }
+    pub fn is_valid(&self, node: &Node) -> bool {
+        self.validate(node).is_empty()
+    }
+
+    /// Pushes the violations of the node against the (sub)schema. `refs` are
+    /// the references that were followed without descending into the node, a
+    /// reference that is followed again can not add any constraints.
+    fn check<'s>(
+        &'s self,
+        schema: &'s Node,
+        node: &Node,
+        ctx: &mut Ctx,
+        refs: &[&'s str],
+        out: &mut Vec<Violation>,
+    ) {
+        let keywords = match schema {
+            Node::Bool(b) if b.val => return,
+            Node::Object(o) => get_sorted(&o.entries),
+            _ => return out.push(ctx.new_violation(ViolationTyp::False, node)),
+        };
+
+        for (keyword, val) in keywords.iter() {
+            ctx.schema_path.push(PathSegment::Key(keyword.to_string()));
+            self.check_keyword(keyword, val, schema, node, ctx, refs, out);
+            ctx.schema_path.pop();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn check_keyword<'s>(
+        &'s self,
+        keyword: &str,
+        val: &'s Node,
+        schema: &'s Node,
+        node: &Node,
+        ctx: &mut Ctx,
+        refs: &[&'s str],
+        out: &mut Vec<Violation>,
+    ) {
+        let mut violate = |typ| out.push(ctx.new_violation(typ, node));
+
+        // The values of the keywords were checked by `compile`, values of
+        // other types are skipped instead of relying on that.
+        match (keyword, node) {
+            ("type", _) => {
+                let expected = match val {
+                    Node::Array(a) => a.entries.iter().filter_map(|t| t.as_str().ok()).collect(),
+                    _ => val.as_str().into_iter().collect::<Vec<&str>>(),
+                };
+
+                if !expected.iter().any(|typ| has_typ(node, typ)) {
+                    violate(ViolationTyp::Typ {
+                        expected: expected.into_iter().map(str::to_owned).collect(),
+                        received: node.typ(),
+                    });
+                }
+            }
+            ("const", _) if !eq_val(val, node) => violate(ViolationTyp::Const),
+            ("enum", _) if !get_array(val).iter().any(|v| eq_val(v, node)) => {
+                violate(ViolationTyp::Enum)
+            }
+            ("$ref", _) => {
+                let reference = match val.as_str() {
+                    Ok(reference) if !refs.contains(&reference) => reference,
+                    _ => return,
+                };
+
+                if let Some(target) = self.resolve(reference) {
+                    let mut refs = refs.to_vec();
+                    refs.push(reference);
+
+                    self.check(target, node, ctx, &refs, out);
+                }
+            }
+            ("allOf", _) => {
+                for (i, schema) in get_array(val).iter().enumerate() {
+                    ctx.schema_path.push(PathSegment::Index(i));
+                    self.check(schema, node, ctx, refs, out);
+                    ctx.schema_path.pop();
+                }
+            }
+            ("anyOf", _) => {
+                let schemas = get_array(val);
+
+                if !schemas.iter().any(|s| self.matches(s, node, refs)) {
+                    violate(ViolationTyp::AnyOf);
+                }
+            }
+            ("oneOf", _) => {
+                let schemas = get_array(val);
+                let matched = schemas
+                    .iter()
+                    .filter(|s| self.matches(s, node, refs))
+                    .count();
+
+                if matched != 1 {
+                    violate(ViolationTyp::OneOf { matched });
+                }
+            }
+            ("not", _) if self.matches(val, node, refs) => violate(ViolationTyp::Not),
+            ("minimum", Node::Number(_)) if cmp_num(node, val) == Some(Ordering::Less) => {
+                violate(ViolationTyp::Minimum {
+                    limit: get_lexeme(val),
+                })
+            }
+            ("exclusiveMinimum", Node::Number(_))
+                if cmp_num(node, val).is_some_and(Ordering::is_le) =>
+            {
+                violate(ViolationTyp::ExclusiveMinimum {
+                    limit: get_lexeme(val),
+                })
+            }
+            ("maximum", Node::Number(_)) if cmp_num(node, val) == Some(Ordering::Greater) => {
+                violate(ViolationTyp::Maximum {
+                    limit: get_lexeme(val),
+                })
+            }
+            ("exclusiveMaximum", Node::Number(_))
+                if cmp_num(node, val).is_some_and(Ordering::is_ge) =>
+            {
+                violate(ViolationTyp::ExclusiveMaximum {
+                    limit: get_lexeme(val),
+                })
+            }
+            ("multipleOf", Node::Number(_)) if !is_multiple_of(node, val) => {
+                violate(ViolationTyp::MultipleOf {
+                    divisor: get_lexeme(val),
+                })
+            }
+            ("minLength", Node::String(s)) if s.val.chars().count() < to_usize(val) => {
+                violate(ViolationTyp::MinLength {
+                    limit: to_usize(val),
+                })
+            }
+            ("maxLength", Node::String(s)) if s.val.chars().count() > to_usize(val) => {
+                violate(ViolationTyp::MaxLength {
+                    limit: to_usize(val),
+                })
+            }
+            ("pattern", Node::String(s)) => {
+                let pattern = val.as_str().unwrap_or_default();
+
+                if self
+                    .regexes
+                    .get(pattern)
+                    .is_some_and(|r| !r.is_match(&s.val))
+                {
+                    violate(ViolationTyp::Pattern {
+                        pattern: pattern.to_owned(),
+                    })
+                }
+            }
+            ("minItems", Node::Array(a)) if a.entries.len() < to_usize(val) => {
+                violate(ViolationTyp::MinItems {
+                    limit: to_usize(val),
+                })
+            }
+            ("maxItems", Node::Array(a)) if a.entries.len() > to_usize(val) => {
+                violate(ViolationTyp::MaxItems {
+                    limit: to_usize(val),
+                })
+            }
+            ("uniqueItems", Node::Array(a)) if val.as_bool() == Ok(true) => {
+                let unique = a.entries.iter().map(Semantic::new).collect::<HashSet<_>>();
+
+                if unique.len() != a.entries.len() {
+                    violate(ViolationTyp::UniqueItems)
+                }
+            }
+            ("prefixItems", Node::Array(a)) => {
+                let schemas = get_array(val);
+
+                for (i, (schema, entry)) in schemas.iter().zip(a.entries.iter()).enumerate() {
+                    ctx.schema_path.push(PathSegment::Index(i));
+                    self.check_child(schema, entry, PathSegment::Index(i), ctx, out);
+                    ctx.schema_path.pop();
+                }
+            }
+            ("items", Node::Array(a)) => {
+                let skipped = schema.get("prefixItems").map_or(0, |p| get_array(p).len());
+
+                for (i, entry) in a.entries.iter().enumerate().skip(skipped) {
+                    self.check_child(val, entry, PathSegment::Index(i), ctx, out);
+                }
+            }
+            ("required", Node::Object(o)) => {
+                for key in get_array(val).iter().filter_map(|k| k.as_str().ok()) {
+                    if !o.entries.contains_key(key) {
+                        violate(ViolationTyp::Required {
+                            key: key.to_owned(),
+                        });
+                    }
+                }
+            }
+            ("minProperties", Node::Object(o)) if o.entries.len() < to_usize(val) => {
+                violate(ViolationTyp::MinProperties {
+                    limit: to_usize(val),
+                })
+            }
+            ("maxProperties", Node::Object(o)) if o.entries.len() > to_usize(val) => {
+                violate(ViolationTyp::MaxProperties {
+                    limit: to_usize(val),
+                })
+            }
+            ("properties", Node::Object(o)) => {
+                for (key, schema) in get_schemas(val) {
+                    if let Some(entry) = o.entries.get(key) {
+                        ctx.schema_path.push(PathSegment::Key(key.clone()));
+                        self.check_child(schema, entry, PathSegment::Key(key.clone()), ctx, out);
+                        ctx.schema_path.pop();
+                    }
+                }
+            }
+            ("patternProperties", Node::Object(o)) => {
+                for (pattern, schema) in get_schemas(val) {
+                    let regex = match self.regexes.get(pattern) {
+                        Some(regex) => regex,
+                        None => continue,
+                    };
+
+                    for (key, entry) in get_sorted(&o.entries) {
+                        if regex.is_match(key) {
+                            ctx.schema_path.push(PathSegment::Key(pattern.clone()));
+                            self.check_child(
+                                schema,
+                                entry,
+                                PathSegment::Key(key.clone()),
+                                ctx,
+                                out,
+                            );
+                            ctx.schema_path.pop();
+                        }
+                    }
+                }
+            }
+            ("additionalProperties", Node::Object(o)) => {
+                for (key, entry) in get_sorted(&o.entries) {
+                    if !self.is_additional(schema, key) {
+                        continue;
+                    }
+
+                    match val {
+                        Node::Bool(b) if !b.val => {
+                            ctx.instance_path.push(PathSegment::Key(key.clone()));
+                            out.push(ctx.new_violation(
+                                ViolationTyp::AdditionalProperty { key: key.clone() },
+                                entry,
+                            ));
+                            ctx.instance_path.pop();
+                        }
+                        _ => self.check_child(val, entry, PathSegment::Key(key.clone()), ctx, out),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    /// Checks an entry of the node. References that were followed before do
+    /// not have to be tracked anymore, as the entry is a different node.
+    fn check_child(
+        &self,
+        schema: &Node,
+        entry: &Node,
+        segment: PathSegment,
+        ctx: &mut Ctx,
+        out: &mut Vec<Violation>,
+    ) {
+        ctx.instance_path.push(segment);
+        self.check(schema, entry, ctx, &[], out);
+        ctx.instance_path.pop();
+    }
+    fn matches<'s>(&'s self, schema: &'s Node, node: &Node, refs: &[&'s str]) -> bool {
+        let mut violations = vec![];
+        let mut ctx = Ctx {
+            instance_path: vec![],
+            schema_path: vec![],
+        };
+
+        self.check(schema, node, &mut ctx, refs, &mut violations);
+
+        violations.is_empty()
+    }
+    /// Returns whether the key is neither listed in `properties` nor matched by
+    /// `patternProperties` of the schema.
+    fn is_additional(&self, schema: &Node, key: &str) -> bool {
+        let is_property = schema
+            .get("properties")
+            .is_ok_and(|properties| properties.get(key).is_ok());
+
+        let is_pattern_property = schema.get("patternProperties").is_ok_and(|patterns| {
+            get_schemas(patterns)
+                .iter()
+                .filter_map(|(pattern, _)| self.regexes.get(*pattern))
+                .any(|regex| regex.is_match(key))
+        });
+
+        !is_property && !is_pattern_property
+    }
+    fn resolve(&self, reference: &str) -> Option<&Node> {
+        resolve(&self.root, reference)
+    }
+}
+
+struct Ctx {
+    instance_path: Vec<PathSegment>,
+    schema_path: Vec<PathSegment>,
+}
+
+impl Ctx {
+    fn new_violation(&self, typ: ViolationTyp, node: &Node) -> Violation {
+        Violation {
+            typ,
+            instance_path: self.instance_path.clone(),
+            schema_path: self.schema_path.clone(),
+            from: node.from(),
+            to: node.to(),
+            provenance: node.provenance().cloned(),
+        }
+    }
+}
+
+/// Checks the (sub)schema, and compiles its regular expressions. The targets
+/// of references are checked as well, once each, so every schema that can be
+/// reached while validating is known to be valid. `refs` are the references
+/// whose targets were checked already.
+#[allow(clippy::result_large_err)]
+fn compile<'a>(
+    root: &'a Node,
+    schema: &'a Node,
+    path: &mut Vec<PathSegment>,
+    regexes: &mut HashMap<String, Regex>,
+    refs: &mut HashSet<&'a str>,
+) -> Result<(), SchemaErr> {
+    let keywords = match schema {
+        Node::Bool(_) => return Ok(()),
+        Node::Object(o) => get_sorted(&o.entries),
+        _ => {
+            let typ = SchemaErrTyp::NotASchema {
+                received: schema.typ(),
+            };
+
+            return Err(SchemaErr::new(typ, schema, path));
+        }
+    };
+
+    for (keyword, val) in keywords {
+        path.push(PathSegment::Key(keyword.clone()));
+        compile_keyword(root, keyword, val, path, regexes, refs)?;
+        path.pop();
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::result_large_err)]
+fn compile_keyword<'a>(
+    root: &'a Node,
+    keyword: &str,
+    val: &'a Node,
+    path: &mut Vec<PathSegment>,
+    regexes: &mut HashMap<String, Regex>,
+    refs: &mut HashSet<&'a str>,
+) -> Result<(), SchemaErr> {
+    let invalid = |expected| Err(SchemaErr::new_invalid_keyword(keyword, expected, val, path));
+
+    match keyword {
+        "additionalProperties" | "items" | "not" => compile(root, val, path, regexes, refs),
+        "allOf" | "anyOf" | "oneOf" | "prefixItems" => match val {
+            Node::Array(a) if !a.entries.is_empty() => {
+                for (i, schema) in a.entries.iter().enumerate() {
+                    path.push(PathSegment::Index(i));
+                    compile(root, schema, path, regexes, refs)?;
+                    path.pop();
+                }
+
+                Ok(())
+            }
+            _ => invalid("a non-empty array of schemas"),
+        },
+        "properties" | "patternProperties" | "$defs" | "definitions" => match val {
+            Node::Object(o) => {
+                for (key, schema) in get_sorted(&o.entries) {
+                    path.push(PathSegment::Key(key.clone()));
+
+                    if keyword == "patternProperties" {
+                        compile_pattern(key, schema, path, regexes)?;
+                    }
+
+                    compile(root, schema, path, regexes, refs)?;
+                    path.pop();
+                }
+
+                Ok(())
+            }
+            _ => invalid("an object of schemas"),
+        },
+        "type" => {
+            let is_typ = |t: &Node| t.as_str().is_ok_and(|t| TYPES.contains(&t));
+
+            match val {
+                Node::Array(a) if !a.entries.is_empty() && a.entries.iter().all(is_typ) => Ok(()),
+                Node::String(_) if is_typ(val) => Ok(()),
+                _ => invalid("a type name or an array of type names"),
+            }
+        }
+        "required" => match val {
+            Node::Array(a) if a.entries.iter().all(|k| k.as_str().is_ok()) => Ok(()),
+            _ => invalid("an array of strings"),
+        },
+        "enum" => match val {
+            Node::Array(_) => Ok(()),
+            _ => invalid("an array"),
+        },
+        "minLength" | "maxLength" | "minItems" | "maxItems" | "minProperties" | "maxProperties" => {
+            match val.as_u64() {
+                Ok(_) => Ok(()),
+                Err(_) => invalid("a non-negative integer"),
+            }
+        }
+        "minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum" => match val {
+            Node::Number(_) => Ok(()),
+            _ => invalid("a number"),
+        },
+        "multipleOf" => match val {
+            Node::Number(_) if to_f64(val) > 0.0 => Ok(()),
+            _ => invalid("a positive number"),
+        },
+        "uniqueItems" => match val {
+            Node::Bool(_) => Ok(()),
+            _ => invalid("a bool"),
+        },
+        "pattern" => match val.as_str() {
+            Ok(pattern) => compile_pattern(pattern, val, path, regexes),
+            Err(_) => invalid("a string"),
+        },
+        "$ref" => match val.as_str() {
+            Ok(reference) => match resolve(root, reference) {
+                // The target is checked with the path of the first reference
+                // to it, as violations are reported with the path of the
+                // reference they were found through.
+                Some(target) if refs.insert(reference) => {
+                    compile(root, target, path, regexes, refs)
+                }
+                Some(_) => Ok(()),
+                None => Err(SchemaErr::new(
+                    SchemaErrTyp::UnresolvedRef {
+                        reference: reference.to_owned(),
+                    },
+                    val,
+                    path,
+                )),
+            },
+            Err(_) => invalid("a string"),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Compiles the pattern. `node` is used as the location of the error, as keys
+/// of `patternProperties` are not nodes.
+#[allow(clippy::result_large_err)]
+fn compile_pattern(
+    pattern: &str,
+    node: &Node,
+    path: &[PathSegment],
+    regexes: &mut HashMap<String, Regex>,
+) -> Result<(), SchemaErr> {
+    if regexes.contains_key(pattern) {
+        return Ok(());
+    }
+
+    match Regex::new(pattern) {
+        Ok(regex) => {
+            regexes.insert(pattern.to_owned(), regex);
+
+            Ok(())
+        }
+        Err(_) => Err(SchemaErr::new(
+            SchemaErrTyp::InvalidPattern {
+                pattern: pattern.to_owned(),
+            },
+            node,
+            path,
+        )),
+    }
+}
+
+/// Resolves a reference to a location in the schema (`#/$defs/a%20b`). The
+/// fragment is percent-decoded before it is used as a JSON Pointer.
+fn resolve<'a>(root: &'a Node, reference: &str) -> Option<&'a Node> {
+    let fragment = reference.strip_prefix('#')?;
+
+    root.pointer(&percent_decode(fragment)?).ok()
+}
+
+/// Decodes `%XX` escapes. Returns `None` for incomplete escapes, or if the
+/// decoded bytes are not valid UTF-8.
+fn percent_decode(val: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(val.len());
+    let mut rest = val.as_bytes();
+
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+
+        if b != b'%' {
+            bytes.push(b);
+            continue;
+        }
+
+        let hex = std::str::from_utf8(rest.get(..2)?).ok()?;
+        bytes.push(u8::from_str_radix(hex, 16).ok()?);
+        rest = &rest[2..];
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+fn has_typ(node: &Node, typ: &str) -> bool {
+    match (typ, node) {
+        ("array", Node::Array(_))
+        | ("boolean", Node::Bool(_))
+        | ("null", Node::Null(_))
+        | ("number", Node::Number(_))
+        | ("object", Node::Object(_))
+        | ("string", Node::String(_)) => true,
+        ("integer", Node::Number(n)) => {
+            let n = n.number();
+
+            n.to_i128().is_ok() || n.to_f64().is_ok_and(|n| n.fract() == 0.0)
+        }
+        _ => false,
+    }
+}
+
+/// Returns the entries of an array keyword, or none if it is not an array.
+fn get_array(node: &Node) -> &[Node] {
+    node.as_array().unwrap_or_default()
+}
+
+/// Returns the sorted entries of an object keyword, or none if it is not an
+/// object.
+fn get_schemas(node: &Node) -> Vec<(&String, &Node)> {
+    match node {
+        Node::Object(o) => get_sorted(&o.entries),
+        _ => vec![],
+    }
+}
+
+fn get_sorted(entries: &HashMap<String, Node>) -> Vec<(&String, &Node)> {
+    let mut entries = entries.iter().collect::<Vec<(&String, &Node)>>();
+    entries.sort_by_key(|(key, _)| *key);
+
+    entries
+}
+
+/// Numbers are compared as `f64`, values outside of its range are treated as
+/// infinite.
+fn to_f64(node: &Node) -> f64 {
+    match node {
+        Node::Number(n) => n.val.parse().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    }
+}
+
+/// Compares the numbers exactly if both are integers that fit into an
+/// `i128`, and as `f64` otherwise.
+fn cmp_num(a: &Node, b: &Node) -> Option<Ordering> {
+    if let (Node::Number(a), Node::Number(b)) = (a, b) {
+        if let (Ok(a), Ok(b)) = (a.number().to_i128(), b.number().to_i128()) {
+            return Some(a.cmp(&b));
+        }
+    }
+
+    to_f64(a).partial_cmp(&to_f64(b))
+}
+
+/// Divides the decimal values exactly, by scaling both to integers with the
+/// same exponent (`0.3` and `0.1` to `3` and `1`). Falls back to dividing them
+/// as `f64` if the scaled values do not fit into an `i128`.
+fn is_multiple_of(node: &Node, divisor: &Node) -> bool {
+    let to_decimal = |node: &Node| match node {
+        Node::Number(n) => n.number().to_decimal(),
+        _ => None,
+    };
+
+    if let (Some((a, a_exp)), Some((b, b_exp))) = (to_decimal(node), to_decimal(divisor)) {
+        let exp = a_exp.min(b_exp);
+        let scale = |mantissa: i128, mantissa_exp: i64| {
+            10i128
+                .checked_pow(u32::try_from(mantissa_exp - exp).ok()?)?
+                .checked_mul(mantissa)
+        };
+
+        if let (Some(a), Some(b)) = (scale(a, a_exp), scale(b, b_exp)) {
+            return a.checked_rem(b) == Some(0);
+        }
+    }
+
+    let quotient = to_f64(node) / to_f64(divisor);
+
+    quotient.is_finite() && quotient.fract() == 0.0
+}
+
+fn to_usize(node: &Node) -> usize {
+    node.as_u64()
+        .map_or(usize::MAX, |n| usize::try_from(n).unwrap_or(usize::MAX))
+}
+
+fn get_lexeme(node: &Node) -> String {
+    match node {
+        Node::Number(n) => n.val.clone(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tokenizer::tokenize,
+        treebuilder::{build, testing::parse, Config},
+    };
+
+    use super::*;
+
+    /// Returns the violations as `instance pointer: schema pointer`.
+    fn validate(schema: &str, inp: &str) -> Vec<String> {
+        let schema = Schema::new(&parse(schema)).unwrap();
+
+        schema
+            .validate(&parse(inp))
+            .iter()
+            .map(|v| format!("{}: {}", v.instance_pointer(), v.schema_pointer()))
+            .collect()
+    }
+
+    const SERVER: &str = r##"{
+        "type": "object",
+        "required": ["host", "port"],
+        "properties": {
+            "host": {"type": "string", "pattern": "^[a-z.]+$"},
+            "port": {"$ref": "#/$defs/port"},
+            "tags": {"type": "array", "items": {"type": "string"}, "uniqueItems": true}
+        },
+        "additionalProperties": false,
+        "$defs": {
+            "port": {"type": "integer", "minimum": 1, "maximum": 65535}
+        }
+    }"##;
+
+    #[test]
+    fn valid() {
+        assert!(validate(
+            SERVER,
+            r#"{"host": "example.com", "port": 8080, "tags": ["a"]}"#
+        )
+        .is_empty());
+        assert!(validate(SERVER, r#"{"host": "a", "port": 80.0}"#).is_empty());
+    }
+
+    #[test]
+    fn violations() {
+        assert_eq!(
+            validate(
+                SERVER,
+                r#"{"host": "Example", "port": 70000, "tags": ["a", 1, "a"], "debug": true}"#
+            ),
+            [
+                "/debug: /additionalProperties",
+                "/host: /properties/host/pattern",
+                "/port: /properties/port/$ref/maximum",
+                "/tags/1: /properties/tags/items/type",
+                "/tags: /properties/tags/uniqueItems",
+            ]
+        );
+        assert_eq!(
+            validate(SERVER, r#"{"port": "80"}"#),
+            ["/port: /properties/port/$ref/type", ": /required"]
+        );
+    }
+
+    #[test]
+    fn violation_spans() {
+        let schema = Schema::new(&parse(SERVER)).unwrap();
+        let inp = r#"{"host": "a", "port": 0}"#;
+        let toks = tokenize(inp, &Config::DEFAULT).unwrap();
+        let node = build(&toks, &Config::DEFAULT).unwrap().unwrap();
+
+        let violations = schema.validate(&node);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].typ,
+            ViolationTyp::Minimum {
+                limit: "1".to_owned()
+            }
+        );
+        assert_eq!(
+            (violations[0].from, violations[0].to),
+            (node["port"].from(), node["port"].to())
+        );
+        assert_eq!(
+            violations[0].msg(&toks, inp),
+            "the number is less than 1 (at `/port`, schema: `/properties/port/$ref/minimum`), line: 1, char: 23\n\n{\"host\": \"a\", \"port\": 0}\n                      ^\n"
+        );
+    }
+
+    #[test]
+    fn combinators() {
+        let schema = r#"{
+            "anyOf": [{"type": "string"}, {"type": "integer"}],
+            "oneOf": [{"minimum": 0}, {"multipleOf": 2}],
+            "not": {"const": 4}
+        }"#;
+
+        assert!(validate(schema, "3").is_empty());
+        assert_eq!(validate(schema, "1.5"), [": /anyOf"]);
+        assert_eq!(validate(schema, "6"), [": /oneOf"]);
+        assert_eq!(validate(schema, "4"), [": /not", ": /oneOf"]);
+    }
+
+    #[test]
+    fn prefix_items_and_bool_schemas() {
+        let schema =
+            r#"{"prefixItems": [{"type": "string"}, true], "items": false, "minItems": 2}"#;
+
+        assert!(validate(schema, r#"["a", 1]"#).is_empty());
+        assert_eq!(
+            validate(schema, r#"[1]"#),
+            [": /minItems", "/0: /prefixItems/0/type"]
+        );
+        assert_eq!(validate(schema, r#"["a", 1, 2]"#), ["/2: /items"]);
+    }
+
+    #[test]
+    fn recursive_ref() {
+        let schema = r##"{"$defs": {"node": {"type": "array", "items": {"$ref": "#/$defs/node"}}}, "$ref": "#/$defs/node"}"##;
+
+        assert!(validate(schema, "[[], [[]]]").is_empty());
+        assert_eq!(
+            validate(schema, "[[1]]"),
+            ["/0/0: /$ref/items/$ref/items/$ref/type"]
+        );
+        assert!(validate(r##"{"$ref": "#"}"##, "1").is_empty());
+    }
+
+    #[test]
+    fn numbers_are_compared_exactly() {
+        let schema = r#"{"multipleOf": 0.1}"#;
+
+        assert!(validate(schema, "0.3").is_empty());
+        assert!(validate(schema, "12").is_empty());
+        assert_eq!(validate(schema, "0.35"), [": /multipleOf"]);
+        assert!(validate(r#"{"multipleOf": 0.01}"#, "19.99").is_empty());
+        assert!(validate(r#"{"multipleOf": 3}"#, "0").is_empty());
+
+        let schema = r#"{"maximum": 18446744073709551615}"#;
+
+        assert!(validate(schema, "18446744073709551615").is_empty());
+        assert_eq!(validate(schema, "18446744073709551616"), [": /maximum"]);
+        assert_eq!(
+            validate(
+                r#"{"exclusiveMinimum": 9007199254740993}"#,
+                "9007199254740993"
+            ),
+            [": /exclusiveMinimum"]
+        );
+        assert!(validate(r#"{"minimum": 1.5}"#, "2").is_empty());
+    }
+
+    #[test]
+    fn percent_encoded_refs() {
+        let schema = r##"{"$ref": "#/$defs/a%20b%25", "$defs": {"a b%": {"type": "string"}}}"##;
+
+        assert_eq!(validate(schema, "1"), [": /$ref/type"]);
+
+        for reference in ["#/$defs/a%2", "#/$defs/a%zz", "#/$defs/%ff"] {
+            let schema = format!(r#"{{"$ref": "{}", "$defs": {{"a": true}}}}"#, reference);
+
+            assert_eq!(
+                Schema::new(&parse(&schema)).unwrap_err().typ,
+                SchemaErrTyp::UnresolvedRef {
+                    reference: reference.to_owned()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn ref_targets() {
+        // `x` is not a keyword, the pattern is only reached through the
+        // reference.
+        let schema = r##"{"$ref": "#/x", "x": {"pattern": "^a"}}"##;
+
+        assert!(validate(schema, r#""ab""#).is_empty());
+        assert_eq!(validate(schema, r#""ba""#), [": /$ref/pattern"]);
+
+        let schema = r##"{
+            "properties": {"a": {"$ref": "#/definitions/a"}},
+            "definitions": {"a": {"type": "array", "items": {"$ref": "#/definitions/b"}}, "b": {"enum": [1, 2]}}
+        }"##;
+
+        assert_eq!(
+            validate(schema, r#"{"a": [1, 3]}"#),
+            ["/a/1: /properties/a/$ref/items/$ref/enum"]
+        );
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        let cases = [
+            (
+                "[]",
+                SchemaErrTyp::NotASchema {
+                    received: NodeTyp::Array,
+                },
+                "",
+            ),
+            (
+                r#"{"properties": {"a": {"minLength": 1.5}}}"#,
+                SchemaErrTyp::InvalidKeyword {
+                    keyword: "minLength".to_owned(),
+                    expected: "a non-negative integer",
+                },
+                "/properties/a/minLength",
+            ),
+            (
+                r#"{"type": "text"}"#,
+                SchemaErrTyp::InvalidKeyword {
+                    keyword: "type".to_owned(),
+                    expected: "a type name or an array of type names",
+                },
+                "/type",
+            ),
+            (
+                r#"{"pattern": "("}"#,
+                SchemaErrTyp::InvalidPattern {
+                    pattern: "(".to_owned(),
+                },
+                "/pattern",
+            ),
+            (
+                r##"{"$ref": "#/$defs/missing"}"##,
+                SchemaErrTyp::UnresolvedRef {
+                    reference: "#/$defs/missing".to_owned(),
+                },
+                "/$ref",
+            ),
+            (
+                r#"{"type": 5}"#,
+                SchemaErrTyp::InvalidKeyword {
+                    keyword: "type".to_owned(),
+                    expected: "a type name or an array of type names",
+                },
+                "/type",
+            ),
+            (
+                r##"{"$ref": "#/x", "x": {"pattern": "("}}"##,
+                SchemaErrTyp::InvalidPattern {
+                    pattern: "(".to_owned(),
+                },
+                "/$ref/pattern",
+            ),
+            (
+                r##"{"items": {"$ref": "#/x/y"}, "x": {"y": {"type": 5}}}"##,
+                SchemaErrTyp::InvalidKeyword {
+                    keyword: "type".to_owned(),
+                    expected: "a type name or an array of type names",
+                },
+                "/items/$ref/type",
+            ),
+            (
+                r##"{"properties": {"a": {"$ref": "#/x"}}, "x": {"$ref": "#/missing"}}"##,
+                SchemaErrTyp::UnresolvedRef {
+                    reference: "#/missing".to_owned(),
+                },
+                "/properties/a/$ref/$ref",
+            ),
+            (
+                r#"{"definitions": {"a": {"minItems": 1.5}}}"#,
+                SchemaErrTyp::InvalidKeyword {
+                    keyword: "minItems".to_owned(),
+                    expected: "a non-negative integer",
+                },
+                "/definitions/a/minItems",
+            ),
+        ];
+
+        for (schema, typ, path) in cases {
+            let err = Schema::new(&parse(schema)).unwrap_err();
+
+            assert_eq!(
+                (err.typ, err.schema_path.as_str()),
+                (typ, path),
+                "{}",
+                schema
+            );
+        }
+    }
+}
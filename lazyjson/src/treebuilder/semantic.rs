@@ -0,0 +1,249 @@
+use std::{
+    hash::{Hash, Hasher},
+    mem,
+};
+
+use super::node::{KeyOrder, Node, NumberNode};
+
+/// How numbers are compared by [`Node::eq_semantic`].
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum NumberEq {
+    /// Compares the exact values, so `1`, `1.0` and `10e-1` are equal. No
+    /// precision is lost, `0.1` and `0.10000000000000001` differ.
+    Value,
+    /// Compares the lexemes, so `1` and `1.0` differ.
+    Lexeme,
+}
+
+/// How nodes are compared by [`Node::eq_semantic`]. Spans and provenance are
+/// always ignored.
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub struct EqConfig {
+    pub numbers: NumberEq,
+    /// Objects are only equal if their keys are in the same order (see
+    /// [`KeyOrder::Source`]). By default, objects are compared like maps.
+    pub ordered_objects: bool,
+}
+
+impl EqConfig {
+    pub const DEFAULT: EqConfig = EqConfig {
+        numbers: NumberEq::Value,
+        ordered_objects: false,
+    };
+}
+
+impl Node {
+    /// Compares the values of the nodes, ignoring their spans. The derived
+    /// `PartialEq` compares the spans as well, so the same document formatted
+    /// differently is not equal.
+    pub fn eq_semantic(&self, other: &Node, config: &EqConfig) -> bool {
+        match (self, other) {
+            (Node::Array(a), Node::Array(b)) => {
+                a.entries.len() == b.entries.len()
+                    && a.entries
+                        .iter()
+                        .zip(b.entries.iter())
+                        .all(|(a, b)| a.eq_semantic(b, config))
+            }
+            (Node::Bool(a), Node::Bool(b)) => a.val == b.val,
+            (Node::Null(_), Node::Null(_)) => true,
+            (Node::Number(a), Node::Number(b)) => match config.numbers {
+                NumberEq::Value => a.number().eq_val(&b.number()),
+                NumberEq::Lexeme => a.val == b.val,
+            },
+            (Node::Object(a), Node::Object(b)) if config.ordered_objects => {
+                let (a, b) = (
+                    a.entries_ordered(KeyOrder::Source),
+                    b.entries_ordered(KeyOrder::Source),
+                );
+
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((k, a), (l, b))| k == l && a.eq_semantic(b, config))
+            }
+            (Node::Object(a), Node::Object(b)) => {
+                a.entries.len() == b.entries.len()
+                    && a.entries.iter().all(|(key, a)| {
+                        b.entries.get(key).is_some_and(|b| a.eq_semantic(b, config))
+                    })
+            }
+            (Node::String(a), Node::String(b)) => a.val == b.val,
+            _ => false,
+        }
+    }
+    /// Hashes the value of the node, consistently with [`Node::eq_semantic`].
+    pub fn hash_semantic<H: Hasher>(&self, state: &mut H, config: &EqConfig) {
+        mem::discriminant(self).hash(state);
+
+        match self {
+            Node::Array(a) => {
+                a.entries.len().hash(state);
+
+                for entry in a.entries.iter() {
+                    entry.hash_semantic(state, config);
+                }
+            }
+            Node::Bool(b) => b.val.hash(state),
+            Node::Null(_) => {}
+            Node::Number(n) => hash_number(n, state, config),
+            Node::Object(o) => {
+                let entries = match config.ordered_objects {
+                    true => o.entries_ordered(KeyOrder::Source),
+                    false => o.entries_ordered(KeyOrder::Sorted),
+                };
+
+                entries.len().hash(state);
+
+                for (key, val) in entries {
+                    key.hash(state);
+                    val.hash_semantic(state, config);
+                }
+            }
+            Node::String(s) => s.val.hash(state),
+        }
+    }
+}
+
+fn hash_number<H: Hasher>(n: &NumberNode, state: &mut H, config: &EqConfig) {
+    match config.numbers {
+        NumberEq::Value => n.number().hash_val(state),
+        NumberEq::Lexeme => n.val.hash(state),
+    }
+}
+
+/// Wraps a node, so it is compared and hashed by [`Node::eq_semantic`] and
+/// [`Node::hash_semantic`], for example to use it as the key of a `HashMap`.
+#[derive(Debug, Clone, Copy)]
+pub struct Semantic<'a> {
+    pub node: &'a Node,
+    pub config: &'a EqConfig,
+}
+
+impl<'a> Semantic<'a> {
+    /// Uses [`EqConfig::DEFAULT`].
+    pub fn new(node: &'a Node) -> Semantic<'a> {
+        Semantic {
+            node,
+            config: &EqConfig::DEFAULT,
+        }
+    }
+    pub fn with_config(node: &'a Node, config: &'a EqConfig) -> Semantic<'a> {
+        Semantic { node, config }
+    }
+}
+
+/// Only the config of `self` is used, wrappers with different configs should
+/// not be mixed.
+impl PartialEq for Semantic<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node.eq_semantic(other.node, self.config)
+    }
+}
+
+impl Eq for Semantic<'_> {}
+
+impl Hash for Semantic<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.node.hash_semantic(state, self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{hash_map::DefaultHasher, HashSet};
+
+    use crate::treebuilder::testing::parse;
+
+    use super::*;
+
+    fn hash(node: &Node, config: &EqConfig) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        node.hash_semantic(&mut hasher, config);
+
+        hasher.finish()
+    }
+
+    /// Asserts that the nodes are equal and have the same hash, or neither.
+    fn assert_eq_semantic(a: &str, b: &str, config: &EqConfig, expected: bool) {
+        let (a, b) = (parse(a), parse(b));
+
+        assert_eq!(a.eq_semantic(&b, config), expected, "{:?} {:?}", a, b);
+        assert_eq!(
+            hash(&a, config) == hash(&b, config),
+            expected,
+            "{:?} {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn spans_are_ignored() {
+        let (a, b) = (
+            parse(r#"{"a": [1, true, null]}"#),
+            parse(r#"[0, {"a": [1, true, null]}]"#),
+        );
+        let config = &EqConfig::DEFAULT;
+
+        assert_ne!(a, b[1]);
+        assert!(a.eq_semantic(&b[1], config));
+        assert_eq!(hash(&a, config), hash(&b[1], config));
+    }
+
+    #[test]
+    fn numbers() {
+        let lexeme = EqConfig {
+            numbers: NumberEq::Lexeme,
+            ..EqConfig::DEFAULT
+        };
+
+        assert_eq_semantic("[1, 2]", "[1.0, 2.00]", &EqConfig::DEFAULT, true);
+        assert_eq_semantic("[1, 2]", "[1.0, 2.00]", &lexeme, false);
+        assert_eq_semantic("[1.5]", "[1.5]", &lexeme, true);
+    }
+
+    #[test]
+    fn object_order() {
+        let ordered = EqConfig {
+            ordered_objects: true,
+            ..EqConfig::DEFAULT
+        };
+        let (a, b) = (r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#);
+
+        assert_eq_semantic(a, b, &EqConfig::DEFAULT, true);
+        assert_eq_semantic(a, b, &ordered, false);
+        assert_eq_semantic(a, a, &ordered, true);
+
+        // The value of `a` has the span of the variable definition.
+        let c = r#"{let x = 1, "b": 2, "a": x}"#;
+
+        assert_eq_semantic(c, b, &ordered, true);
+        assert_eq_semantic(c, a, &ordered, false);
+    }
+
+    #[test]
+    fn different_values() {
+        for (a, b) in [
+            ("[1, 2]", "[2, 1]"),
+            (r#"{"a": 1}"#, r#"{"a": 1, "b": 1}"#),
+            (r#""1""#, "1"),
+            ("[[]]", "[{}]"),
+        ] {
+            assert_eq_semantic(a, b, &EqConfig::DEFAULT, false);
+        }
+    }
+
+    #[test]
+    fn dedup() {
+        let node = parse(r#"[{"a": 1}, {"a": 1.0}, { "a" : 1 }, {"a": 2}]"#);
+        let entries = node.as_array().unwrap();
+
+        let unique = entries
+            .iter()
+            .map(Semantic::new)
+            .collect::<HashSet<Semantic>>();
+
+        assert_eq!(unique.len(), 2);
+    }
+}
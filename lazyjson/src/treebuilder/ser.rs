@@ -0,0 +1,87 @@
+use serde::{ser, Serialize, Serializer};
+
+use super::node::{Node, NumberNode};
+
+/// Objects are serialized in sorted key order. Integers are serialized as an
+/// `i64`, `u64` or `i128` (the first one that fits), all other numbers as the
+/// nearest `f64`. Numbers that fit into none of them (`1e400`) are rejected.
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Node::Array(a) => serializer.collect_seq(a.entries.iter()),
+            Node::Bool(b) => serializer.serialize_bool(b.val),
+            Node::Null(_) => serializer.serialize_unit(),
+            Node::Number(n) => serialize_number(n, serializer),
+            Node::Object(o) => {
+                let mut entries = o.entries.iter().collect::<Vec<(&String, &Node)>>();
+                entries.sort_by_key(|(key, _)| *key);
+
+                serializer.collect_map(entries)
+            }
+            Node::String(s) => serializer.serialize_str(&s.val),
+        }
+    }
+}
+
+fn serialize_number<S: Serializer>(n: &NumberNode, serializer: S) -> Result<S::Ok, S::Error> {
+    let number = n.number();
+
+    if number.is_integer() {
+        if let Ok(n) = number.to_i64() {
+            return serializer.serialize_i64(n);
+        }
+
+        if let Ok(n) = number.to_u64() {
+            return serializer.serialize_u64(n);
+        }
+
+        if let Ok(n) = number.to_i128() {
+            return serializer.serialize_i128(n);
+        }
+    }
+
+    match number.lexeme().parse::<f64>() {
+        Ok(n) if n.is_finite() => serializer.serialize_f64(n),
+        _ => Err(ser::Error::custom(format!(
+            "the number `{}` can not be serialized",
+            number
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+
+    use super::*;
+
+    #[test]
+    fn values() {
+        let node = json!({"b": [1, -2.5, null], "a": {"c": true, "d": "x"}});
+
+        assert_eq!(
+            serde_json::to_string(&node).unwrap(),
+            r#"{"a":{"c":true,"d":"x"},"b":[1,-2.5,null]}"#
+        );
+    }
+
+    #[test]
+    fn large_numbers() {
+        let node = json!([u64::MAX, i128::MIN, 1e300]);
+
+        assert_eq!(
+            serde_json::to_string(&node).unwrap(),
+            "[18446744073709551615,-170141183460469231731687303715884105728,1e300]"
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let node = Node::Number(NumberNode::synthetic("1e400".to_owned()));
+
+        assert_eq!(
+            serde_json::to_string(&node).unwrap_err().to_string(),
+            "the number `1e400` can not be serialized"
+        );
+    }
+}
@@ -1,9 +1,23 @@
-use crate::tokenizer::Token;
+use crate::tokenizer::{tokenize, Token};
+
+use super::{build, Config, Node};
+
+/// Parses the input with the default config. Panics if it is invalid or
+/// empty.
+pub fn parse(inp: &str) -> Node {
+    let toks = tokenize(inp, &Config::DEFAULT).unwrap();
+
+    build(&toks, &Config::DEFAULT).unwrap().unwrap()
+}
 
 pub fn new_delimiter(val: &str) -> Token {
     Token::new_delimiter(val, 0, 0)
 }
 
+pub fn new_ident(val: &str) -> Token {
+    Token::new_ident(val, 0, 0)
+}
+
 pub fn new_kwd(val: &str) -> Token {
     Token::new_kwd(val, 0, 0)
 }
@@ -3,9 +3,15 @@ use std::rc::Rc;
 use crate::{queue::Queue, tokenizer::Token};
 
 use super::{
-    array_consumer, error::TreebuilderErr, keyword_consumer, node::Node, number_consumer,
-    object_consumer, string_consumer, var_dict::VarDict,
-    variable_usage_consumer::variable_usage_consumer, Config,
+    array_consumer,
+    error::TreebuilderErr,
+    function_call_consumer::function_call_consumer,
+    keyword_consumer,
+    node::{ArrayNode, Node, ObjectNode},
+    number_consumer, object_consumer, string_consumer,
+    var_dict::VarDict,
+    variable_usage_consumer::variable_usage_consumer,
+    Config,
 };
 
 type Consumer =
@@ -26,6 +32,7 @@ pub fn value_consumer(
     let consumers: &[&Consumer] = &[
         &array_consumer,
         &keyword_consumer,
+        &function_call_consumer,
         &variable_usage_consumer,
         &number_consumer,
         &object_consumer,
@@ -43,13 +50,73 @@ pub fn value_consumer(
     Err(TreebuilderErr::new_not_a_val(inp.idx()))
 }
 
+/// Builds the values of arrays and objects. The consumers of arrays and objects
+/// only handle the tokens, so the same grammar can build a tree of [`Node`]s,
+/// or the nodes of an [`Arena`](super::Arena).
+pub(super) trait ValBuilder {
+    type Val;
+
+    /// Consumes a value, like [`value_consumer`].
+    fn val(
+        &mut self,
+        inp: &mut Queue<Token>,
+        var_dict: &Rc<VarDict>,
+        config: &Config,
+    ) -> Result<Option<Self::Val>, TreebuilderErr>;
+    /// Converts a node that was computed, like the entries of a comprehension.
+    /// The node was consumed from the tokens `from..to`.
+    fn node(&mut self, node: Node, from: usize, to: usize) -> Result<Self::Val, TreebuilderErr>;
+    fn arr(
+        &mut self,
+        entries: Vec<Self::Val>,
+        from: usize,
+        to: usize,
+    ) -> Result<Self::Val, TreebuilderErr>;
+    fn obj(
+        &mut self,
+        entries: Vec<(String, Self::Val)>,
+        from: usize,
+        to: usize,
+    ) -> Result<Self::Val, TreebuilderErr>;
+}
+
+/// Builds a tree of [`Node`]s.
+pub(super) struct NodeBuilder;
+
+impl ValBuilder for NodeBuilder {
+    type Val = Node;
+
+    fn val(
+        &mut self,
+        inp: &mut Queue<Token>,
+        var_dict: &Rc<VarDict>,
+        config: &Config,
+    ) -> Result<Option<Node>, TreebuilderErr> {
+        value_consumer(inp, var_dict, config)
+    }
+    fn node(&mut self, node: Node, _: usize, _: usize) -> Result<Node, TreebuilderErr> {
+        Ok(node)
+    }
+    fn arr(&mut self, entries: Vec<Node>, from: usize, to: usize) -> Result<Node, TreebuilderErr> {
+        Ok(ArrayNode::new(from, to, entries).into())
+    }
+    fn obj(
+        &mut self,
+        entries: Vec<(String, Node)>,
+        from: usize,
+        to: usize,
+    ) -> Result<Node, TreebuilderErr> {
+        Ok(ObjectNode::from_entries(from, to, entries).into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
     use crate::treebuilder::{
-        node::{ArrayNode, BoolNode, NullNode, NumberNode, ObjectNode, StringNode},
-        testing::{new_delimiter, new_kwd, new_num, new_str},
+        node::{ArrayNode, BoolNode, NullNode, NumberNode, ObjectNode, Provenance, StringNode},
+        testing::{new_delimiter, new_ident, new_kwd, new_num, new_str},
         value_consumer,
         var_dict::VarDict,
     };
@@ -129,14 +196,16 @@ mod tests {
 
     #[test]
     fn use_variable() {
-        let inp = &mut Queue::new(vec![new_kwd("variable")]);
+        let inp = &mut Queue::new(vec![new_ident("variable")]);
 
         let mut var_dict = VarDict::new();
         var_dict.insert("variable".into(), NullNode::new(0).into());
 
         assert_eq!(
             value_consumer(inp, &Rc::new(var_dict), &Config::DEFAULT),
-            Ok(Some(NullNode::new(0).into())),
+            Ok(Some(
+                Node::Null(NullNode::new(0)).with_provenance(Provenance::new("variable", 0, 0, 1))
+            )),
         )
     }
 }
@@ -2,9 +2,32 @@ use std::{collections::HashMap, rc::Rc};
 
 use super::Node;
 
+/// The definition of a variable (`let foo = ...`).
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct VarDef {
+    pub node: Node,
+    /// Index of the first token of the definition. **Inclusive.**
+    pub from: usize,
+    /// Index of the last token of the definition. **NON inclusive.**
+    pub to: usize,
+}
+
+impl VarDef {
+    pub fn new(from: usize, to: usize, node: Node) -> VarDef {
+        VarDef { node, from, to }
+    }
+}
+
+/// A scope of variables. Scopes are chained through their (shared) parents, so
+/// creating a nested scope does not copy any variables. The definitions are
+/// shared as well, so cloning a scope is shallow.
+///
+/// While a scope is being built, it is usually only borrowed by the nested
+/// consumers. It can therefore be extended in place with [`Rc::make_mut`],
+/// after the nested consumers are done with it.
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub struct VarDict {
-    dict: HashMap<String, Node>,
+    dict: HashMap<String, Rc<VarDef>>,
     parent: Option<Rc<VarDict>>,
 }
 
@@ -23,17 +46,29 @@ impl VarDict {
         dict
     }
 
+    /// Inserts a variable that has no definition in the source. The span of the
+    /// node is used as the definition span.
     pub fn insert(&mut self, key: String, node: Node) {
-        self.dict.insert(key, node);
+        let def = VarDef::new(node.from(), node.to(), node);
+
+        self.insert_def(key, def);
+    }
+
+    pub fn insert_def(&mut self, key: String, def: VarDef) {
+        self.dict.insert(key, Rc::new(def));
     }
 
     pub fn get(&self, key: &str) -> Option<&Node> {
-        if let Some(n) = self.dict.get(key) {
-            return Some(n);
+        self.get_def(key).map(|def| &def.node)
+    }
+
+    pub fn get_def(&self, key: &str) -> Option<&VarDef> {
+        if let Some(def) = self.dict.get(key) {
+            return Some(def.as_ref());
         }
 
         if let Some(parent) = &self.parent {
-            return parent.get(key);
+            return parent.get_def(key);
         }
 
         None
@@ -63,6 +98,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn definitions_can_be_inserted_and_retrieved() {
+        let mut dict = VarDict::new();
+
+        dict.insert_def(
+            "foo".to_string(),
+            VarDef::new(1, 5, NullNode::new(4).into()),
+        );
+
+        assert_eq!(
+            dict.get_def("foo"),
+            Some(&VarDef::new(1, 5, NullNode::new(4).into()))
+        );
+        assert_eq!(dict.get("foo"), Some(&NullNode::new(4).into()));
+    }
+
     #[test]
     fn queries_the_parent_var_dict() {
         let mut parent_dict = VarDict::new();
@@ -89,4 +140,17 @@ mod tests {
             Some(&StringNode::new(0, "bar".to_owned()).into())
         );
     }
+
+    #[test]
+    fn clones_share_definitions() {
+        let mut dict = VarDict::new();
+        dict.insert("foo".to_string(), NullNode::new(0).into());
+
+        let clone = dict.clone();
+
+        assert!(std::ptr::eq(
+            dict.get_def("foo").unwrap(),
+            clone.get_def("foo").unwrap()
+        ));
+    }
 }
@@ -5,13 +5,17 @@ use crate::{
     tokenizer::{Token, TokenType},
 };
 
-use super::{value_consumer, Config, Node, TreebuilderErr, VarDict};
+use super::{
+    builtins::get_builtin, value_consumer, var_dict::VarDef, Config, TreebuilderErr, VarDict,
+};
 
 pub fn variable_definition_consumer(
     inp: &mut Queue<Token>,
     parent_var_dict: &Rc<VarDict>,
     config: &Config,
-) -> Result<Option<(String, Node)>, TreebuilderErr> {
+) -> Result<Option<(String, VarDef)>, TreebuilderErr> {
+    let from = inp.idx();
+
     if !consume_var_kwd(inp) {
         return Ok(None);
     }
@@ -25,7 +29,7 @@ pub fn variable_definition_consumer(
         Some(_) => value_consumer(inp, parent_var_dict, config)?.unwrap(),
     };
 
-    Ok(Some((var_name, var_value)))
+    Ok(Some((var_name, VarDef::new(from, inp.idx(), var_value))))
 }
 
 /// Returns `true` if the variable keyword was found.
@@ -46,11 +50,14 @@ fn consume_var_name(inp: &mut Queue<Token>) -> Result<String, TreebuilderErr> {
         .peek()
         .ok_or(TreebuilderErr::new_not_var_name(inp.idx() - 1))?;
 
-    if t.typ == TokenType::KeywordLiteral {
-        return Ok(inp.next().unwrap().val.clone());
+    match t.typ {
+        TokenType::Identifier if get_builtin(&t.val).is_some() => {
+            Err(TreebuilderErr::new_shadowed_builtin(inp.idx()))
+        }
+        TokenType::Identifier => Ok(inp.next().unwrap().val.clone()),
+        TokenType::KeywordLiteral => Err(TreebuilderErr::new_reserved_var_name(inp.idx())),
+        _ => Err(TreebuilderErr::new_not_var_name(inp.idx())),
     }
-
-    Err(TreebuilderErr::new_not_var_name(inp.idx()))
 }
 
 /// Consumes the assignment operator.
@@ -75,8 +82,8 @@ mod tests {
     use crate::{
         tokenizer::Token,
         treebuilder::{
-            node::{NullNode, NumberNode, ObjectNode},
-            testing::{new_equal_assignment_op, new_kwd, new_num},
+            node::{Node, NullNode, NumberNode, ObjectNode, Provenance},
+            testing::{new_equal_assignment_op, new_ident, new_kwd, new_num},
         },
     };
 
@@ -115,9 +122,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn shadowed_builtin() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("let"),
+            new_ident("len"),
+            new_equal_assignment_op(),
+            new_num("1"),
+        ]);
+
+        assert_eq!(
+            variable_definition_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_shadowed_builtin(1)),
+        );
+    }
+
+    #[test]
+    fn reserved_variable_name() {
+        let inp = &mut Queue::new(vec![
+            new_kwd("let"),
+            new_kwd("true"),
+            new_equal_assignment_op(),
+            new_num("1"),
+        ]);
+
+        assert_eq!(
+            variable_definition_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
+            Err(TreebuilderErr::new_reserved_var_name(1)),
+        );
+    }
+
     #[test]
     fn missing_assignment_op() {
-        let inp = &mut Queue::new(vec![new_kwd("let"), new_kwd("num")]);
+        let inp = &mut Queue::new(vec![new_kwd("let"), new_ident("num")]);
 
         assert_eq!(
             variable_definition_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
@@ -127,7 +164,7 @@ mod tests {
 
     #[test]
     fn not_assignment_op() {
-        let inp = &mut Queue::new(vec![new_kwd("let"), new_kwd("num"), new_num("10")]);
+        let inp = &mut Queue::new(vec![new_kwd("let"), new_ident("num"), new_num("10")]);
 
         assert_eq!(
             variable_definition_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
@@ -139,7 +176,7 @@ mod tests {
     fn missing_value_composition() {
         let inp = &mut Queue::new(vec![
             new_kwd("let"),
-            new_kwd("foo"),
+            new_ident("foo"),
             new_equal_assignment_op(),
         ]);
 
@@ -153,7 +190,7 @@ mod tests {
     fn number_variable() {
         let inp = &mut Queue::new(vec![
             new_kwd("let"),
-            new_kwd("num"),
+            new_ident("num"),
             new_equal_assignment_op(),
             new_num("10"),
         ]);
@@ -162,7 +199,7 @@ mod tests {
             variable_definition_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
             Ok(Some((
                 "num".to_string(),
-                NumberNode::new(3, "10".to_owned()).into()
+                VarDef::new(0, 4, NumberNode::new(3, "10".to_owned()).into())
             ))),
         )
     }
@@ -171,7 +208,7 @@ mod tests {
     fn object_variable() {
         let inp = &mut Queue::new(vec![
             Token::new_kwd("let", 0, 0),
-            Token::new_kwd("obj", 0, 0),
+            Token::new_ident("obj", 0, 0),
             Token::new_equal_assignment_op(0),
             Token::new_delimiter("{", 0, 0),
             Token::new_delimiter("}", 0, 0),
@@ -181,7 +218,7 @@ mod tests {
             variable_definition_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
             Ok(Some((
                 "obj".to_string(),
-                ObjectNode::new(3, 5, HashMap::new(),).into()
+                VarDef::new(0, 5, ObjectNode::new(3, 5, HashMap::new()).into())
             )))
         )
     }
@@ -190,9 +227,9 @@ mod tests {
     fn can_use_variable_from_parent_var_dict() {
         let inp = &mut Queue::new(vec![
             Token::new_kwd("let", 0, 0),
-            Token::new_kwd("var", 0, 0),
+            Token::new_ident("var", 0, 0),
             Token::new_equal_assignment_op(0),
-            Token::new_kwd("parent_var", 0, 0),
+            Token::new_ident("parent_var", 0, 0),
         ]);
 
         let mut parent_var_dict = VarDict::new();
@@ -200,7 +237,19 @@ mod tests {
 
         assert_eq!(
             variable_definition_consumer(inp, &Rc::new(parent_var_dict), &Config::DEFAULT),
-            Ok(Some(("var".into(), NullNode::new(0).into())))
+            Ok(Some((
+                "var".into(),
+                VarDef::new(
+                    0,
+                    4,
+                    Node::Null(NullNode::new(0)).with_provenance(Provenance::new(
+                        "parent_var",
+                        3,
+                        0,
+                        1
+                    ))
+                ),
+            )))
         );
     }
 }
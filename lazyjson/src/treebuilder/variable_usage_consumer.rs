@@ -5,7 +5,7 @@ use crate::{
     tokenizer::{Token, TokenType},
 };
 
-use super::{var_dict::VarDict, Config, Node, TreebuilderErr};
+use super::{node::Provenance, var_dict::VarDict, Config, Node, TreebuilderErr};
 
 pub fn variable_usage_consumer(
     inp: &mut Queue<Token>,
@@ -14,14 +14,16 @@ pub fn variable_usage_consumer(
 ) -> Result<Option<Node>, TreebuilderErr> {
     let tok = inp.peek().unwrap();
 
-    if tok.typ != TokenType::KeywordLiteral {
+    if tok.typ != TokenType::Identifier {
         return Ok(None);
     }
 
-    if let Some(n) = var_dict.get(&tok.val) {
+    if let Some(def) = var_dict.get_def(&tok.val) {
+        let provenance = Provenance::new(&tok.val, inp.idx(), def.from, def.to);
+
         inp.next();
 
-        return Ok(Some(n.clone()));
+        return Ok(Some(def.node.clone().with_provenance(provenance)));
     }
 
     Err(TreebuilderErr::new_undeclared_variable(inp.idx()))
@@ -32,8 +34,8 @@ mod tests {
     use crate::{
         tokenizer::Token,
         treebuilder::{
-            node::BoolNode,
-            testing::{new_kwd, new_str},
+            node::{ArrayNode, BoolNode},
+            testing::{new_ident, new_kwd, new_str},
             var_dict::VarDict,
             TreebuilderErr,
         },
@@ -54,7 +56,7 @@ mod tests {
 
     #[test]
     fn unknown_variable_results_in_an_error_pos_0() {
-        let inp = &mut Queue::new(vec![new_kwd("undeclared_var")]);
+        let inp = &mut Queue::new(vec![new_ident("undeclared_var")]);
 
         assert_eq!(
             variable_usage_consumer(inp, &Rc::new(VarDict::new()), &Config::DEFAULT),
@@ -64,7 +66,7 @@ mod tests {
 
     #[test]
     fn unknown_variable_results_in_an_error_pos_1() {
-        let inp = &mut Queue::new(vec![new_kwd("null"), new_kwd("undeclared_var")]);
+        let inp = &mut Queue::new(vec![new_kwd("null"), new_ident("undeclared_var")]);
         inp.next();
 
         assert_eq!(
@@ -75,15 +77,31 @@ mod tests {
 
     #[test]
     fn known_variable_is_consumed_and_results_in_the_corresponding_node() {
-        let inp = &mut Queue::new(vec![new_kwd("foo")]);
+        let inp = &mut Queue::new(vec![new_ident("foo")]);
 
         let mut var_dict = VarDict::new();
         var_dict.insert("foo".into(), BoolNode::new(0, true).into());
 
         assert_eq!(
             variable_usage_consumer(inp, &Rc::new(var_dict), &Config::DEFAULT),
-            Ok(Some(BoolNode::new(0, true).into())),
+            Ok(Some(
+                Node::Bool(BoolNode::new(0, true)).with_provenance(Provenance::new("foo", 0, 0, 1))
+            )),
         );
         assert_eq!(inp.next(), None);
     }
+
+    #[test]
+    fn entries_of_the_variable_are_shared() {
+        let inp = &mut Queue::new(vec![new_ident("foo")]);
+
+        let arr = ArrayNode::new(0, 0, vec![BoolNode::new(0, true).into()]);
+        let mut var_dict = VarDict::new();
+        var_dict.insert("foo".into(), arr.clone().into());
+
+        match variable_usage_consumer(inp, &Rc::new(var_dict), &Config::DEFAULT) {
+            Ok(Some(Node::Array(used))) => assert!(Rc::ptr_eq(&used.entries, &arr.entries)),
+            res => panic!("expected an array, received: {:?}", res),
+        }
+    }
 }
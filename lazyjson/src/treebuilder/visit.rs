@@ -0,0 +1,357 @@
+//! Traversal of [`Node`] trees.
+//!
+//! [`Visit`] walks a tree by reference, [`VisitMut`] by mutable reference, and
+//! [`Fold`] takes the tree by value and rebuilds it. Every method has a default
+//! implementation that walks into the children (using the `walk_*` functions of
+//! this module), so implementations only override the node types they care
+//! about. An overriding method that still wants to visit the children calls the
+//! matching `walk_*` function.
+//!
+//! All methods receive the path from the root of the traversal to the current
+//! node. The entries of objects are visited in the order of their keys.
+
+use std::rc::Rc;
+
+use super::node::{
+    ArrayNode, BoolNode, Node, NullNode, NumberNode, ObjectNode, PathSegment, StringNode,
+};
+
+pub trait Visit {
+    fn visit_node(&mut self, node: &Node, path: &mut Vec<PathSegment>) {
+        walk_node(self, node, path);
+    }
+    fn visit_array(&mut self, arr: &ArrayNode, path: &mut Vec<PathSegment>) {
+        walk_array(self, arr, path);
+    }
+    fn visit_bool(&mut self, _bl: &BoolNode, _path: &mut Vec<PathSegment>) {}
+    fn visit_null(&mut self, _null: &NullNode, _path: &mut Vec<PathSegment>) {}
+    fn visit_number(&mut self, _num: &NumberNode, _path: &mut Vec<PathSegment>) {}
+    fn visit_object(&mut self, obj: &ObjectNode, path: &mut Vec<PathSegment>) {
+        walk_object(self, obj, path);
+    }
+    fn visit_string(&mut self, _str: &StringNode, _path: &mut Vec<PathSegment>) {}
+}
+
+/// Calls the `visit_*` method that matches the type of the node.
+pub fn walk_node<V: Visit + ?Sized>(v: &mut V, node: &Node, path: &mut Vec<PathSegment>) {
+    match node {
+        Node::Array(a) => v.visit_array(a, path),
+        Node::Bool(b) => v.visit_bool(b, path),
+        Node::Null(n) => v.visit_null(n, path),
+        Node::Number(n) => v.visit_number(n, path),
+        Node::Object(o) => v.visit_object(o, path),
+        Node::String(s) => v.visit_string(s, path),
+    }
+}
+
+/// Visits the entries of the array.
+pub fn walk_array<V: Visit + ?Sized>(v: &mut V, arr: &ArrayNode, path: &mut Vec<PathSegment>) {
+    for (i, e) in arr.entries.iter().enumerate() {
+        path.push(PathSegment::Index(i));
+        v.visit_node(e, path);
+        path.pop();
+    }
+}
+
+/// Visits the entries of the object, in the order of their keys.
+pub fn walk_object<V: Visit + ?Sized>(v: &mut V, obj: &ObjectNode, path: &mut Vec<PathSegment>) {
+    for key in get_sorted_keys(obj) {
+        path.push(PathSegment::Key(key.clone()));
+        v.visit_node(&obj.entries[&key], path);
+        path.pop();
+    }
+}
+
+/// Same as [`Visit`], but the nodes can be modified in place. Entries that are
+/// shared with other nodes are copied when they are walked (see
+/// [`ArrayNode::entries`]).
+pub trait VisitMut {
+    fn visit_node_mut(&mut self, node: &mut Node, path: &mut Vec<PathSegment>) {
+        walk_node_mut(self, node, path);
+    }
+    fn visit_array_mut(&mut self, arr: &mut ArrayNode, path: &mut Vec<PathSegment>) {
+        walk_array_mut(self, arr, path);
+    }
+    fn visit_bool_mut(&mut self, _bl: &mut BoolNode, _path: &mut Vec<PathSegment>) {}
+    fn visit_null_mut(&mut self, _null: &mut NullNode, _path: &mut Vec<PathSegment>) {}
+    fn visit_number_mut(&mut self, _num: &mut NumberNode, _path: &mut Vec<PathSegment>) {}
+    fn visit_object_mut(&mut self, obj: &mut ObjectNode, path: &mut Vec<PathSegment>) {
+        walk_object_mut(self, obj, path);
+    }
+    fn visit_string_mut(&mut self, _str: &mut StringNode, _path: &mut Vec<PathSegment>) {}
+}
+
+/// Calls the `visit_*_mut` method that matches the type of the node.
+pub fn walk_node_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    node: &mut Node,
+    path: &mut Vec<PathSegment>,
+) {
+    match node {
+        Node::Array(a) => v.visit_array_mut(a, path),
+        Node::Bool(b) => v.visit_bool_mut(b, path),
+        Node::Null(n) => v.visit_null_mut(n, path),
+        Node::Number(n) => v.visit_number_mut(n, path),
+        Node::Object(o) => v.visit_object_mut(o, path),
+        Node::String(s) => v.visit_string_mut(s, path),
+    }
+}
+
+/// Visits the entries of the array.
+pub fn walk_array_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    arr: &mut ArrayNode,
+    path: &mut Vec<PathSegment>,
+) {
+    for (i, e) in Rc::make_mut(&mut arr.entries).iter_mut().enumerate() {
+        path.push(PathSegment::Index(i));
+        v.visit_node_mut(e, path);
+        path.pop();
+    }
+}
+
+/// Visits the entries of the object, in the order of their keys.
+pub fn walk_object_mut<V: VisitMut + ?Sized>(
+    v: &mut V,
+    obj: &mut ObjectNode,
+    path: &mut Vec<PathSegment>,
+) {
+    let keys = get_sorted_keys(obj);
+    let entries = Rc::make_mut(&mut obj.entries);
+
+    for key in keys {
+        let e = entries.get_mut(&key).unwrap();
+
+        path.push(PathSegment::Key(key));
+        v.visit_node_mut(e, path);
+        path.pop();
+    }
+}
+
+/// Rebuilds a tree. Each method returns the node that replaces the given one,
+/// which can be of a different type (for example to turn numbers into strings).
+pub trait Fold {
+    fn fold_node(&mut self, node: Node, path: &mut Vec<PathSegment>) -> Node {
+        fold_node(self, node, path)
+    }
+    fn fold_array(&mut self, arr: ArrayNode, path: &mut Vec<PathSegment>) -> Node {
+        fold_array(self, arr, path)
+    }
+    fn fold_bool(&mut self, bl: BoolNode, _path: &mut Vec<PathSegment>) -> Node {
+        Node::Bool(bl)
+    }
+    fn fold_null(&mut self, null: NullNode, _path: &mut Vec<PathSegment>) -> Node {
+        Node::Null(null)
+    }
+    fn fold_number(&mut self, num: NumberNode, _path: &mut Vec<PathSegment>) -> Node {
+        Node::Number(num)
+    }
+    fn fold_object(&mut self, obj: ObjectNode, path: &mut Vec<PathSegment>) -> Node {
+        fold_object(self, obj, path)
+    }
+    fn fold_string(&mut self, str: StringNode, _path: &mut Vec<PathSegment>) -> Node {
+        Node::String(str)
+    }
+}
+
+/// Calls the `fold_*` method that matches the type of the node.
+pub fn fold_node<F: Fold + ?Sized>(f: &mut F, node: Node, path: &mut Vec<PathSegment>) -> Node {
+    match node {
+        Node::Array(a) => f.fold_array(a, path),
+        Node::Bool(b) => f.fold_bool(b, path),
+        Node::Null(n) => f.fold_null(n, path),
+        Node::Number(n) => f.fold_number(n, path),
+        Node::Object(o) => f.fold_object(o, path),
+        Node::String(s) => f.fold_string(s, path),
+    }
+}
+
+/// Folds the entries of the array, and keeps the span and provenance of the
+/// array itself.
+pub fn fold_array<F: Fold + ?Sized>(
+    f: &mut F,
+    mut arr: ArrayNode,
+    path: &mut Vec<PathSegment>,
+) -> Node {
+    let entries = Rc::try_unwrap(arr.entries).unwrap_or_else(|shared| (*shared).clone());
+
+    arr.entries = Rc::new(
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, e)| {
+                path.push(PathSegment::Index(i));
+                let e = f.fold_node(e, path);
+                path.pop();
+
+                e
+            })
+            .collect(),
+    );
+
+    Node::Array(arr)
+}
+
+/// Folds the entries of the object, in the order of their keys, and keeps the
+/// span and provenance of the object itself.
+pub fn fold_object<F: Fold + ?Sized>(
+    f: &mut F,
+    mut obj: ObjectNode,
+    path: &mut Vec<PathSegment>,
+) -> Node {
+    let mut entries = Rc::try_unwrap(obj.entries)
+        .unwrap_or_else(|shared| (*shared).clone())
+        .into_iter()
+        .collect::<Vec<(String, Node)>>();
+
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    obj.entries = Rc::new(
+        entries
+            .into_iter()
+            .map(|(key, e)| {
+                path.push(PathSegment::Key(key));
+                let e = f.fold_node(e, path);
+
+                match path.pop() {
+                    Some(PathSegment::Key(key)) => (key, e),
+                    _ => unreachable!(),
+                }
+            })
+            .collect(),
+    );
+
+    Node::Object(obj)
+}
+
+fn get_sorted_keys(obj: &ObjectNode) -> Vec<String> {
+    let mut keys = obj.entries.keys().cloned().collect::<Vec<String>>();
+    keys.sort();
+
+    keys
+}
+
+impl Node {
+    /// Walks the tree with the visitor, starting with an empty path.
+    pub fn visit<V: Visit + ?Sized>(&self, v: &mut V) {
+        v.visit_node(self, &mut Vec::new());
+    }
+    /// Walks the tree with the visitor, starting with an empty path.
+    pub fn visit_mut<V: VisitMut + ?Sized>(&mut self, v: &mut V) {
+        v.visit_node_mut(self, &mut Vec::new());
+    }
+    /// Rebuilds the tree with the folder, starting with an empty path.
+    pub fn fold<F: Fold + ?Sized>(self, f: &mut F) -> Node {
+        f.fold_node(self, &mut Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+
+    use super::*;
+
+    /// Collects the paths of all numbers.
+    struct NumberPaths(Vec<Vec<PathSegment>>);
+
+    impl Visit for NumberPaths {
+        fn visit_number(&mut self, _: &NumberNode, path: &mut Vec<PathSegment>) {
+            self.0.push(path.clone());
+        }
+    }
+
+    #[test]
+    fn visit_tracks_paths() {
+        let node = json!({"b": [1, {"c": 2}], "a": 3, "d": "4"});
+        let v = &mut NumberPaths(Vec::new());
+
+        node.visit(v);
+
+        assert_eq!(
+            v.0,
+            vec![
+                vec![PathSegment::Key("a".to_owned())],
+                vec![PathSegment::Key("b".to_owned()), PathSegment::Index(0)],
+                vec![
+                    PathSegment::Key("b".to_owned()),
+                    PathSegment::Index(1),
+                    PathSegment::Key("c".to_owned()),
+                ],
+            ]
+        );
+    }
+
+    /// Counts the arrays, without descending into them.
+    struct ShallowArrayCount(usize);
+
+    impl Visit for ShallowArrayCount {
+        fn visit_array(&mut self, _: &ArrayNode, _: &mut Vec<PathSegment>) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn overriding_stops_the_walk() {
+        let v = &mut ShallowArrayCount(0);
+
+        json!({"a": [[1]], "b": []}).visit(v);
+
+        assert_eq!(v.0, 2);
+    }
+
+    struct Upper;
+
+    impl VisitMut for Upper {
+        fn visit_string_mut(&mut self, str: &mut StringNode, _: &mut Vec<PathSegment>) {
+            str.val = str.val.to_uppercase();
+        }
+    }
+
+    #[test]
+    fn visit_mut_does_not_affect_clones() {
+        let original = json!({"a": ["x", {"b": "y"}], "c": 1});
+        let mut modified = original.clone();
+
+        modified.visit_mut(&mut Upper);
+
+        assert_eq!(modified, json!({"a": ["X", {"b": "Y"}], "c": 1}));
+        assert_eq!(original, json!({"a": ["x", {"b": "y"}], "c": 1}));
+    }
+
+    /// Replaces numbers with their path as a JSON Pointer.
+    struct NumbersToPointers;
+
+    impl Fold for NumbersToPointers {
+        fn fold_number(&mut self, _: NumberNode, path: &mut Vec<PathSegment>) -> Node {
+            let pointer = path
+                .iter()
+                .map(|segment| match segment {
+                    PathSegment::Index(i) => format!("/{}", i),
+                    PathSegment::Key(key) => format!("/{}", key),
+                })
+                .collect::<String>();
+
+            pointer.into()
+        }
+    }
+
+    #[test]
+    fn fold_can_change_types() {
+        assert_eq!(
+            json!({"a": [1, true], "b": {"c": 2}}).fold(&mut NumbersToPointers),
+            json!({"a": ["/a/0", true], "b": {"c": "/b/c"}})
+        );
+    }
+
+    #[test]
+    fn fold_keeps_spans() {
+        let node: Node =
+            ArrayNode::new(3, 7, vec![NumberNode::new(4, "1".to_owned()).into()]).into();
+
+        let folded = node.fold(&mut NumbersToPointers);
+
+        assert_eq!((folded.from(), folded.to()), (3, 7));
+        assert!(folded[0].is_synthetic());
+    }
+}